@@ -2,7 +2,98 @@ complete -c zellij -n "__fish_use_subcommand" -s s -l split -d 'Send "split (dir
 complete -c zellij -n "__fish_use_subcommand" -s o -l open-file -d 'Send "open file in new pane" to active zellij session'
 complete -c zellij -n "__fish_use_subcommand" -l max-panes -d 'Maximum panes on screen, caution: opening more panes will close old ones'
 complete -c zellij -n "__fish_use_subcommand" -s l -l layout -d 'Path to a layout yaml file'
+complete -c zellij -n "__fish_use_subcommand" -s S -l session -d 'Name of the session to create, or to attach to if it\'s already running. The one-shot commands above are sent to this session'
+complete -c zellij -n "__fish_use_subcommand" -l size-policy -d 'Which of the attached clients a new session is sized after: the smallest one, or the one that was used last. Larger clients see the unused area filled in' -r -f -a "smallest latest"
+complete -c zellij -n "__fish_use_subcommand" -l resurrect -d 'Resurrect a session from its last save (eg. after a reboot), given its name or the path of the file it was saved to. It keeps its name, unless it\'s renamed with --session'
+complete -c zellij -n "__fish_use_subcommand" -l scrollback-lines-to-save -d 'The most lines of scrollback saved for each pane, with --save-scrollback'
 complete -c zellij -n "__fish_use_subcommand" -s m -l move-focus -d 'Send "move focused pane" to active zellij session'
 complete -c zellij -n "__fish_use_subcommand" -s d -l debug
+complete -c zellij -n "__fish_use_subcommand" -s a -l attach -d 'Attach to a running zellij session (the only one running, unless --session is given)'
+complete -c zellij -n "__fish_use_subcommand" -l force -d 'Detach every other client from the session when attaching to it'
+complete -c zellij -n "__fish_use_subcommand" -l read-only -d 'Attach to a running zellij session as a spectator, whose input is ignored except for the detach key (implies --attach)'
+complete -c zellij -n "__fish_use_subcommand" -l save-scrollback -d 'Save the scrollback of every pane along with the session, to be shown in the panes again when the session is resurrected'
 complete -c zellij -n "__fish_use_subcommand" -s h -l help -d 'Prints help information'
 complete -c zellij -n "__fish_use_subcommand" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_use_subcommand" -f -a "list-sessions" -d 'List the running sessions, cleaning up after the ones that died'
+complete -c zellij -n "__fish_use_subcommand" -f -a "kill-session" -d 'Kill a session, closing all of its panes'
+complete -c zellij -n "__fish_use_subcommand" -f -a "action" -d 'Send an action to a running session (the one given with --session, the one we\'re in or else the only one running), as if its key was pressed in the client that was used last'
+complete -c zellij -n "__fish_use_subcommand" -f -a "dump-screen" -d 'Print the contents of a pane of a running session, by default the focused pane of the client that was used last'
+complete -c zellij -n "__fish_use_subcommand" -f -a "query" -d 'Print the tabs of a running session and their panes as JSON'
+complete -c zellij -n "__fish_use_subcommand" -f -a "run" -d 'Open a new pane running a command in a running session, next to the focused pane of the client that was used last, eg. `zellij run --direction down -- cargo test`'
+complete -c zellij -n "__fish_use_subcommand" -f -a "help" -d 'Prints this message or the help of the given subcommand(s)'
+complete -c zellij -n "__fish_seen_subcommand_from list-sessions" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from list-sessions" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from kill-session" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from kill-session" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from action" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from action" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "quit" -d 'Quit the session'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "write" -d 'Write bytes to the focused pane, given as numbers (eg. `27` for ESC)'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "write-chars" -d 'Write characters to the focused pane'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "switch-mode" -d 'Switch to an input mode (normal, command, resize, pane, tab or scroll)'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "resize" -d 'Resize the focused pane'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "focus-next-pane" -d 'Move the focus to the next pane'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "move-focus" -d 'Move the focus to the pane next to the focused one'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "scroll-up" -d 'Scroll up in the focused pane'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "scroll-down" -d 'Scroll down in the focused pane'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "toggle-fullscreen" -d 'Toggle the focused pane between fullscreen and its place in the layout'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "new-pane" -d 'Open a new pane next to the focused one, or in the largest space available if no direction is given'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "close-pane" -d 'Close the focused pane'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "new-tab" -d 'Open a new tab'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "go-to-next-tab" -d 'Go to the next tab'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "go-to-previous-tab" -d 'Go to the previous tab'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "close-tab" -d 'Close the current tab'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "detach" -d 'Detach the client that was used last from the session'
+complete -c zellij -n "__fish_seen_subcommand_from action" -f -a "help" -d 'Prints this message or the help of the given subcommand(s)'
+complete -c zellij -n "__fish_seen_subcommand_from quit" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from quit" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from write" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from write" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from write-chars" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from write-chars" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from switch-mode" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from switch-mode" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from resize" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from resize" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from focus-next-pane" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from focus-next-pane" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from move-focus" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from move-focus" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from scroll-up" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from scroll-up" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from scroll-down" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from scroll-down" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from toggle-fullscreen" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from toggle-fullscreen" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from new-pane" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from new-pane" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from close-pane" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from close-pane" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from new-tab" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from new-tab" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from go-to-next-tab" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from go-to-next-tab" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from go-to-previous-tab" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from go-to-previous-tab" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from close-tab" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from close-tab" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from detach" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from detach" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from help" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from help" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from dump-screen" -l pane-id -d 'Id of the terminal pane to print instead of the focused one'
+complete -c zellij -n "__fish_seen_subcommand_from dump-screen" -l full -d 'Print the whole scrollback of the pane instead of what\'s in view'
+complete -c zellij -n "__fish_seen_subcommand_from dump-screen" -l ansi -d 'Keep the colors and styles of the text, as ANSI escape codes'
+complete -c zellij -n "__fish_seen_subcommand_from dump-screen" -l join-wrapped -d 'Join lines that were wrapped to fit in the pane back together'
+complete -c zellij -n "__fish_seen_subcommand_from dump-screen" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from dump-screen" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from query" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from query" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from run" -s d -l direction -d 'Where to open the pane, relative to the focused one. By default it goes in the largest space available' -r -f -a "left right up down"
+complete -c zellij -n "__fish_seen_subcommand_from run" -l cwd -d 'Directory to run the command in, by default the current one'
+complete -c zellij -n "__fish_seen_subcommand_from run" -s e -l env -d 'Environment variable to set for the command, as NAME=VALUE (can be repeated)'
+complete -c zellij -n "__fish_seen_subcommand_from run" -l hold -d 'Keep the pane open after the command exits, instead of closing it'
+complete -c zellij -n "__fish_seen_subcommand_from run" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from run" -s V -l version -d 'Prints version information'
+complete -c zellij -n "__fish_seen_subcommand_from help" -s h -l help -d 'Prints help information'
+complete -c zellij -n "__fish_seen_subcommand_from help" -s V -l version -d 'Prints version information'
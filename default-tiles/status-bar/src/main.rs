@@ -22,47 +22,79 @@ impl Display for LinePart {
     }
 }
 
-fn prefix(help: &Help) -> LinePart {
-    let prefix_text = " Zellij ";
-    let part = match &help.mode {
-        InputMode::Command => {
-            let prefix = prefix_text.bold().white().on_black();
-            let separator = ARROW_SEPARATOR.black().on_magenta();
-            format!("{}{}", prefix, separator)
-        }
-        InputMode::Normal => {
-            let prefix = prefix_text.bold().white().on_black();
-            let separator = ARROW_SEPARATOR.black().on_green();
-            format!("{}{}", prefix, separator)
-        }
-        _ => {
-            let prefix = prefix_text.bold().white().on_black();
-            let separator = ARROW_SEPARATOR.black().on_magenta();
-            format!("{}{}", prefix, separator)
-        }
+// draws `text` in colors of the theme, which resets them after it like `colored` does
+fn paint(text: &str, fg: PaletteColor, bg: PaletteColor, bold: bool) -> String {
+    let bold = if bold { "\u{1b}[1m" } else { "" };
+    format!("{}{}{}{}\u{1b}[0m", bold, fg.fg(), bg.bg(), text)
+}
+
+fn prefix(help: &Help, theme: &Theme) -> LinePart {
+    // only worth mentioning when the session is shared
+    let prefix_text = if help.readers + help.writers > 1 {
+        format!(
+            " Zellij ({} {}, {} {}) ",
+            help.writers,
+            if help.writers == 1 {
+                "writer"
+            } else {
+                "writers"
+            },
+            help.readers,
+            if help.readers == 1 {
+                "reader"
+            } else {
+                "readers"
+            },
+        )
+    } else {
+        " Zellij ".to_string()
     };
+    let separator = paint(
+        ARROW_SEPARATOR,
+        theme.bg,
+        theme.mode_color(&help.mode),
+        false,
+    );
+    let part = format!(
+        "{}{}",
+        paint(&prefix_text, theme.fg, theme.bg, true),
+        separator
+    );
     let len = prefix_text.chars().count() + ARROW_SEPARATOR.chars().count();
     LinePart { part, len }
 }
 
-fn key_path(help: &Help) -> LinePart {
+// the key a mode is entered with from command mode, shown after the superkey
+fn mode_shortcut(mode: &InputMode) -> Option<String> {
+    match mode {
+        InputMode::Resize => Some("r ".to_string()),
+        InputMode::Pane => Some("p ".to_string()),
+        InputMode::Tab => Some("t ".to_string()),
+        InputMode::Scroll => Some("s ".to_string()),
+        // modes defined in the config file don't have a shortcut, so they're shown by name
+        InputMode::Custom(name) => Some(format!("{} ", name)),
+        InputMode::Normal | InputMode::Command | InputMode::Exiting => None,
+    }
+}
+
+fn key_path(help: &Help, theme: &Theme) -> LinePart {
     let superkey_text = "<Ctrl-g> ";
-    let (part, len) = match &help.mode {
-        InputMode::Command => {
-            let key_path = superkey_text.bold().on_magenta();
-            let first_separator = ARROW_SEPARATOR.magenta().on_black();
+    let mode_color = theme.mode_color(&help.mode);
+    let (part, len) = match (&help.mode, mode_shortcut(&help.mode)) {
+        (InputMode::Command, _) => {
+            let key_path = paint(superkey_text, theme.fg, mode_color, true);
+            let first_separator = paint(ARROW_SEPARATOR, mode_color, theme.bg, false);
             let len = superkey_text.chars().count()
                 + ARROW_SEPARATOR.chars().count()
                 + ARROW_SEPARATOR.chars().count();
             (format!("{}{}", key_path, first_separator), len)
         }
-        InputMode::Resize => {
-            let mode_shortcut_text = "r ";
-            let superkey = superkey_text.bold().on_magenta();
-            let first_superkey_separator = ARROW_SEPARATOR.magenta().on_black();
-            let second_superkey_separator = ARROW_SEPARATOR.black().on_magenta();
-            let mode_shortcut = mode_shortcut_text.white().bold().on_magenta();
-            let mode_shortcut_separator = ARROW_SEPARATOR.magenta().on_black();
+        (_, Some(mode_shortcut_text)) => {
+            let superkey = paint(superkey_text, theme.fg, mode_color, true);
+            let first_superkey_separator = paint(ARROW_SEPARATOR, mode_color, theme.bg, false);
+            let second_superkey_separator = paint(ARROW_SEPARATOR, theme.bg, mode_color, false);
+            let mode_shortcut = paint(&mode_shortcut_text, theme.fg, mode_color, true);
+            let mode_shortcut_separator = paint(ARROW_SEPARATOR, mode_color, theme.bg, false);
             let len = superkey_text.chars().count()
                 + ARROW_SEPARATOR.chars().count()
                 + ARROW_SEPARATOR.chars().count()
@@ -80,81 +112,9 @@ fn key_path(help: &Help) -> LinePart {
                 len,
             )
         }
-        InputMode::Pane => {
-            let mode_shortcut_text = "p ";
-            let superkey = superkey_text.bold().on_magenta();
-            let first_superkey_separator = ARROW_SEPARATOR.magenta().on_black();
-            let second_superkey_separator = ARROW_SEPARATOR.black().on_magenta();
-            let mode_shortcut = mode_shortcut_text.white().bold().on_magenta();
-            let mode_shortcut_separator = ARROW_SEPARATOR.magenta().on_black();
-            let len = superkey_text.chars().count()
-                + ARROW_SEPARATOR.chars().count()
-                + ARROW_SEPARATOR.chars().count()
-                + mode_shortcut_text.chars().count()
-                + ARROW_SEPARATOR.chars().count();
-            (
-                format!(
-                    "{}{}{}{}{}",
-                    superkey,
-                    first_superkey_separator,
-                    second_superkey_separator,
-                    mode_shortcut,
-                    mode_shortcut_separator
-                ),
-                len,
-            )
-        }
-        InputMode::Tab => {
-            let mode_shortcut_text = "t ";
-            let superkey = superkey_text.bold().on_magenta();
-            let first_superkey_separator = ARROW_SEPARATOR.magenta().on_black();
-            let second_superkey_separator = ARROW_SEPARATOR.black().on_magenta();
-            let mode_shortcut = mode_shortcut_text.white().bold().on_magenta();
-            let mode_shortcut_separator = ARROW_SEPARATOR.magenta().on_black();
-            let len = superkey_text.chars().count()
-                + ARROW_SEPARATOR.chars().count()
-                + ARROW_SEPARATOR.chars().count()
-                + mode_shortcut_text.chars().count()
-                + ARROW_SEPARATOR.chars().count();
-            (
-                format!(
-                    "{}{}{}{}{}",
-                    superkey,
-                    first_superkey_separator,
-                    second_superkey_separator,
-                    mode_shortcut,
-                    mode_shortcut_separator
-                ),
-                len,
-            )
-        }
-        InputMode::Scroll => {
-            let mode_shortcut_text = "s ";
-            let superkey = superkey_text.bold().on_magenta();
-            let first_superkey_separator = ARROW_SEPARATOR.magenta().on_black();
-            let second_superkey_separator = ARROW_SEPARATOR.black().on_magenta();
-            let mode_shortcut = mode_shortcut_text.white().bold().on_magenta();
-            let mode_shortcut_separator = ARROW_SEPARATOR.magenta().on_black();
-            let len = superkey_text.chars().count()
-                + ARROW_SEPARATOR.chars().count()
-                + ARROW_SEPARATOR.chars().count()
-                + mode_shortcut_text.chars().count()
-                + ARROW_SEPARATOR.chars().count();
-            (
-                format!(
-                    "{}{}{}{}{}",
-                    superkey,
-                    first_superkey_separator,
-                    second_superkey_separator,
-                    mode_shortcut,
-                    mode_shortcut_separator
-                ),
-                len,
-            )
-        }
-        InputMode::Normal | _ => {
-            let key_path = superkey_text.on_green();
-            let separator = ARROW_SEPARATOR.green().on_black();
+        (_, None) => {
+            let key_path = paint(superkey_text, theme.fg, mode_color, false);
+            let separator = paint(ARROW_SEPARATOR, mode_color, theme.bg, false);
             (
                 format!("{}{}", key_path, separator),
                 superkey_text.chars().count() + ARROW_SEPARATOR.chars().count(),
@@ -164,7 +124,23 @@ fn key_path(help: &Help) -> LinePart {
     LinePart { part, len }
 }
 
-fn keybinds(help: &Help, max_width: usize) -> LinePart {
+// the keys of a sequence that was started, shown until it's finished
+fn pending_keys(help: &Help) -> LinePart {
+    if help.pending_keys.is_empty() {
+        return LinePart {
+            part: String::new(),
+            len: 0,
+        };
+    }
+    let pending_keys_text = format!(" {} ... ", help.pending_keys.join(" "));
+    let len = pending_keys_text.chars().count();
+    LinePart {
+        part: format!("{}", pending_keys_text.bold().black().on_yellow()),
+        len,
+    }
+}
+
+fn keybinds(help: &Help, theme: &Theme, max_width: usize) -> LinePart {
     let mut keybinds = String::new();
     let mut len = 0;
     let full_keybinds_len =
@@ -182,8 +158,8 @@ fn keybinds(help: &Help, max_width: usize) -> LinePart {
             let separator = if i > 0 { " / " } else { "" };
             let shortcut_len = shortcut.chars().count();
             let shortcut = match help.mode {
-                InputMode::Normal => shortcut.cyan(),
-                _ => shortcut.white().bold(),
+                InputMode::Normal => shortcut.cyan().to_string(),
+                _ => paint(shortcut, theme.fg, theme.bg, true),
             };
             keybinds = format!("{}{}<{}> {}", keybinds, separator, shortcut, description);
             len += shortcut_len + separator.chars().count();
@@ -196,8 +172,8 @@ fn keybinds(help: &Help, max_width: usize) -> LinePart {
             let description_first_word_length = description_first_word.chars().count();
             let (separator, separator_len) = if i > 0 { (" / ", 3) } else { ("", 0) };
             let shortcut = match help.mode {
-                InputMode::Normal => shortcut.cyan(),
-                _ => shortcut.white().bold(),
+                InputMode::Normal => shortcut.cyan().to_string(),
+                _ => paint(shortcut, theme.fg, theme.bg, true),
             };
             if current_length
                 + shortcut_length
@@ -238,24 +214,26 @@ impl ZellijTile for State {
 
     fn draw(&mut self, _rows: usize, cols: usize) {
         let help = get_help();
-        let line_prefix = prefix(&help);
-        let key_path = key_path(&help);
-        let line_len_before_keybinds = line_prefix.len + key_path.len;
+        let theme = get_theme();
+        let line_prefix = prefix(&help, &theme);
+        let key_path = key_path(&help, &theme);
+        let pending_keys = pending_keys(&help);
+        let line_len_before_keybinds = line_prefix.len + key_path.len + pending_keys.len;
         let status_bar = if line_len_before_keybinds + MORE_MSG.chars().count() < cols {
-            let keybinds = keybinds(&help, cols - line_len_before_keybinds);
-            let keybinds = keybinds.part.cyan().on_black();
-            format!("{}{}{}", line_prefix, key_path, keybinds)
+            let keybinds = keybinds(&help, &theme, cols - line_len_before_keybinds);
+            let keybinds = paint(&keybinds.part, PaletteColor::Index(6), theme.bg, false);
+            format!("{}{}{}{}", line_prefix, key_path, pending_keys, keybinds)
         } else if line_len_before_keybinds < cols {
-            format!("{}{}", line_prefix, key_path)
+            format!("{}{}{}", line_prefix, key_path, pending_keys)
         } else if line_prefix.len < cols {
             format!("{}", line_prefix)
         } else {
             // sorry, too small :(
             format!("")
         };
-        // 40m is black background, 0K is so that it fills the rest of the line,
+        // the background of the theme, and 0K so that it fills the rest of the line,
         // I could not find a way to do this with colored and did not want to have to
         // manually fill the line with spaces to achieve the same
-        println!("{}\u{1b}[40m\u{1b}[0K", status_bar);
+        println!("{}{}\u{1b}[0K", status_bar, theme.bg.bg());
     }
 }
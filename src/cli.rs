@@ -1,7 +1,9 @@
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 use structopt::StructOpt;
 
-#[derive(StructOpt, Debug, Default)]
+#[derive(StructOpt, Debug, Default, Clone)]
 #[structopt(name = "zellij")]
 pub struct CliArgs {
     /// Send "split (direction h == horizontal / v == vertical)" to active zellij session
@@ -24,6 +26,267 @@ pub struct CliArgs {
     #[structopt(short, long)]
     pub layout: Option<PathBuf>,
 
+    /// Path to a config yaml file, instead of config.yaml in the config directory
+    #[structopt(short, long)]
+    pub config: Option<PathBuf>,
+
     #[structopt(short, long)]
     pub debug: bool,
+
+    /// Name of the session to create, or to attach to if it's already running.
+    /// The one-shot commands above are sent to this session
+    #[structopt(short = "S", long)]
+    pub session: Option<String>,
+
+    /// Attach to a running zellij session (the only one running, unless --session is given)
+    #[structopt(short, long)]
+    pub attach: bool,
+
+    /// Detach every other client from the session when attaching to it
+    #[structopt(long)]
+    pub force: bool,
+
+    /// Attach to a running zellij session as a spectator, whose input is ignored except for
+    /// the detach key (implies --attach)
+    #[structopt(long, conflicts_with = "force")]
+    pub read_only: bool,
+
+    /// Which of the attached clients a new session is sized after: the smallest one, or the
+    /// one that was used last. Larger clients see the unused area filled in
+    #[structopt(long, default_value = "smallest", possible_values = &["smallest", "latest"])]
+    pub size_policy: SizePolicy,
+
+    /// Resurrect a session from its last save (eg. after a reboot), given its name or the path
+    /// of the file it was saved to. It keeps its name, unless it's renamed with --session
+    #[structopt(long, conflicts_with_all = &["attach", "read-only", "layout"])]
+    pub resurrect: Option<PathBuf>,
+
+    /// Save the scrollback of every pane along with the session, to be shown in the panes
+    /// again when the session is resurrected (also `save_scrollback: true` in the config file)
+    #[structopt(long)]
+    pub save_scrollback: bool,
+
+    /// The most lines of scrollback saved for each pane, with --save-scrollback, instead of
+    /// `scrollback_lines_to_save` in the config file (10000 by default)
+    #[structopt(long)]
+    pub scrollback_lines_to_save: Option<usize>,
+
+    /// How often the session is saved, instead of the default (which for tests is never)
+    #[structopt(skip)]
+    pub session_save_interval: Option<Duration>,
+
+    /// Where sessions are saved and resurrected from, instead of the data directory
+    #[structopt(skip)]
+    pub sessions_dir: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// How a session that's shown on several terminals picks its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizePolicy {
+    /// Fit the smallest attached terminal, so that every client sees the whole session
+    Smallest,
+    /// Fit the terminal of the client that last sent some input
+    Latest,
+}
+
+impl Default for SizePolicy {
+    fn default() -> Self {
+        SizePolicy::Smallest
+    }
+}
+
+impl FromStr for SizePolicy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smallest" => Ok(SizePolicy::Smallest),
+            "latest" => Ok(SizePolicy::Latest),
+            _ => Err(format!("unknown size policy {:?}", s)),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug, Clone)]
+pub enum Command {
+    /// List the running sessions, cleaning up after the ones that died
+    #[structopt(name = "list-sessions", alias = "ls")]
+    ListSessions,
+
+    /// Kill a session, closing all of its panes
+    #[structopt(name = "kill-session")]
+    KillSession {
+        /// Name of the session to kill
+        name: String,
+    },
+
+    /// Send an action to a running session (the one given with --session, the one we're in or
+    /// else the only one running), as if its key was pressed in the client that was used last
+    #[structopt(name = "action")]
+    Action(CliAction),
+
+    /// Print the contents of a pane of a running session, by default the focused pane of the
+    /// client that was used last
+    #[structopt(name = "dump-screen")]
+    DumpScreen {
+        /// Id of the terminal pane to print instead of the focused one
+        #[structopt(long)]
+        pane_id: Option<i32>,
+        /// Print the whole scrollback of the pane instead of what's in view
+        #[structopt(long)]
+        full: bool,
+        /// Keep the colors and styles of the text, as ANSI escape codes
+        #[structopt(long)]
+        ansi: bool,
+        /// Join lines that were wrapped to fit in the pane back together
+        #[structopt(long)]
+        join_wrapped: bool,
+    },
+
+    /// Print the tabs of a running session and their panes as JSON
+    #[structopt(name = "query")]
+    Query,
+
+    /// Print the events of a running session as they happen (eg. panes opening and closing),
+    /// as one JSON object per line, until the session ends
+    #[structopt(name = "subscribe")]
+    Subscribe,
+
+    /// Open a new pane running a command in a running session, next to the focused pane of the
+    /// client that was used last, eg. `zellij run --direction down -- cargo test`
+    #[structopt(name = "run")]
+    Run {
+        /// Where to open the pane, relative to the focused one. By default it goes in the
+        /// largest space available
+        #[structopt(short, long, possible_values = DIRECTIONS)]
+        direction: Option<CliDirection>,
+        /// Directory to run the command in, by default the current one
+        #[structopt(long, parse(from_os_str))]
+        cwd: Option<PathBuf>,
+        /// Environment variable to set for the command, as NAME=VALUE (can be repeated)
+        #[structopt(short, long = "env", parse(try_from_str = parse_env_var))]
+        env: Vec<(String, String)>,
+        /// Keep the pane open after the command exits, instead of closing it
+        #[structopt(long)]
+        hold: bool,
+        /// Wait for the command to exit, and exit with the same code
+        #[structopt(long)]
+        wait: bool,
+        /// The command to run, followed by its arguments
+        #[structopt(required = true)]
+        command: Vec<String>,
+    },
+
+    /// Type keys or text into a pane of a running session, by default the focused pane of the
+    /// client that was used last, eg. `zellij send-keys 'cargo test' Enter`
+    #[structopt(name = "send-keys")]
+    SendKeys {
+        /// Id of the terminal pane to type into instead of the focused one
+        #[structopt(long, conflicts_with = "pane-index")]
+        pane_id: Option<i32>,
+        /// Index of the pane to type into instead of the focused one, in the list of panes of
+        /// its tab printed by `zellij query`
+        #[structopt(long)]
+        pane_index: Option<usize>,
+        /// Index of the tab of the pane given with --pane-index, instead of the one the client
+        /// that was used last is looking at
+        #[structopt(long, requires = "pane-index")]
+        tab: Option<usize>,
+        /// Type the arguments as text, even the ones that are the names of keys
+        #[structopt(short, long, conflicts_with = "bytes")]
+        literal: bool,
+        /// Write the arguments as bytes given as numbers (eg. `27` for ESC)
+        #[structopt(long)]
+        bytes: bool,
+        /// Names of keys to press (eg. `Enter`, `C-c`, `M-x`, `Up` or `F1`), or text to type
+        #[structopt(required = true)]
+        keys: Vec<String>,
+    },
+}
+
+/// Parses an environment variable given as NAME=VALUE.
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    match s.find('=') {
+        Some(i) if i > 0 => Ok((s[..i].to_string(), s[i + 1..].to_string())),
+        _ => Err(format!("expected NAME=VALUE, got {:?}", s)),
+    }
+}
+
+/// The actions that can be sent to a session with `zellij action`.
+#[derive(StructOpt, Debug, Clone)]
+pub enum CliAction {
+    /// Quit the session
+    Quit,
+    /// Write bytes to the focused pane, given as numbers (eg. `27` for ESC)
+    Write { bytes: Vec<u8> },
+    /// Write characters to the focused pane
+    WriteChars { chars: String },
+    /// Switch to an input mode (normal, command, resize, pane, tab, scroll or one defined in the
+    /// config file)
+    SwitchMode { mode: String },
+    /// Resize the focused pane
+    Resize {
+        #[structopt(possible_values = DIRECTIONS)]
+        direction: CliDirection,
+    },
+    /// Move the focus to the next pane
+    FocusNextPane,
+    /// Move the focus to the pane next to the focused one
+    MoveFocus {
+        #[structopt(possible_values = DIRECTIONS)]
+        direction: CliDirection,
+    },
+    /// Scroll up in the focused pane
+    ScrollUp,
+    /// Scroll down in the focused pane
+    ScrollDown,
+    /// Toggle the focused pane between fullscreen and its place in the layout
+    ToggleFullscreen,
+    /// Open a new pane next to the focused one, or in the largest space available if no
+    /// direction is given
+    NewPane {
+        #[structopt(possible_values = DIRECTIONS)]
+        direction: Option<CliDirection>,
+    },
+    /// Close the focused pane
+    ClosePane,
+    /// Show a title on the border of the focused pane instead of what's running in it, or what's
+    /// running again if the title is empty
+    RenamePane { title: String },
+    /// Open a new tab
+    NewTab,
+    /// Go to the next tab
+    GoToNextTab,
+    /// Go to the previous tab
+    GoToPreviousTab,
+    /// Close the current tab
+    CloseTab,
+    /// Detach the client that was used last from the session
+    Detach,
+}
+
+const DIRECTIONS: &[&str] = &["left", "right", "up", "down"];
+
+/// A direction given to an action on the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl FromStr for CliDirection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(CliDirection::Left),
+            "right" => Ok(CliDirection::Right),
+            "up" => Ok(CliDirection::Up),
+            "down" => Ok(CliDirection::Down),
+            _ => Err(format!("unknown direction {:?}", s)),
+        }
+    }
 }
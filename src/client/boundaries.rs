@@ -1,7 +1,8 @@
+use crate::common::theme::{BorderStyle, PaletteColor, Theme};
+use crate::panes::PaneId;
 use crate::tab::Pane;
 use std::collections::HashMap;
-
-use std::fmt::{Display, Error, Formatter};
+use unicode_truncate::UnicodeTruncateStr;
 
 pub mod boundary_type {
     pub const TOP_RIGHT: &str = "┐";
@@ -19,10 +20,67 @@ pub mod boundary_type {
 
 pub type BoundaryType = &'static str; // easy way to refer to boundary_type above
 
+/// The rows at the top of the screen, above the panes, that the top border of the panes there is
+/// drawn on, so that they have somewhere to show their title too.
+pub const TOP_BORDER_ROWS: usize = 1;
+
+/// Returns what `boundary_type` looks like in `border_style`. Boundaries are always combined as
+/// the glyphs of [`boundary_type`], which have the same shapes in every style, and only drawn
+/// in the style they're in.
+fn styled_boundary_type(boundary_type: BoundaryType, border_style: BorderStyle) -> &'static str {
+    use boundary_type::*;
+    match border_style {
+        BorderStyle::Single => boundary_type,
+        BorderStyle::Rounded => match boundary_type {
+            TOP_LEFT => "╭",
+            TOP_RIGHT => "╮",
+            BOTTOM_LEFT => "╰",
+            BOTTOM_RIGHT => "╯",
+            _ => boundary_type,
+        },
+        BorderStyle::Double => match boundary_type {
+            TOP_RIGHT => "╗",
+            VERTICAL => "║",
+            HORIZONTAL => "═",
+            TOP_LEFT => "╔",
+            BOTTOM_RIGHT => "╝",
+            BOTTOM_LEFT => "╚",
+            VERTICAL_LEFT => "╣",
+            VERTICAL_RIGHT => "╠",
+            HORIZONTAL_DOWN => "╦",
+            HORIZONTAL_UP => "╩",
+            CROSS => "╬",
+            _ => boundary_type,
+        },
+        BorderStyle::Heavy => match boundary_type {
+            TOP_RIGHT => "┓",
+            VERTICAL => "┃",
+            HORIZONTAL => "━",
+            TOP_LEFT => "┏",
+            BOTTOM_RIGHT => "┛",
+            BOTTOM_LEFT => "┗",
+            VERTICAL_LEFT => "┫",
+            VERTICAL_RIGHT => "┣",
+            HORIZONTAL_DOWN => "┳",
+            HORIZONTAL_UP => "┻",
+            CROSS => "╋",
+            _ => boundary_type,
+        },
+        BorderStyle::Ascii => match boundary_type {
+            VERTICAL => "|",
+            HORIZONTAL => "-",
+            _ => "+",
+        },
+        BorderStyle::Blank => " ",
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct BoundarySymbol {
     boundary_type: BoundaryType,
     invisible: bool,
+    /// Whether it's on the border of the focused pane
+    focused: bool,
 }
 
 impl BoundarySymbol {
@@ -30,20 +88,22 @@ impl BoundarySymbol {
         BoundarySymbol {
             boundary_type,
             invisible: false,
+            focused: false,
         }
     }
     pub fn invisible(mut self) -> Self {
         self.invisible = true;
         self
     }
-}
-
-impl Display for BoundarySymbol {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+    pub fn focused(mut self) -> Self {
+        self.focused = true;
+        self
+    }
+    fn styled(&self, border_style: BorderStyle) -> &'static str {
         if self.invisible {
-            write!(f, " ")
+            " "
         } else {
-            write!(f, "{}", self.boundary_type)
+            styled_boundary_type(self.boundary_type, border_style)
         }
     }
 }
@@ -53,6 +113,8 @@ fn combine_symbols(
     next_symbol: BoundarySymbol,
 ) -> Option<BoundarySymbol> {
     let invisible = current_symbol.invisible || next_symbol.invisible;
+    // junctions with the border of the focused pane are part of it, so that it's drawn whole
+    let focused = current_symbol.focused || next_symbol.focused;
     let current_symbol = current_symbol.boundary_type;
     let next_symbol = next_symbol.boundary_type;
     match (current_symbol, next_symbol) {
@@ -62,6 +124,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::VERTICAL) => {
@@ -70,6 +133,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::HORIZONTAL) => {
@@ -78,6 +142,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::TOP_LEFT) => {
@@ -86,6 +151,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::BOTTOM_RIGHT) => {
@@ -94,6 +160,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::BOTTOM_LEFT) => {
@@ -102,6 +169,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::VERTICAL_LEFT) => {
@@ -110,6 +178,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::VERTICAL_RIGHT) => {
@@ -118,6 +187,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::HORIZONTAL_DOWN) => {
@@ -126,6 +196,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::HORIZONTAL_UP) => {
@@ -134,6 +205,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_RIGHT, boundary_type::CROSS) => {
@@ -142,6 +214,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::HORIZONTAL) => {
@@ -150,6 +223,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::VERTICAL) => {
@@ -158,6 +232,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::TOP_LEFT) => {
@@ -166,6 +241,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::BOTTOM_RIGHT) => {
@@ -174,6 +250,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::BOTTOM_LEFT) => {
@@ -182,6 +259,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::VERTICAL_LEFT) => {
@@ -190,6 +268,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::VERTICAL_RIGHT) => {
@@ -198,6 +277,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::HORIZONTAL_DOWN) => {
@@ -206,6 +286,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::HORIZONTAL_UP) => {
@@ -214,6 +295,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL, boundary_type::CROSS) => {
@@ -222,6 +304,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::VERTICAL) => {
@@ -230,6 +313,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::TOP_LEFT) => {
@@ -238,6 +322,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::BOTTOM_RIGHT) => {
@@ -246,6 +331,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::BOTTOM_LEFT) => {
@@ -254,6 +340,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::VERTICAL_LEFT) => {
@@ -262,6 +349,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::VERTICAL_RIGHT) => {
@@ -270,6 +358,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::HORIZONTAL_DOWN) => {
@@ -278,6 +367,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::HORIZONTAL_UP) => {
@@ -286,6 +376,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL, boundary_type::CROSS) => {
@@ -294,6 +385,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::TOP_LEFT) => {
@@ -302,6 +394,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::BOTTOM_RIGHT) => {
@@ -310,6 +403,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::BOTTOM_LEFT) => {
@@ -318,6 +412,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::VERTICAL_LEFT) => {
@@ -326,6 +421,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::VERTICAL_RIGHT) => {
@@ -334,6 +430,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::HORIZONTAL_DOWN) => {
@@ -342,6 +439,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::HORIZONTAL_UP) => {
@@ -350,6 +448,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::TOP_LEFT, boundary_type::CROSS) => {
@@ -358,6 +457,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::BOTTOM_RIGHT) => {
@@ -366,6 +466,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::BOTTOM_LEFT) => {
@@ -374,6 +475,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::VERTICAL_LEFT) => {
@@ -382,6 +484,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::VERTICAL_RIGHT) => {
@@ -390,6 +493,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::HORIZONTAL_DOWN) => {
@@ -398,6 +502,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::HORIZONTAL_UP) => {
@@ -406,6 +511,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_RIGHT, boundary_type::CROSS) => {
@@ -414,6 +520,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_LEFT, boundary_type::BOTTOM_LEFT) => {
@@ -422,6 +529,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_LEFT, boundary_type::VERTICAL_LEFT) => {
@@ -430,6 +538,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_LEFT, boundary_type::VERTICAL_RIGHT) => {
@@ -438,6 +547,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_LEFT, boundary_type::HORIZONTAL_DOWN) => {
@@ -446,6 +556,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_LEFT, boundary_type::HORIZONTAL_UP) => {
@@ -454,6 +565,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::BOTTOM_LEFT, boundary_type::CROSS) => {
@@ -462,6 +574,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_LEFT, boundary_type::VERTICAL_LEFT) => {
@@ -470,6 +583,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_LEFT, boundary_type::VERTICAL_RIGHT) => {
@@ -478,6 +592,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_LEFT, boundary_type::HORIZONTAL_DOWN) => {
@@ -486,6 +601,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_LEFT, boundary_type::HORIZONTAL_UP) => {
@@ -494,6 +610,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_LEFT, boundary_type::CROSS) => {
@@ -502,6 +619,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_RIGHT, boundary_type::VERTICAL_RIGHT) => {
@@ -510,6 +628,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_RIGHT, boundary_type::HORIZONTAL_DOWN) => {
@@ -518,6 +637,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_RIGHT, boundary_type::HORIZONTAL_UP) => {
@@ -526,6 +646,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::VERTICAL_RIGHT, boundary_type::CROSS) => {
@@ -534,6 +655,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL_DOWN, boundary_type::HORIZONTAL_DOWN) => {
@@ -542,6 +664,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL_DOWN, boundary_type::HORIZONTAL_UP) => {
@@ -550,6 +673,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL_DOWN, boundary_type::CROSS) => {
@@ -558,6 +682,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL_UP, boundary_type::HORIZONTAL_UP) => {
@@ -566,6 +691,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::HORIZONTAL_UP, boundary_type::CROSS) => {
@@ -574,6 +700,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (boundary_type::CROSS, boundary_type::CROSS) => {
@@ -582,6 +709,7 @@ fn combine_symbols(
             Some(BoundarySymbol {
                 boundary_type,
                 invisible,
+                focused,
             })
         }
         (_, _) => None,
@@ -665,26 +793,59 @@ pub struct Boundaries {
     rows: usize,
     // boundary_characters: HashMap<Coordinates, BoundaryType>,
     boundary_characters: HashMap<Coordinates, BoundarySymbol>,
+    /// The pane whose border is drawn in `focused_color` instead of `color`
+    active_pane: Option<PaneId>,
+    color: PaletteColor,
+    focused_color: PaletteColor,
+    border_style: BorderStyle,
+    /// The titles drawn over the top border of panes: where each starts, and whether it's the
+    /// focused pane's
+    titles: Vec<(Coordinates, String, bool)>,
 }
 
 impl Boundaries {
-    pub fn new(columns: u16, rows: u16) -> Self {
+    pub fn new(columns: u16, rows: u16, theme: &Theme, active_pane: Option<PaneId>) -> Self {
         let columns = columns as usize;
         let rows = rows as usize;
         Boundaries {
             columns,
             rows,
             boundary_characters: HashMap::new(),
+            active_pane,
+            color: theme.inactive_border,
+            focused_color: theme.focused_border,
+            border_style: theme.border_style,
+            titles: vec![],
         }
     }
+    /// Shows `title` on the top border of `rect`, cut short if it's too wide for it. There's
+    /// always a piece of border left at both of its ends, so that the junctions with the borders
+    /// of the panes next to `rect` still show.
+    pub fn add_title(&mut self, rect: &dyn Pane, title: &str) {
+        // the pieces of border at both ends, and a space on both sides of the title
+        let max_width = rect.columns().saturating_sub(4);
+        if max_width == 0 {
+            return;
+        }
+        let title: String = title.chars().filter(|c| !c.is_control()).collect();
+        let (title, _) = title.unicode_truncate(max_width);
+        if title.is_empty() {
+            return;
+        }
+        let coordinates = Coordinates::new(rect.x() + 1, self.rect_y(rect) - 1);
+        let is_focused = self.active_pane == Some(rect.pid());
+        self.titles
+            .push((coordinates, format!(" {} ", title), is_focused));
+    }
     pub fn add_rect(&mut self, rect: &dyn Pane) {
+        let is_focused = self.active_pane == Some(rect.pid());
         if rect.x() > 0 {
             let boundary_x_coords = rect.x() - 1;
             let first_row_coordinates = self.rect_right_boundary_row_start(rect);
             let last_row_coordinates = self.rect_right_boundary_row_end(rect);
             for row in first_row_coordinates..last_row_coordinates {
                 let coordinates = Coordinates::new(boundary_x_coords, row);
-                let mut symbol_to_add = if row == first_row_coordinates && row != 0 {
+                let mut symbol_to_add = if row == first_row_coordinates {
                     BoundarySymbol::new(boundary_type::TOP_LEFT)
                 } else if row == last_row_coordinates - 1 && row != self.rows - 1 {
                     BoundarySymbol::new(boundary_type::BOTTOM_LEFT)
@@ -694,6 +855,9 @@ impl Boundaries {
                 if rect.invisible_borders() {
                     symbol_to_add = symbol_to_add.invisible();
                 }
+                if is_focused {
+                    symbol_to_add = symbol_to_add.focused();
+                }
                 let next_symbol = self
                     .boundary_characters
                     .remove(&coordinates)
@@ -702,29 +866,31 @@ impl Boundaries {
                 self.boundary_characters.insert(coordinates, next_symbol);
             }
         }
-        if rect.y() > 0 {
-            let boundary_y_coords = rect.y() - 1;
-            let first_col_coordinates = self.rect_bottom_boundary_col_start(rect);
-            let last_col_coordinates = self.rect_bottom_boundary_col_end(rect);
-            for col in first_col_coordinates..last_col_coordinates {
-                let coordinates = Coordinates::new(col, boundary_y_coords);
-                let mut symbol_to_add = if col == first_col_coordinates && col != 0 {
-                    BoundarySymbol::new(boundary_type::TOP_LEFT)
-                } else if col == last_col_coordinates - 1 && col != self.columns - 1 {
-                    BoundarySymbol::new(boundary_type::TOP_RIGHT)
-                } else {
-                    BoundarySymbol::new(boundary_type::HORIZONTAL)
-                };
-                if rect.invisible_borders() {
-                    symbol_to_add = symbol_to_add.invisible();
-                }
-                let next_symbol = self
-                    .boundary_characters
-                    .remove(&coordinates)
-                    .and_then(|current_symbol| find_next_symbol(current_symbol, symbol_to_add))
-                    .unwrap_or(symbol_to_add);
-                self.boundary_characters.insert(coordinates, next_symbol);
+        // the panes at the top of the screen have their top border on the row above them
+        let boundary_y_coords = self.rect_y(rect) - 1;
+        let first_col_coordinates = self.rect_bottom_boundary_col_start(rect);
+        let last_col_coordinates = self.rect_bottom_boundary_col_end(rect);
+        for col in first_col_coordinates..last_col_coordinates {
+            let coordinates = Coordinates::new(col, boundary_y_coords);
+            let mut symbol_to_add = if col == first_col_coordinates && col != 0 {
+                BoundarySymbol::new(boundary_type::TOP_LEFT)
+            } else if col == last_col_coordinates - 1 && col != self.columns - 1 {
+                BoundarySymbol::new(boundary_type::TOP_RIGHT)
+            } else {
+                BoundarySymbol::new(boundary_type::HORIZONTAL)
+            };
+            if rect.invisible_borders() {
+                symbol_to_add = symbol_to_add.invisible();
+            }
+            if is_focused {
+                symbol_to_add = symbol_to_add.focused();
             }
+            let next_symbol = self
+                .boundary_characters
+                .remove(&coordinates)
+                .and_then(|current_symbol| find_next_symbol(current_symbol, symbol_to_add))
+                .unwrap_or(symbol_to_add);
+            self.boundary_characters.insert(coordinates, next_symbol);
         }
         if self.rect_right_boundary_is_before_screen_edge(rect) {
             // let boundary_x_coords = self.rect_right_boundary_x_coords(rect);
@@ -733,7 +899,7 @@ impl Boundaries {
             let last_row_coordinates = self.rect_right_boundary_row_end(rect);
             for row in first_row_coordinates..last_row_coordinates {
                 let coordinates = Coordinates::new(boundary_x_coords, row);
-                let mut symbol_to_add = if row == first_row_coordinates && row != 0 {
+                let mut symbol_to_add = if row == first_row_coordinates {
                     BoundarySymbol::new(boundary_type::TOP_RIGHT)
                 } else if row == last_row_coordinates - 1 && row != self.rows - 1 {
                     BoundarySymbol::new(boundary_type::BOTTOM_RIGHT)
@@ -743,6 +909,9 @@ impl Boundaries {
                 if rect.invisible_borders() {
                     symbol_to_add = symbol_to_add.invisible();
                 }
+                if is_focused {
+                    symbol_to_add = symbol_to_add.focused();
+                }
                 let next_symbol = self
                     .boundary_characters
                     .remove(&coordinates)
@@ -752,7 +921,7 @@ impl Boundaries {
             }
         }
         if self.rect_bottom_boundary_is_before_screen_edge(rect) {
-            let boundary_y_coords = rect.bottom_boundary_y_coords();
+            let boundary_y_coords = rect.bottom_boundary_y_coords() + TOP_BORDER_ROWS;
             let first_col_coordinates = self.rect_bottom_boundary_col_start(rect);
             let last_col_coordinates = self.rect_bottom_boundary_col_end(rect);
             for col in first_col_coordinates..last_col_coordinates {
@@ -767,6 +936,9 @@ impl Boundaries {
                 if rect.invisible_borders() {
                     symbol_to_add = symbol_to_add.invisible();
                 }
+                if is_focused {
+                    symbol_to_add = symbol_to_add.focused();
+                }
                 let next_symbol = self
                     .boundary_characters
                     .remove(&coordinates)
@@ -778,31 +950,49 @@ impl Boundaries {
     }
     pub fn vte_output(&self) -> String {
         let mut vte_output = String::new();
+        let color = self.color.foreground_styles();
+        let focused_color = self.focused_color.foreground_styles();
         for (coordinates, boundary_character) in &self.boundary_characters {
+            let color = if boundary_character.focused {
+                focused_color
+            } else {
+                color
+            };
+            vte_output.push_str(&format!(
+                "\u{1b}[{};{}H\u{1b}[m{}{}",
+                coordinates.y + 1,
+                coordinates.x + 1,
+                color,
+                boundary_character.styled(self.border_style)
+            )); // goto row/col + color + boundary character
+        }
+        for (coordinates, title, is_focused) in &self.titles {
+            let color = if *is_focused { focused_color } else { color };
             vte_output.push_str(&format!(
-                "\u{1b}[{};{}H\u{1b}[m{}",
+                "\u{1b}[{};{}H\u{1b}[m{}{}",
                 coordinates.y + 1,
                 coordinates.x + 1,
-                boundary_character
-            )); // goto row/col + boundary character
+                color,
+                title
+            ));
         }
         vte_output
     }
     fn rect_right_boundary_is_before_screen_edge(&self, rect: &dyn Pane) -> bool {
         rect.x() + rect.columns() < self.columns
     }
+    /// The row of the screen the first row of `rect` is on, below the top border row.
+    fn rect_y(&self, rect: &dyn Pane) -> usize {
+        rect.y() + TOP_BORDER_ROWS
+    }
     fn rect_bottom_boundary_is_before_screen_edge(&self, rect: &dyn Pane) -> bool {
-        rect.y() + rect.rows() < self.rows
+        self.rect_y(rect) + rect.rows() < self.rows
     }
     fn rect_right_boundary_row_start(&self, rect: &dyn Pane) -> usize {
-        if rect.y() == 0 {
-            0
-        } else {
-            rect.y() - 1
-        }
+        self.rect_y(rect) - 1
     }
     fn rect_right_boundary_row_end(&self, rect: &dyn Pane) -> usize {
-        let rect_bottom_row = rect.y() + rect.rows();
+        let rect_bottom_row = self.rect_y(rect) + rect.rows();
         // we do this because unless we're on the screen edge, we'd like to go one extra row to
         // connect to whatever boundary is beneath us
         if rect_bottom_row == self.rows {
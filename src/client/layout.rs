@@ -2,7 +2,8 @@ use crate::utils::consts::ZELLIJ_ROOT_LAYOUT_DIR;
 use directories_next::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use std::{fs::File, io::prelude::*};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, fs::File, io, io::prelude::*};
 
 use crate::panes::PositionAndSize;
 
@@ -157,6 +158,142 @@ fn split_space(
     pane_positions
 }
 
+/// Finds where `panes` can be cut apart along `direction` without cutting through any of them,
+/// returning the panes on each side of the cuts along with the space they take up.
+fn split_panes_at_gaps(
+    space: &PositionAndSize,
+    panes: &[(Layout, PositionAndSize)],
+    direction: &Direction,
+) -> Vec<(PositionAndSize, Vec<(Layout, PositionAndSize)>)> {
+    // the start and length of the panes and of the space along `direction`
+    let span = |position_and_size: &PositionAndSize| match direction {
+        Direction::Vertical => (position_and_size.x, position_and_size.columns),
+        Direction::Horizontal => (position_and_size.y, position_and_size.rows),
+    };
+    let (space_start, space_length) = span(space);
+    let space_end = space_start + space_length;
+    let mut cuts: Vec<usize> = panes
+        .iter()
+        .map(|(_, position_and_size)| {
+            let (start, length) = span(position_and_size);
+            start + length // the gap right after the pane
+        })
+        .filter(|gap| *gap < space_end)
+        .filter(|gap| {
+            panes.iter().all(|(_, position_and_size)| {
+                let (start, length) = span(position_and_size);
+                start + length <= *gap || start > *gap
+            })
+        })
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+
+    let mut part_start = space_start;
+    let mut parts = Vec::new();
+    for part_end in cuts.into_iter().chain(std::iter::once(space_end)) {
+        let part_space = match direction {
+            Direction::Vertical => PositionAndSize {
+                x: part_start,
+                columns: part_end - part_start,
+                ..*space
+            },
+            Direction::Horizontal => PositionAndSize {
+                y: part_start,
+                rows: part_end - part_start,
+                ..*space
+            },
+        };
+        let part_panes = panes
+            .iter()
+            .filter(|(_, position_and_size)| {
+                let (start, _) = span(position_and_size);
+                start >= part_start && start < part_end
+            })
+            .cloned()
+            .collect();
+        parts.push((part_space, part_panes));
+        part_start = part_end + 1; // 1 for gap
+    }
+    parts
+}
+
+fn join_space(
+    space: &PositionAndSize,
+    panes: Vec<(Layout, PositionAndSize)>,
+) -> (Direction, Vec<Layout>) {
+    for direction in [Direction::Vertical, Direction::Horizontal].iter() {
+        let parts = split_panes_at_gaps(space, &panes, direction);
+        if parts.len() < 2 {
+            continue;
+        }
+        let max_length = match direction {
+            Direction::Vertical => space.columns,
+            Direction::Horizontal => space.rows,
+        } - (parts.len() - 1); // minus space for gaps
+        let last_flexible_index = parts.iter().rposition(|(_, part_panes)| {
+            !matches!(direction, Direction::Horizontal) || !has_fixed_height(part_panes.as_slice())
+        });
+        let parts = parts
+            .into_iter()
+            .enumerate()
+            .map(|(index, (part_space, mut part_panes))| {
+                let mut part = if part_panes.len() == 1 {
+                    part_panes.remove(0).0
+                } else {
+                    let (part_direction, parts) = join_space(&part_space, part_panes);
+                    Layout {
+                        direction: part_direction,
+                        parts,
+                        split_size: None,
+                        plugin: None,
+                        cwd: None,
+                        command: None,
+                        title: None,
+                        scrollback: None,
+                        scrollback_to_save: None,
+                    }
+                };
+                let length = match direction {
+                    Direction::Vertical => part_space.columns,
+                    Direction::Horizontal => part_space.rows,
+                };
+                let keeps_fixed_size = matches!(direction, Direction::Horizontal)
+                    && matches!(part.split_size, Some(SplitSize::Fixed(_)));
+                if !keeps_fixed_size {
+                    part.split_size = if Some(index) == last_flexible_index {
+                        // takes up whatever space the other parts leave
+                        None
+                    } else {
+                        let percent = (length * 100 + max_length / 2) / max_length.max(1);
+                        Some(SplitSize::Percent(percent.clamp(1, 100) as u8))
+                    };
+                }
+                part
+            })
+            .collect();
+        return (direction.clone(), parts);
+    }
+    // these panes can't be cut apart, the best we can do is to stack them
+    let mut panes = panes;
+    panes.sort_by_key(|(_, position_and_size)| (position_and_size.y, position_and_size.x));
+    let parts = panes
+        .into_iter()
+        .map(|(mut part, _)| {
+            part.split_size = None;
+            part
+        })
+        .collect();
+    (Direction::Horizontal, parts)
+}
+
+fn has_fixed_height(panes: &[(Layout, PositionAndSize)]) -> bool {
+    match panes {
+        [(part, _)] => matches!(part.split_size, Some(SplitSize::Fixed(_))),
+        _ => false,
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum Direction {
     Horizontal,
@@ -178,6 +315,24 @@ pub struct Layout {
     pub split_size: Option<SplitSize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plugin: Option<PathBuf>,
+    /// The directory the pane's shell is started in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// A command line typed into the pane's shell once it starts, it only runs once the user
+    /// presses enter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// The title shown on the pane's border instead of what's running in it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// A file with output of a previous run (eg. saved along with a session) that the pane is
+    /// filled with before its shell starts, as ANSI text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scrollback: Option<PathBuf>,
+    /// The scrollback of the pane this part was made from, written to the `scrollback` file
+    /// when its session is saved
+    #[serde(skip)]
+    pub scrollback_to_save: Option<String>,
 }
 
 impl Layout {
@@ -217,4 +372,189 @@ impl Layout {
     ) -> Vec<(Layout, PositionAndSize)> {
         split_space(space, &self)
     }
+
+    /// The inverse of [`Layout::position_panes_in_space`]: builds a [`Layout`] splitting `space`
+    /// into `panes`, each of them given as a part without any parts of its own and the position
+    /// it takes up.
+    ///
+    /// The parts are sized in proportion to the space they take up, except for the parts of
+    /// horizontal splits that are given with a fixed size, which they keep.
+    pub fn from_panes_in_space(
+        space: &PositionAndSize,
+        panes: Vec<(Layout, PositionAndSize)>,
+    ) -> Self {
+        let (direction, parts) = join_space(space, panes);
+        Layout {
+            direction,
+            parts,
+            split_size: None,
+            plugin: None,
+            cwd: None,
+            command: None,
+            title: None,
+            scrollback: None,
+            scrollback_to_save: None,
+        }
+    }
+
+    /// Returns the parts that are terminal panes, in the order their terminals are expected by
+    /// [`Tab::apply_layout`](crate::tab::Tab::apply_layout).
+    pub fn terminal_panes(&self) -> Vec<&Layout> {
+        let mut terminal_panes = Vec::new();
+        for part in self.parts.iter() {
+            if !part.parts.is_empty() {
+                terminal_panes.append(&mut part.terminal_panes());
+            } else if part.plugin.is_none() {
+                terminal_panes.push(part);
+            }
+        }
+        terminal_panes
+    }
+
+    fn terminal_panes_mut(&mut self) -> Vec<&mut Layout> {
+        let mut terminal_panes = Vec::new();
+        for part in self.parts.iter_mut() {
+            if !part.parts.is_empty() {
+                terminal_panes.append(&mut part.terminal_panes_mut());
+            } else if part.plugin.is_none() {
+                terminal_panes.push(part);
+            }
+        }
+        terminal_panes
+    }
+}
+
+/// A snapshot of all the tabs of a session, which is saved every now and then while the session
+/// is running so that it can be resurrected (eg. after a reboot) with `zellij --resurrect`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionLayout {
+    pub tabs: Vec<Layout>,
+}
+
+impl SessionLayout {
+    /// Loads the session saved in `sessions_dir` under the name `saved_session`, or from the
+    /// file at that path.
+    pub fn new(saved_session: &Path, sessions_dir: &Path) -> Result<Self, String> {
+        let session_path = if saved_session.is_file() {
+            saved_session.to_path_buf()
+        } else {
+            session_layout_path(sessions_dir, &saved_session.to_string_lossy())
+        };
+        let session_layout = fs::read_to_string(&session_path).map_err(|_| {
+            format!(
+                "There is no saved session named {:?}.",
+                saved_session.display()
+            )
+        })?;
+        let session_layout: SessionLayout = serde_yaml::from_str(&session_layout).map_err(|e| {
+            format!(
+                "Could not parse the saved session {}: {}",
+                session_path.display(),
+                e
+            )
+        })?;
+        if session_layout.tabs.iter().any(|tab| tab.parts.is_empty()) {
+            return Err(format!(
+                "Could not parse the saved session {}: every tab needs at least one part",
+                session_path.display()
+            ));
+        }
+        Ok(session_layout)
+    }
+
+    /// Saves this session in `sessions_dir` under the name `session_name`, replacing the last
+    /// save of any session of the same name. The scrollback of its panes (if it was taken) is
+    /// saved to a file of its own for each pane, next to the session.
+    pub fn save(mut self, sessions_dir: &Path, session_name: &str) -> io::Result<()> {
+        let session_path = session_layout_path(sessions_dir, session_name);
+        // the scrollback goes to a new directory every time, and the one of the last save is
+        // only removed once the session points to the new one, so that a crash can't leave a
+        // save without its scrollback
+        let scrollback_dir = new_scrollback_dir(sessions_dir, session_name)?;
+        let mut has_scrollback = false;
+        for (tab_index, tab) in self.tabs.iter_mut().enumerate() {
+            for (pane_index, pane) in tab.terminal_panes_mut().into_iter().enumerate() {
+                if let Some(scrollback) = pane.scrollback_to_save.take() {
+                    let scrollback_path =
+                        scrollback_dir.join(format!("{}-{}", tab_index, pane_index));
+                    fs::write(&scrollback_path, scrollback)?;
+                    pane.scrollback = Some(scrollback_path);
+                    has_scrollback = true;
+                }
+            }
+        }
+        let session_layout = serde_yaml::to_string(&self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        // and the session is written to a temporary file first, so that a crash can't leave it
+        // half written
+        let tmp_path = session_path.with_extension("yaml.tmp");
+        fs::write(&tmp_path, session_layout)?;
+        fs::rename(tmp_path, session_path)?;
+
+        // along with what's left of the saves that crashed
+        for old_scrollback_dir in session_scrollback_dirs(sessions_dir, session_name) {
+            if old_scrollback_dir != scrollback_dir || !has_scrollback {
+                fs::remove_dir_all(old_scrollback_dir)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the session saved under the name `saved_session`, or to the file at that
+/// path (as accepted by [`SessionLayout::new`]).
+pub fn saved_session_name(saved_session: &Path) -> String {
+    match saved_session.file_stem() {
+        Some(file_stem) if saved_session.is_file() => file_stem.to_string_lossy().into_owned(),
+        _ => saved_session.to_string_lossy().into_owned(),
+    }
+}
+
+/// Returns the directory sessions are saved to, unless they're given another one.
+pub fn default_sessions_dir() -> PathBuf {
+    let project_dirs = ProjectDirs::from("org", "Zellij Contributors", "Zellij").unwrap();
+    project_dirs.data_dir().join("sessions")
+}
+
+/// Returns the path the session named `session_name` is saved to in `sessions_dir`.
+pub fn session_layout_path(sessions_dir: &Path, session_name: &str) -> PathBuf {
+    sessions_dir.join(format!("{}.yaml", session_name))
+}
+
+/// Returns the directories the scrollback of the panes of the session named `session_name` is
+/// saved to in `sessions_dir`: the one of its last save, and those of saves that didn't finish.
+pub fn session_scrollback_dirs(sessions_dir: &Path, session_name: &str) -> Vec<PathBuf> {
+    let prefix = format!("{}.scrollback.", session_name);
+    fs::read_dir(sessions_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    // not the ones of another session whose name starts with this one's
+                    matches!(
+                        entry.file_name().to_str().and_then(|name| name.strip_prefix(&prefix)),
+                        Some(suffix) if suffix.parse::<u128>().is_ok()
+                    )
+                })
+                .map(|entry| entry.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Creates a directory that wasn't used by any save of the session named `session_name` to save
+/// its scrollback to in `sessions_dir`, and returns its path.
+fn new_scrollback_dir(sessions_dir: &Path, session_name: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(sessions_dir)?;
+    let mut suffix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    loop {
+        let scrollback_dir = sessions_dir.join(format!("{}.scrollback.{}", session_name, suffix));
+        match fs::create_dir(&scrollback_dir) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => suffix += 1,
+            result => return result.map(|()| scrollback_dir),
+        }
+    }
 }
@@ -3,4 +3,200 @@ pub mod layout;
 pub mod panes;
 pub mod tab;
 
-pub fn _start_client() {}
+use std::io::Write;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cli::CliArgs;
+use crate::common::ipc::{
+    session_socket_path, ClientToServerMsg, ClientType, ExitReason, Response, ResponseData,
+    ServerToClientMsg,
+};
+use crate::common::OPENCALLS;
+use crate::errors::ContextType;
+use crate::os_input_output::OsApi;
+use crate::panes::PositionAndSize;
+
+/// How long a new client waits for the server it just started to come up.
+const SERVER_STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connects to the server of the session named `session_name`, waiting for it to come up if we
+/// just started it.
+fn connect_to_server(os_input: &mut Box<dyn OsApi>, session_name: &str, wait_for_server: bool) {
+    let socket_path = session_socket_path(session_name);
+    let started_waiting = Instant::now();
+    loop {
+        match os_input.connect_to_server(&socket_path) {
+            Ok(()) => break,
+            Err(e) => {
+                if !wait_for_server || started_waiting.elapsed() > SERVER_STARTUP_TIMEOUT {
+                    eprintln!(
+                        "Could not connect to the zellij session {:?}: {}",
+                        session_name, e
+                    );
+                    std::process::exit(1);
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+}
+
+/// Attaches to the session of the server we're connected to as a `client_type` client whose
+/// terminal is of size `client_size`, detaching its other clients first if `force` is set.
+fn attach_to_session(
+    os_input: &mut Box<dyn OsApi>,
+    client_type: ClientType,
+    client_size: PositionAndSize,
+    force: bool,
+) {
+    os_input.send_to_server(ClientToServerMsg::ListSessions);
+    let session = match os_input.recv_from_server() {
+        Some(ServerToClientMsg::Response(Response::Data(ResponseData::SessionList(sessions)))) => {
+            sessions.into_iter().next()
+        }
+        _ => None,
+    };
+    match session {
+        Some(session) => {
+            if force {
+                os_input.send_to_server(ClientToServerMsg::DetachSession(session.id));
+            }
+            os_input.send_to_server(ClientToServerMsg::AttachToSession(
+                session.id,
+                client_type,
+                client_size,
+            ));
+        }
+        None => {
+            eprintln!("There is no zellij session to attach to.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Start a Zellij client for the session named `session_name`, which either creates the session
+/// on its freshly started server or attaches to it (if `opts.attach` is set), and then relays
+/// input and rendered frames between the server and the terminal until it's told to exit.
+pub fn start_client(mut os_input: Box<dyn OsApi>, opts: CliArgs, session_name: String) {
+    connect_to_server(&mut os_input, &session_name, !opts.attach);
+
+    let take_snapshot = "\u{1b}[?1049h";
+    os_input.unset_raw_mode(0);
+    let _ = os_input
+        .get_stdout_writer()
+        .write(take_snapshot.as_bytes())
+        .unwrap();
+
+    let full_screen_ws = os_input.get_terminal_size_using_fd(0);
+    os_input.set_raw_mode(0);
+
+    if opts.attach {
+        let client_type = if opts.read_only {
+            ClientType::Reader
+        } else {
+            ClientType::Writer
+        };
+        attach_to_session(&mut os_input, client_type, full_screen_ws, opts.force);
+    } else {
+        os_input.send_to_server(ClientToServerMsg::CreateSession(full_screen_ws));
+    }
+
+    let (send_unblock_input, receive_unblock_input) = mpsc::channel();
+    let _stdin_thread = thread::Builder::new()
+        .name("stdin_handler".to_string())
+        .spawn({
+            let os_input = os_input.clone();
+            move || {
+                let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
+                err_ctx.add_call(ContextType::StdinHandler);
+                loop {
+                    let stdin_buffer = os_input.read_from_stdin();
+                    os_input.send_to_server(ClientToServerMsg::Input(stdin_buffer));
+                    // wait for the server to handle this input before reading any more of it,
+                    // this stops us from racing ahead of commands that take a while (eg.
+                    // opening a new pane)
+                    if receive_unblock_input.recv().is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+    let _signal_thread = thread::Builder::new()
+        .name("signal_listener".to_string())
+        .spawn({
+            let os_input = os_input.clone();
+            move || {
+                let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
+                err_ctx.add_call(ContextType::SignalListener);
+                os_input.receive_sigwinch(Box::new({
+                    let os_api = os_input.clone();
+                    move || {
+                        let new_size = os_api.get_terminal_size_using_fd(0);
+                        os_api.send_to_server(ClientToServerMsg::TerminalResize(new_size));
+                    }
+                }));
+            }
+        });
+
+    let mut stdout = os_input.get_stdout_writer();
+    let exit_reason = loop {
+        match os_input.recv_from_server() {
+            Some(ServerToClientMsg::Render(output)) => {
+                stdout
+                    .write_all(output.as_bytes())
+                    .expect("cannot write to stdout");
+                stdout.flush().expect("could not flush");
+            }
+            Some(ServerToClientMsg::UnblockInputThread) => drop(send_unblock_input.send(())),
+            Some(ServerToClientMsg::Exit(exit_reason)) => break exit_reason,
+            Some(ServerToClientMsg::Response(Response::Error(error))) => {
+                break ExitReason::Error(error)
+            }
+            // attached clients don't subscribe to events
+            Some(ServerToClientMsg::Response(_)) | Some(ServerToClientMsg::Event(_)) => {}
+            None => break ExitReason::Error("Lost the connection to the server.".to_string()),
+        }
+    };
+
+    // the terminal might have been resized since we started
+    let full_screen_ws = os_input.get_terminal_size_using_fd(0);
+    let goto_start_of_last_line = format!("\u{1b}[{};{}H", full_screen_ws.rows, 1);
+    let message = match exit_reason {
+        ExitReason::Normal => "Bye from Zellij!".to_string(),
+        ExitReason::Detached => format!(
+            "Detached from session {0:?}, run `zellij --session {0}` to reattach.",
+            session_name
+        ),
+        ExitReason::ForceDetached => format!(
+            "Detached from session {:?} by another client.",
+            session_name
+        ),
+        ExitReason::Error(backtrace) => {
+            os_input.unset_raw_mode(0);
+            let error = format!("{}\n{}", goto_start_of_last_line, backtrace);
+            let _ = os_input
+                .get_stdout_writer()
+                .write(error.as_bytes())
+                .unwrap();
+            std::process::exit(1);
+        }
+    };
+
+    let reset_style = "\u{1b}[m";
+    let show_cursor = "\u{1b}[?25h";
+    let restore_snapshot = "\u{1b}[?1049l";
+    let goodbye_message = format!(
+        "{}\n{}{}{}{}\n",
+        goto_start_of_last_line, restore_snapshot, reset_style, show_cursor, message
+    );
+
+    os_input.unset_raw_mode(0);
+    let _ = os_input
+        .get_stdout_writer()
+        .write(goodbye_message.as_bytes())
+        .unwrap();
+    os_input.get_stdout_writer().flush().unwrap();
+}
@@ -1,14 +1,28 @@
 use std::{
     cmp::Ordering,
+    collections::HashSet,
     fmt::{self, Debug, Formatter},
 };
 
+use serde::{Deserialize, Serialize};
+
 static TABSTOP_WIDTH: usize = 8; // TODO: is this always right?
 
 use crate::panes::terminal_character::{
     CharacterStyles, TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
 };
 
+/// What to include of the contents of a [`Grid`] when dumping them as text, and how.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct DumpOptions {
+    /// Include the lines that were scrolled out of view, not just the viewport.
+    pub full: bool,
+    /// Keep the styles of the text as ANSI escape codes.
+    pub ansi: bool,
+    /// Join rows that were wrapped back into the lines they were wrapped from.
+    pub join_wrapped: bool,
+}
+
 fn get_top_non_canonical_rows(rows: &mut Vec<Row>) -> Vec<Row> {
     let mut index_of_last_non_canonical_row = None;
     for (i, row) in rows.iter().enumerate() {
@@ -385,6 +399,16 @@ impl Grid {
             self.set_scroll_region_to_viewport_size();
         }
     }
+    /// Returns the hyperlinks (as in [`TerminalCharacter::link`]) of all the characters of the
+    /// grid, scrolled out of view or not.
+    pub fn links(&self) -> HashSet<usize> {
+        self.lines_above
+            .iter()
+            .chain(&self.viewport)
+            .chain(&self.lines_below)
+            .flat_map(|row| row.columns.iter().filter_map(|character| character.link))
+            .collect()
+    }
     pub fn as_character_lines(&self) -> Vec<Vec<TerminalCharacter>> {
         let mut lines: Vec<Vec<TerminalCharacter>> = self
             .viewport
@@ -402,6 +426,88 @@ impl Grid {
         }
         lines
     }
+    /// Returns the lines of this grid, including the ones that were scrolled out of view if
+    /// `full` is set, and joining wrapped rows back together if `join_wrapped` is set. The empty
+    /// lines at the bottom are left out.
+    fn lines(&self, full: bool, join_wrapped: bool) -> Vec<Vec<TerminalCharacter>> {
+        let rows: Box<dyn Iterator<Item = &Row>> = if full {
+            Box::new(
+                self.lines_above
+                    .iter()
+                    .chain(self.viewport.iter())
+                    .chain(self.lines_below.iter()),
+            )
+        } else {
+            Box::new(self.viewport.iter())
+        };
+        let mut lines: Vec<Vec<TerminalCharacter>> = vec![];
+        for row in rows {
+            if join_wrapped {
+                match lines.last_mut() {
+                    Some(line) if !row.is_canonical => line.extend(row.columns.iter().copied()),
+                    _ => lines.push(row.columns.clone()),
+                }
+            } else if row.len() > self.width {
+                // the lines above the viewport are kept whole, wrap them as they were shown
+                let wrapped_rows = row.clone().split_to_rows_of_length(self.width);
+                lines.extend(wrapped_rows.into_iter().map(|row| row.columns));
+            } else {
+                lines.push(row.columns.clone());
+            }
+        }
+        while let Some(line) = lines.last() {
+            if line.iter().all(|character| character.character == ' ') {
+                lines.pop();
+            } else {
+                break;
+            }
+        }
+        lines
+    }
+    /// Returns the text of `line`, with its styles as ANSI escape codes if `ansi` is set.
+    fn line_as_text(line: &[TerminalCharacter], ansi: bool) -> String {
+        let mut text = String::new();
+        // every line starts out with its styles reset
+        let mut character_styles = EMPTY_TERMINAL_CHARACTER.styles;
+        for character in line {
+            if ansi && character.styles != character_styles {
+                if let Some(new_styles) = character_styles.update_and_return_diff(&character.styles)
+                {
+                    text.push_str(&new_styles.to_string());
+                }
+            }
+            text.push(character.character);
+        }
+        text
+    }
+    /// Returns the last `max_lines` lines of this grid (both the ones that were scrolled out of
+    /// view and the ones in the viewport) as text, with their styles as ANSI escape codes.
+    /// Wrapped lines are joined back together and the empty lines at the bottom are left out.
+    pub fn scrollback(&self, max_lines: usize) -> String {
+        let lines = self.lines(true, true);
+        let first_line_index = lines.len().saturating_sub(max_lines);
+        let mut scrollback = String::new();
+        for line in lines.iter().skip(first_line_index) {
+            scrollback.push_str(&Grid::line_as_text(line, true));
+            scrollback.push_str("\u{1b}[m\r\n");
+        }
+        scrollback
+    }
+    /// Returns the contents of this grid as text, one line per row (or per line, if wrapped
+    /// rows are joined), as asked for in `options`.
+    pub fn dump(&self, options: DumpOptions) -> String {
+        let mut dump = String::new();
+        for line in self.lines(options.full, options.join_wrapped) {
+            if options.ansi {
+                dump.push_str(&Grid::line_as_text(&line, true));
+                dump.push_str("\u{1b}[m");
+            } else {
+                dump.push_str(Grid::line_as_text(&line, false).trim_end());
+            }
+            dump.push('\n');
+        }
+        dump
+    }
     pub fn cursor_coordinates(&self) -> Option<(usize, usize)> {
         if self.cursor.is_hidden {
             None
@@ -630,6 +736,9 @@ impl Grid {
         self.cursor.is_hidden = false;
     }
     pub fn set_scroll_region(&mut self, top_line_index: usize, bottom_line_index: usize) {
+        // like other terminals, keep the scroll region inside the viewport if it's set past it
+        // (eg. by a program that hasn't caught up with a resize yet)
+        let bottom_line_index = std::cmp::min(bottom_line_index, self.height - 1);
         self.scroll_region = Some((top_line_index, bottom_line_index));
     }
     pub fn clear_scroll_region(&mut self) {
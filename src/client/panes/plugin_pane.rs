@@ -4,7 +4,7 @@ use crate::{common::SenderWithContext, pty_bus::VteEvent, tab::Pane, wasm_vm::Pl
 
 use std::{sync::mpsc::channel, unimplemented};
 
-use crate::panes::{PaneId, PositionAndSize};
+use crate::panes::{DumpOptions, PaneId, PositionAndSize};
 
 pub struct PluginPane {
     pub pid: u32,
@@ -15,6 +15,7 @@ pub struct PluginPane {
     pub position_and_size_override: Option<PositionAndSize>,
     pub send_plugin_instructions: SenderWithContext<PluginInstruction>,
     pub max_height: Option<usize>,
+    pub title: Option<String>,
 }
 
 impl PluginPane {
@@ -32,6 +33,7 @@ impl PluginPane {
             position_and_size_override: None,
             send_plugin_instructions,
             max_height: None,
+            title: None,
         }
     }
 }
@@ -89,6 +91,21 @@ impl Pane for PluginPane {
         unimplemented!() // FIXME: Shouldn't need this implmented?
     }
 
+    fn position_and_size(&self) -> PositionAndSize {
+        self.position_and_size
+    }
+    fn scrollback(&self, _max_lines: usize) -> Option<String> {
+        None
+    }
+    fn dump(&self, _options: DumpOptions) -> Option<String> {
+        None
+    }
+    fn title(&self) -> Option<String> {
+        self.title.clone()
+    }
+    fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
     fn position_and_size_override(&self) -> Option<PositionAndSize> {
         self.position_and_size_override
     }
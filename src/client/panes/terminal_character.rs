@@ -16,6 +16,7 @@ pub const EMPTY_TERMINAL_CHARACTER: TerminalCharacter = TerminalCharacter {
         dim: Some(AnsiCode::Reset),
         italic: Some(AnsiCode::Reset),
     },
+    link: None,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -66,7 +67,7 @@ impl NamedColor {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CharacterStyles {
     pub foreground: Option<AnsiCode>,
     pub background: Option<AnsiCode>,
@@ -563,6 +564,9 @@ impl Display for CharacterStyles {
 pub struct TerminalCharacter {
     pub character: char,
     pub styles: CharacterStyles,
+    /// The hyperlink (OSC 8) the character is part of, as its id among the links of its pane,
+    /// which keeps characters small and `Copy`.
+    pub link: Option<usize>,
 }
 
 impl ::std::fmt::Debug for TerminalCharacter {
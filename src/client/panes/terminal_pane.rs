@@ -1,19 +1,22 @@
 #![allow(clippy::clippy::if_same_then_else)]
 
+use crate::boundaries::TOP_BORDER_ROWS;
 use crate::tab::Pane;
 use ::nix::pty::Winsize;
+use ::std::collections::HashMap;
 use ::std::os::unix::io::RawFd;
 use ::vte::Perform;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
-use crate::panes::grid::Grid;
+use crate::panes::grid::{DumpOptions, Grid};
 use crate::panes::terminal_character::{
     CharacterStyles, TerminalCharacter, EMPTY_TERMINAL_CHARACTER,
 };
 use crate::utils::logging::debug_log_to_file;
 use crate::VteEvent;
 
-#[derive(PartialEq, Eq, Ord, PartialOrd, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Ord, PartialOrd, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PaneId {
     Terminal(RawFd),
     Plugin(u32), // FIXME: Drop the trait object, make this a wrapper for the struct?
@@ -21,7 +24,7 @@ pub enum PaneId {
 
 /// Contains the position and size of a [`Pane`], or more generally of any terminal, measured
 /// in character rows and columns.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PositionAndSize {
     pub x: usize,
     pub y: usize,
@@ -39,6 +42,21 @@ impl From<Winsize> for PositionAndSize {
     }
 }
 
+/// What a terminal asks for in its output that its pane can't do by itself, for the pane's tab
+/// to take care of.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TerminalRequest {
+    /// Copying `content` (as base64) to the clipboard (OSC 52), `selection` being which of the
+    /// clipboards.
+    SetClipboard { selection: String, content: String },
+    /// Asking for the default foreground (OSC 10) or background (OSC 11) color, the answer
+    /// ending the way the question did.
+    QueryDefaultColor {
+        background: bool,
+        bell_terminated: bool,
+    },
+}
+
 #[derive(Debug)]
 pub struct TerminalPane {
     pub grid: Grid,
@@ -50,10 +68,39 @@ pub struct TerminalPane {
     pub position_and_size_override: Option<PositionAndSize>,
     pub cursor_key_mode: bool, // DECCKM - when set, cursor keys should send ANSI direction codes (eg. "OD") instead of the arrow keys (eg. "[D")
     pub max_height: Option<usize>,
+    pub title: Option<String>,
+    /// The title set by what's running in the terminal (OSC 0 or 2), if any
+    terminal_title: Option<String>,
+    /// The parameters and URI of the hyperlinks (OSC 8) of the terminal, as they're sent to open
+    /// one, by the id that characters point to them with in [`TerminalCharacter::link`].
+    links: HashMap<usize, String>,
+    /// The ids of the hyperlinks in `links`, so that a link that's sent again gets the same one
+    link_ids: HashMap<String, usize>,
+    /// The id the next new hyperlink gets
+    next_link_id: usize,
+    /// How many hyperlinks can be kept before the ones no character points to are dropped
+    max_links: usize,
+    /// The hyperlink the characters printed next are part of
+    pending_link: Option<usize>,
+    /// What the terminal asked for, until its tab takes it
+    requests: Vec<TerminalRequest>,
     pending_styles: CharacterStyles,
     clear_viewport_before_rendering: bool,
 }
 
+/// How many hyperlinks a terminal keeps at the very least before dropping the ones that aren't
+/// shown anymore.
+const MIN_MAX_LINKS: usize = 256;
+
+/// Returns the OSC 8 sequence that starts the hyperlink with `link` as its parameters and URI,
+/// or ends the current one if there's no `link`.
+fn hyperlink(link: Option<&String>) -> String {
+    format!(
+        "\u{1b}]8;{}\u{1b}\\",
+        link.map_or(";", |link| link.as_str())
+    )
+}
+
 impl Pane for TerminalPane {
     fn x(&self) -> usize {
         self.get_x()
@@ -73,8 +120,7 @@ impl Pane for TerminalPane {
         self.mark_for_rerender();
     }
     fn change_pos_and_size(&mut self, position_and_size: &PositionAndSize) {
-        self.position_and_size.columns = position_and_size.columns;
-        self.position_and_size.rows = position_and_size.rows;
+        self.position_and_size = *position_and_size;
         self.reflow_lines();
         self.mark_for_rerender();
     }
@@ -166,6 +212,29 @@ impl Pane for TerminalPane {
         input_bytes
     }
 
+    fn position_and_size(&self) -> PositionAndSize {
+        self.position_and_size
+    }
+    fn scrollback(&self, max_lines: usize) -> Option<String> {
+        // leave out whatever a fullscreen app (eg. an editor) is showing on the alternate screen
+        let grid = self.alternative_grid.as_ref().unwrap_or(&self.grid);
+        Some(grid.scrollback(max_lines))
+    }
+    fn dump(&self, options: DumpOptions) -> Option<String> {
+        Some(self.grid.dump(options))
+    }
+    fn title(&self) -> Option<String> {
+        self.title.clone()
+    }
+    fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+    fn terminal_title(&self) -> Option<String> {
+        self.terminal_title.clone()
+    }
+    fn take_terminal_requests(&mut self) -> Vec<TerminalRequest> {
+        self.requests.drain(..).collect()
+    }
     fn position_and_size_override(&self) -> Option<PositionAndSize> {
         self.position_and_size_override
     }
@@ -201,10 +270,11 @@ impl Pane for TerminalPane {
             let buffer_lines = &self.read_buffer_as_lines();
             let display_cols = self.get_columns();
             let mut character_styles = CharacterStyles::new();
+            let mut link = None;
             if self.clear_viewport_before_rendering {
                 for line_index in 0..self.grid.height {
                     let x = self.get_x();
-                    let y = self.get_y();
+                    let y = self.get_y() + TOP_BORDER_ROWS;
                     vte_output = format!(
                         "{}\u{1b}[{};{}H\u{1b}[m",
                         vte_output,
@@ -219,13 +289,17 @@ impl Pane for TerminalPane {
             }
             for (row, line) in buffer_lines.iter().enumerate() {
                 let x = self.get_x();
-                let y = self.get_y();
+                let y = self.get_y() + TOP_BORDER_ROWS;
                 vte_output = format!("{}\u{1b}[{};{}H\u{1b}[m", vte_output, y + row + 1, x + 1); // goto row/col and reset styles
                 for (col, t_character) in line.iter().enumerate() {
                     if col < display_cols {
                         // in some cases (eg. while resizing) some characters will spill over
                         // before they are corrected by the shell (for the prompt) or by reflowing
                         // lines
+                        if t_character.link != link {
+                            link = t_character.link;
+                            vte_output.push_str(&hyperlink(link.and_then(|l| self.links.get(&l))));
+                        }
                         if let Some(new_styles) =
                             character_styles.update_and_return_diff(&t_character.styles)
                         {
@@ -237,6 +311,11 @@ impl Pane for TerminalPane {
                         vte_output.push(t_character.character);
                     }
                 }
+                if link.is_some() {
+                    // what comes after the line isn't part of this pane
+                    link = None;
+                    vte_output.push_str(&hyperlink(None));
+                }
                 character_styles.clear();
             }
             self.mark_for_rerender();
@@ -323,7 +402,41 @@ impl TerminalPane {
             cursor_key_mode: false,
             clear_viewport_before_rendering: false,
             max_height: None,
+            title: None,
+            terminal_title: None,
+            links: HashMap::new(),
+            link_ids: HashMap::new(),
+            next_link_id: 0,
+            max_links: MIN_MAX_LINKS,
+            pending_link: None,
+            requests: vec![],
+        }
+    }
+    /// Returns the id of the hyperlink `link`, giving it a new one if it's new.
+    fn link_id(&mut self, link: String) -> usize {
+        if let Some(id) = self.link_ids.get(&link) {
+            return *id;
+        }
+        if self.links.len() >= self.max_links {
+            self.drop_unused_links();
         }
+        let id = self.next_link_id;
+        self.next_link_id += 1;
+        self.links.insert(id, link.clone());
+        self.link_ids.insert(link, id);
+        id
+    }
+    /// Drops the hyperlinks that no character points to anymore, letting the terminal keep twice
+    /// as many as are left before doing it again.
+    fn drop_unused_links(&mut self) {
+        let mut used_links = self.grid.links();
+        if let Some(alternative_grid) = self.alternative_grid.as_ref() {
+            used_links.extend(alternative_grid.links());
+        }
+        used_links.extend(self.pending_link);
+        self.links.retain(|id, _| used_links.contains(id));
+        self.link_ids.retain(|_, id| used_links.contains(id));
+        self.max_links = MIN_MAX_LINKS.max(2 * self.links.len());
     }
     pub fn mark_for_rerender(&mut self) {
         self.should_render = true;
@@ -361,6 +474,15 @@ impl TerminalPane {
         }
     }
 
+    /// Fills this (fresh) pane with `scrollback`, as returned by [`Pane::scrollback`] (eg. in a
+    /// previous run), leaving the cursor below it for the output of the pane's terminal.
+    pub fn load_scrollback(&mut self, scrollback: &[u8]) {
+        let mut vte_parser = vte::Parser::new();
+        for &byte in scrollback {
+            vte_parser.advance(self, byte);
+        }
+        self.mark_for_rerender();
+    }
     pub fn read_buffer_as_lines(&self) -> Vec<Vec<TerminalCharacter>> {
         self.grid.as_character_lines()
     }
@@ -400,6 +522,7 @@ impl vte::Perform for TerminalPane {
         let terminal_character = TerminalCharacter {
             character: c,
             styles: self.pending_styles,
+            link: self.pending_link,
         };
         self.grid.add_character(terminal_character);
     }
@@ -438,8 +561,40 @@ impl vte::Perform for TerminalPane {
         // TBD
     }
 
-    fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {
-        // TBD
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        // the last parameter of these can have semicolons in it, which split it into several
+        let join = |params: &[&[u8]]| String::from_utf8_lossy(&params.join(&b';')).into_owned();
+        match params {
+            // set the window title (0 also sets the icon name, which we don't have)
+            [b"0", title @ ..] | [b"2", title @ ..] => {
+                let title = join(title);
+                self.terminal_title = if title.is_empty() { None } else { Some(title) };
+            }
+            // start a hyperlink, or end it with an empty URI
+            [b"8", link_params, uri @ ..] => {
+                let uri = join(uri);
+                self.pending_link = if uri.is_empty() {
+                    None
+                } else {
+                    let link = format!("{};{}", String::from_utf8_lossy(link_params), uri);
+                    Some(self.link_id(link))
+                };
+            }
+            // copy to the clipboard, what's in the clipboard is never asked for outside of it
+            [b"52", selection, content] if *content != b"?" => {
+                self.requests.push(TerminalRequest::SetClipboard {
+                    selection: String::from_utf8_lossy(selection).into_owned(),
+                    content: String::from_utf8_lossy(content).into_owned(),
+                });
+            }
+            [b"10", b"?"] | [b"11", b"?"] => {
+                self.requests.push(TerminalRequest::QueryDefaultColor {
+                    background: params[0] == b"11",
+                    bell_terminated,
+                });
+            }
+            _ => {}
+        }
     }
 
     fn csi_dispatch(&mut self, params: &[i64], _intermediates: &[u8], _ignore: bool, c: char) {
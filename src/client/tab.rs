@@ -1,17 +1,30 @@
 //! `Tab`s holds multiple panes. It tracks their coordinates (x/y) and size, as well as how they should be resized
 
-use crate::common::{AppInstruction, SenderWithContext};
-use crate::panes::{PaneId, PositionAndSize, TerminalPane};
-use crate::pty_bus::{PtyInstruction, VteEvent};
-use crate::{boundaries::Boundaries, panes::PluginPane};
-use crate::{layout::Layout, wasm_vm::PluginInstruction};
+use crate::common::events::{Event, EventSender};
+use crate::common::ipc::{ClientId, PaneInfo, ServerToClientMsg};
+use crate::common::theme::{PaletteColor, Theme};
+use crate::common::SenderWithContext;
+use crate::panes::{DumpOptions, PaneId, PositionAndSize, TerminalPane, TerminalRequest};
+use crate::pty_bus::{ExitStatus, PtyInstruction, VteEvent};
+use crate::screen::ScreenSettings;
+use crate::{
+    boundaries::{Boundaries, TOP_BORDER_ROWS},
+    panes::PluginPane,
+};
+use crate::{
+    layout::{Direction, Layout, SplitSize},
+    wasm_vm::PluginInstruction,
+};
 use crate::{os_input_output::OsApi, utils::shared::pad_to_size};
+use std::fs;
 use std::os::unix::io::RawFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use std::{
     cmp::Reverse,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
 };
-use std::{io::Write, sync::mpsc::channel};
+use std::{fmt::Write, sync::mpsc::channel};
 
 use crate::utils::logging::debug_log_to_file;
 
@@ -21,6 +34,10 @@ const MIN_TERMINAL_WIDTH: usize = 4;
 
 type BorderAndPaneIds = (usize, Vec<PaneId>);
 
+/// Where a pane starts and ends along one axis of its tab (its border on the right or bottom
+/// included), and whether it has a fixed size along that axis.
+type PaneSpan = (usize, usize, bool);
+
 fn split_vertically_with_gap(rect: &PositionAndSize) -> (PositionAndSize, PositionAndSize) {
     let width_of_each_half = (rect.columns - 1) / 2;
     let mut first_rect = *rect;
@@ -49,18 +66,96 @@ fn split_horizontally_with_gap(rect: &PositionAndSize) -> (PositionAndSize, Posi
     (first_rect, second_rect)
 }
 
+/// Maps the edges between the panes of a tab along one of its axes from a length of
+/// `old_length` to `new_length`, where every pane spans from one edge to another.
+///
+/// The panes with a fixed size keep it, and the rest of the space is split proportionally to
+/// how it was split before. Returns `None` if the fixed size panes don't fit.
+fn scale_edges(
+    spans: &[PaneSpan],
+    old_length: usize,
+    new_length: usize,
+) -> Option<BTreeMap<usize, usize>> {
+    let mut edges: BTreeSet<usize> = spans.iter().flat_map(|(s, e, _)| vec![*s, *e]).collect();
+    edges.insert(0);
+    edges.insert(old_length);
+    let edges: Vec<usize> = edges.into_iter().collect();
+    let segment_is_fixed = |start: usize, end: usize| {
+        spans
+            .iter()
+            .any(|(s, e, fixed)| *fixed && *s <= start && end <= *e)
+    };
+    let fixed_length: usize = edges
+        .windows(2)
+        .filter(|segment| segment_is_fixed(segment[0], segment[1]))
+        .map(|segment| segment[1] - segment[0])
+        .sum();
+    let old_flexible_length = old_length - fixed_length;
+    let new_flexible_length = new_length.checked_sub(fixed_length)?;
+    if old_flexible_length == 0 && new_flexible_length > 0 {
+        return None;
+    }
+
+    let mut scaled_edges = BTreeMap::new();
+    let (mut fixed_so_far, mut flexible_so_far) = (0, 0);
+    scaled_edges.insert(0, 0);
+    for segment in edges.windows(2) {
+        if segment_is_fixed(segment[0], segment[1]) {
+            fixed_so_far += segment[1] - segment[0];
+        } else {
+            flexible_so_far += segment[1] - segment[0];
+        }
+        // rounded to the closest column/row
+        let scaled_flexible_so_far = (flexible_so_far * new_flexible_length
+            + old_flexible_length / 2)
+            / old_flexible_length.max(1);
+        scaled_edges.insert(segment[1], fixed_so_far + scaled_flexible_so_far);
+    }
+    Some(scaled_edges)
+}
+
+/// The space the panes of a [`Tab`] on a screen of size `screen_size` are laid out in, below the
+/// row of their top border.
+fn panes_area(screen_size: PositionAndSize) -> PositionAndSize {
+    PositionAndSize {
+        rows: screen_size.rows.saturating_sub(TOP_BORDER_ROWS),
+        ..screen_size
+    }
+}
+
+/// How long the command running in a terminal pane is shown as its title before it's looked up
+/// again.
+const FOREGROUND_COMMAND_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
 pub struct Tab {
     pub index: usize,
     panes: BTreeMap<PaneId, Box<dyn Pane>>,
     panes_to_hide: HashSet<PaneId>,
+    /// The terminal panes kept open once what ran in them exited, with how it did.
+    held_panes: HashMap<PaneId, ExitStatus>,
+    /// The plugins loaded into the plugin panes of this [`Tab`], by plugin id.
+    plugin_paths: HashMap<u32, PathBuf>,
     active_terminal: Option<PaneId>,
     max_panes: Option<usize>,
+    /// The colors the borders of the panes are drawn with.
+    theme: Theme,
+    /// Whether what runs in the panes can copy to the clipboard of the clients' terminals.
+    allow_clipboard_writes: bool,
+    /// The space the panes are laid out in: the screen, below the row of their top border.
     full_screen_ws: PositionAndSize,
     fullscreen_is_active: bool,
     os_api: Box<dyn OsApi>,
+    /// The clients this [`Tab`] is rendered to, with the size of their terminals.
+    connected_clients: BTreeMap<ClientId, PositionAndSize>,
+    /// The clients whose terminal is larger than this [`Tab`] and that haven't had the part of it
+    /// outside of the [`Tab`] filled since they were attached, resized or force-rendered.
+    clients_with_unfilled_area: BTreeSet<ClientId>,
+    /// The commands last seen running in the terminal panes, by pid, with when they were looked
+    /// up, so that they aren't asked of the os every time the panes are rendered.
+    foreground_commands: HashMap<RawFd, (Instant, Option<String>)>,
     pub send_pty_instructions: SenderWithContext<PtyInstruction>,
     pub send_plugin_instructions: SenderWithContext<PluginInstruction>,
-    pub send_app_instructions: SenderWithContext<AppInstruction>,
+    events: EventSender,
 }
 
 // FIXME: Use a struct that has a pane_type enum, to reduce all of the duplication
@@ -75,6 +170,24 @@ pub trait Pane {
     fn handle_event(&mut self, event: VteEvent);
     fn cursor_coordinates(&self) -> Option<(usize, usize)>;
     fn adjust_input_to_terminal(&self, input_bytes: Vec<u8>) -> Vec<u8>;
+    /// The position and size of the pane, disregarding any override (eg. when it's fullscreen).
+    fn position_and_size(&self) -> PositionAndSize;
+    /// The last `max_lines` lines of output of the pane as ANSI text, if it has any.
+    fn scrollback(&self, max_lines: usize) -> Option<String>;
+    /// The contents of the pane as text, as asked for in `options`, if it has any.
+    fn dump(&self, options: DumpOptions) -> Option<String>;
+    /// The title the pane was given, by renaming it or by its layout, if any.
+    fn title(&self) -> Option<String>;
+    fn set_title(&mut self, title: Option<String>);
+    /// The title set by what's running in the pane, if any.
+    fn terminal_title(&self) -> Option<String> {
+        None
+    }
+    /// Takes what the pane was asked for by what's running in it since the last time, that it
+    /// can't do by itself.
+    fn take_terminal_requests(&mut self) -> Vec<TerminalRequest> {
+        vec![]
+    }
 
     fn position_and_size_override(&self) -> Option<PositionAndSize>;
     fn should_render(&self) -> bool;
@@ -157,19 +270,22 @@ pub trait Pane {
 }
 
 impl Tab {
-    // FIXME: Too many arguments here! Maybe bundle all of the senders for the whole program in a struct?
+    /// Creates a new [`Tab`] sized and drawn according to the `settings` of its [`Screen`],
+    /// containing a single terminal pane if `pane_id` is given.
+    ///
+    /// [`Screen`]: crate::screen::Screen
     pub fn new(
         index: usize,
-        full_screen_ws: &PositionAndSize,
+        settings: &ScreenSettings,
         mut os_api: Box<dyn OsApi>,
         send_pty_instructions: SenderWithContext<PtyInstruction>,
         send_plugin_instructions: SenderWithContext<PluginInstruction>,
-        send_app_instructions: SenderWithContext<AppInstruction>,
-        max_panes: Option<usize>,
+        events: EventSender,
         pane_id: Option<PaneId>,
     ) -> Self {
+        let full_screen_ws = panes_area(settings.full_screen_ws);
         let panes = if let Some(PaneId::Terminal(pid)) = pane_id {
-            let new_terminal = TerminalPane::new(pid, *full_screen_ws);
+            let new_terminal = TerminalPane::new(pid, full_screen_ws);
             os_api.set_terminal_size_using_fd(
                 new_terminal.pid,
                 new_terminal.columns() as u16,
@@ -181,21 +297,44 @@ impl Tab {
         } else {
             BTreeMap::new()
         };
+        if let Some(pane_id) = pane_id {
+            events.send(Event::PaneOpened {
+                tab: index,
+                pane: pane_id,
+            });
+        }
         Tab {
             index,
             panes,
-            max_panes,
+            max_panes: settings.max_panes,
+            theme: settings.theme.clone(),
+            allow_clipboard_writes: settings.allow_clipboard_writes,
             panes_to_hide: HashSet::new(),
+            held_panes: HashMap::new(),
+            plugin_paths: HashMap::new(),
             active_terminal: pane_id,
-            full_screen_ws: *full_screen_ws,
+            full_screen_ws,
             fullscreen_is_active: false,
             os_api,
-            send_app_instructions,
+            connected_clients: BTreeMap::new(),
+            clients_with_unfilled_area: BTreeSet::new(),
+            foreground_commands: HashMap::new(),
             send_pty_instructions,
             send_plugin_instructions,
+            events,
         }
     }
 
+    /// Adds `pane` to this [`Tab`] under the id `pid`, letting the subscribers to its events
+    /// know about it.
+    fn add_pane(&mut self, pid: PaneId, pane: Box<dyn Pane>) {
+        self.panes.insert(pid, pane);
+        self.events.send(Event::PaneOpened {
+            tab: self.index,
+            pane: pid,
+        });
+    }
+
     pub fn apply_layout(&mut self, layout: Layout, new_pids: Vec<RawFd>) {
         // TODO: this should be an attribute on Screen instead of full_screen_ws
         let free_space = PositionAndSize {
@@ -211,9 +350,12 @@ impl Tab {
             // for now the layout only supports terminal panes
             if let PaneId::Terminal(pid) = pane_kind {
                 match positions_and_size.next() {
-                    Some((_, position_and_size)) => {
+                    Some((layout, position_and_size)) => {
                         terminal_pane.reset_size_and_position_override();
                         terminal_pane.change_pos_and_size(&position_and_size);
+                        if layout.title.is_some() {
+                            terminal_pane.set_title(layout.title.clone());
+                        }
                         self.os_api.set_terminal_size_using_fd(
                             *pid,
                             position_and_size.columns as u16,
@@ -237,23 +379,37 @@ impl Tab {
                     .send(PluginInstruction::Load(pid_tx, plugin.clone()))
                     .unwrap();
                 let pid = pid_rx.recv().unwrap();
-                let new_plugin = PluginPane::new(
+                let mut new_plugin = PluginPane::new(
                     pid,
                     *position_and_size,
                     self.send_plugin_instructions.clone(),
                 );
-                self.panes.insert(PaneId::Plugin(pid), Box::new(new_plugin));
+                new_plugin.set_title(layout.title.clone());
+                self.add_pane(PaneId::Plugin(pid), Box::new(new_plugin));
+                self.plugin_paths.insert(pid, plugin.clone());
             } else {
                 // there are still panes left to fill, use the pids we received in this method
                 let pid = new_pids.next().unwrap(); // if this crashes it means we got less pids than there are panes in this layout
-                let new_terminal = TerminalPane::new(*pid, *position_and_size);
+                let mut new_terminal = TerminalPane::new(*pid, *position_and_size);
+                new_terminal.set_title(layout.title.clone());
                 self.os_api.set_terminal_size_using_fd(
                     new_terminal.pid,
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes
-                    .insert(PaneId::Terminal(*pid), Box::new(new_terminal));
+                if let Some(scrollback_path) = &layout.scrollback {
+                    match fs::read(scrollback_path) {
+                        Ok(scrollback) => new_terminal.load_scrollback(&scrollback),
+                        Err(e) => {
+                            let _ = debug_log_to_file(format!(
+                                "could not load scrollback {}: {}",
+                                scrollback_path.display(),
+                                e
+                            ));
+                        }
+                    }
+                }
+                self.add_pane(PaneId::Terminal(*pid), Box::new(new_terminal));
             }
         }
         for unused_pid in new_pids {
@@ -267,6 +423,128 @@ impl Tab {
         self.active_terminal = self.panes.iter().map(|(id, _)| id.to_owned()).next();
         self.render();
     }
+    /// Returns a [`Layout`] that recreates the panes of this [`Tab`] (the ones that aren't hidden)
+    /// along with the working directory and running command of its terminals, or `None` if it
+    /// doesn't have any panes left. The last `scrollback_lines` lines of each terminal are taken
+    /// along, if given.
+    pub fn layout(&self, scrollback_lines: Option<usize>) -> Option<Layout> {
+        let panes: Vec<(Layout, PositionAndSize)> = self
+            .panes
+            .iter()
+            .filter(|(id, _)| !self.panes_to_hide.contains(id))
+            .map(|(id, pane)| {
+                let position_and_size = pane.position_and_size();
+                let (plugin, cwd, command) = match id {
+                    PaneId::Terminal(pid) => (
+                        None,
+                        self.os_api.get_cwd(*pid),
+                        self.os_api.get_foreground_command(*pid),
+                    ),
+                    PaneId::Plugin(pid) => (self.plugin_paths.get(pid).cloned(), None, None),
+                };
+                let part = Layout {
+                    direction: Direction::Horizontal,
+                    parts: vec![],
+                    // kept when the pane ends up in a horizontal split
+                    split_size: pane
+                        .max_height()
+                        .map(|_| SplitSize::Fixed(position_and_size.rows as u16)),
+                    plugin,
+                    cwd,
+                    command,
+                    title: pane.title(),
+                    scrollback: None,
+                    scrollback_to_save: scrollback_lines
+                        .and_then(|max_lines| pane.scrollback(max_lines)),
+                };
+                (part, position_and_size)
+            })
+            .collect();
+        if panes.is_empty() {
+            return None;
+        }
+        Some(Layout::from_panes_in_space(&self.full_screen_ws, panes))
+    }
+    /// Returns a description of every pane of this [`Tab`], hidden or not.
+    pub fn pane_infos(&self) -> Vec<PaneInfo> {
+        self.panes
+            .iter()
+            .map(|(id, pane)| {
+                let (command, cwd) = match id {
+                    PaneId::Terminal(pid) => (
+                        self.os_api.get_foreground_command(*pid),
+                        self.os_api.get_cwd(*pid),
+                    ),
+                    PaneId::Plugin(_) => (None, None),
+                };
+                let focused = self.active_terminal == Some(*id);
+                PaneInfo {
+                    id: *id,
+                    geometry: pane.position_and_size(),
+                    selectable: pane.selectable(),
+                    fullscreen: focused && self.fullscreen_is_active,
+                    focused,
+                    title: self
+                        .pane_title(*id, pane.as_ref())
+                        .or_else(|| command.clone()),
+                    command,
+                    cwd,
+                    exit_status: self.held_panes.get(id).copied(),
+                }
+            })
+            .collect()
+    }
+    /// Returns the title shown on the border of `pane`: the one it was given, or else the one
+    /// set by what's running in it, or else what's running in it (the command running in its
+    /// shell, as of when it was last looked up, or the name of its plugin).
+    fn pane_title(&self, id: PaneId, pane: &dyn Pane) -> Option<String> {
+        let title = pane.title().or_else(|| pane.terminal_title());
+        title.or_else(|| match id {
+            PaneId::Terminal(pid) => self
+                .foreground_commands
+                .get(&pid)
+                .and_then(|(_, command)| command.clone()),
+            PaneId::Plugin(pid) => self
+                .plugin_paths
+                .get(&pid)
+                .and_then(|path| path.file_stem())
+                .map(|name| name.to_string_lossy().into_owned()),
+        })
+    }
+    /// Looks up again the commands running in the terminal panes that were last looked up longer
+    /// than [`FOREGROUND_COMMAND_REFRESH_INTERVAL`] ago, and forgets those of the closed panes.
+    fn refresh_foreground_commands(&mut self) {
+        let now = Instant::now();
+        let panes = &self.panes;
+        self.foreground_commands
+            .retain(|pid, _| panes.contains_key(&PaneId::Terminal(*pid)));
+        for id in self.panes.keys() {
+            if let PaneId::Terminal(pid) = *id {
+                let is_stale = match self.foreground_commands.get(&pid) {
+                    Some((looked_up_at, _)) => {
+                        now.duration_since(*looked_up_at) >= FOREGROUND_COMMAND_REFRESH_INTERVAL
+                    }
+                    None => true,
+                };
+                if is_stale {
+                    let command = self.os_api.get_foreground_command(pid);
+                    self.foreground_commands.insert(pid, (now, command));
+                }
+            }
+        }
+    }
+    /// Gives the focused pane `title`, or takes the one it was given away if it's empty, so that
+    /// what's running in it is shown again.
+    pub fn rename_active_pane(&mut self, title: String) {
+        if let Some(active_pane_id) = self.get_active_pane_id() {
+            let title = if title.is_empty() { None } else { Some(title) };
+            self.panes
+                .get_mut(&active_pane_id)
+                .unwrap()
+                .set_title(title);
+            self.render();
+        }
+    }
     pub fn new_pane(&mut self, pid: PaneId) {
         self.close_down_to_max_terminals();
         if self.fullscreen_is_active {
@@ -281,7 +559,7 @@ impl Tab {
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.add_pane(pid, Box::new(new_terminal));
                 self.active_terminal = Some(pid);
             }
         } else {
@@ -333,7 +611,7 @@ impl Tab {
                         bottom_winsize.rows as u16,
                     );
                     terminal_to_split.change_pos_and_size(&top_winsize);
-                    self.panes.insert(pid, Box::new(new_terminal));
+                    self.add_pane(pid, Box::new(new_terminal));
                     if let PaneId::Terminal(terminal_id_to_split) = terminal_id_to_split {
                         self.os_api.set_terminal_size_using_fd(
                             terminal_id_to_split,
@@ -354,7 +632,7 @@ impl Tab {
                         right_winsize.rows as u16,
                     );
                     terminal_to_split.change_pos_and_size(&left_winsize);
-                    self.panes.insert(pid, Box::new(new_terminal));
+                    self.add_pane(pid, Box::new(new_terminal));
                     if let PaneId::Terminal(terminal_id_to_split) = terminal_id_to_split {
                         self.os_api.set_terminal_size_using_fd(
                             terminal_id_to_split,
@@ -382,7 +660,7 @@ impl Tab {
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.add_pane(pid, Box::new(new_terminal));
                 self.active_terminal = Some(pid);
             }
         } else {
@@ -413,7 +691,7 @@ impl Tab {
                     bottom_winsize.columns as u16,
                     bottom_winsize.rows as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.add_pane(pid, Box::new(new_terminal));
 
                 if let PaneId::Terminal(active_terminal_pid) = active_pane_id {
                     self.os_api.set_terminal_size_using_fd(
@@ -442,7 +720,7 @@ impl Tab {
                     new_terminal.columns() as u16,
                     new_terminal.rows() as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.add_pane(pid, Box::new(new_terminal));
                 self.active_terminal = Some(pid);
             }
         } else {
@@ -473,7 +751,7 @@ impl Tab {
                     right_winsize.columns as u16,
                     right_winsize.rows as u16,
                 );
-                self.panes.insert(pid, Box::new(new_terminal));
+                self.add_pane(pid, Box::new(new_terminal));
 
                 if let PaneId::Terminal(active_terminal_pid) = active_pane_id {
                     self.os_api.set_terminal_size_using_fd(
@@ -514,26 +792,77 @@ impl Tab {
         // the reason
         if let Some(terminal_output) = self.panes.get_mut(&PaneId::Terminal(pid)) {
             terminal_output.handle_event(event);
+            let requests = terminal_output.take_terminal_requests();
+            for request in requests {
+                self.handle_terminal_request(pid, request);
+            }
         }
     }
-    pub fn write_to_active_terminal(&mut self, input_bytes: Vec<u8>) {
-        match self.get_active_pane_id() {
-            Some(PaneId::Terminal(active_terminal_id)) => {
-                let active_terminal = self.get_active_pane().unwrap();
-                let mut adjusted_input = active_terminal.adjust_input_to_terminal(input_bytes);
+    /// Does what the terminal with PTY file descriptor `pid` asked its pane for.
+    fn handle_terminal_request(&mut self, pid: RawFd, request: TerminalRequest) {
+        match request {
+            TerminalRequest::SetClipboard { selection, content } => {
+                if self.allow_clipboard_writes {
+                    // copied to the clipboard of the terminals of the clients
+                    let set_clipboard = format!("\u{1b}]52;{};{}\u{7}", selection, content);
+                    for client_id in self.connected_clients.keys() {
+                        self.os_api.send_to_client(
+                            *client_id,
+                            ServerToClientMsg::Render(set_clipboard.clone()),
+                        );
+                    }
+                }
+            }
+            TerminalRequest::QueryDefaultColor {
+                background,
+                bell_terminated,
+            } => {
+                // the default colors of panes are the ones of the terminals of the clients,
+                // which there's no telling, but which the theme is expected to go with
+                let (osc, color, default_color) = if background {
+                    (11, self.theme.bg, PaletteColor::Index(0))
+                } else {
+                    (10, self.theme.fg, PaletteColor::Index(7))
+                };
+                let (r, g, b) = color.rgb().or_else(|| default_color.rgb()).unwrap();
+                let terminator = if bell_terminated { "\u{7}" } else { "\u{1b}\\" };
+                // as 16 bit components, like xterm answers
+                let answer = format!(
+                    "\u{1b}]{};rgb:{:04x}/{:04x}/{:04x}{}",
+                    osc,
+                    r as u16 * 257,
+                    g as u16 * 257,
+                    b as u16 * 257,
+                    terminator
+                );
+                // what asked might have exited since
+                let _ = self
+                    .os_api
+                    .write_to_tty_stdin(pid, &mut answer.into_bytes());
+            }
+        }
+    }
+    /// Writes `input_bytes` to the pane with id `pane_id`, adjusted to the state of its terminal.
+    pub fn write_to_pane(&mut self, pane_id: PaneId, input_bytes: Vec<u8>) {
+        match pane_id {
+            PaneId::Terminal(terminal_id) => {
+                let terminal = match self.panes.get(&pane_id) {
+                    Some(terminal) => terminal,
+                    None => return,
+                };
+                let mut adjusted_input = terminal.adjust_input_to_terminal(input_bytes);
                 self.os_api
-                    .write_to_tty_stdin(active_terminal_id, &mut adjusted_input)
+                    .write_to_tty_stdin(terminal_id, &mut adjusted_input)
                     .expect("failed to write to terminal");
                 self.os_api
-                    .tcdrain(active_terminal_id)
+                    .tcdrain(terminal_id)
                     .expect("failed to drain terminal");
             }
-            Some(PaneId::Plugin(pid)) => {
+            PaneId::Plugin(pid) => {
                 self.send_plugin_instructions
                     .send(PluginInstruction::Input(pid, input_bytes))
                     .unwrap();
             }
-            _ => {}
         }
     }
     pub fn get_active_terminal_cursor_position(&self) -> Option<(usize, usize)> {
@@ -543,7 +872,7 @@ impl Tab {
             .cursor_coordinates()
             .map(|(x_in_terminal, y_in_terminal)| {
                 let x = active_terminal.x() + x_in_terminal;
-                let y = active_terminal.y() + y_in_terminal;
+                let y = active_terminal.y() + TOP_BORDER_ROWS + y_in_terminal;
                 (x, y)
             })
     }
@@ -595,24 +924,145 @@ impl Tab {
     pub fn toggle_fullscreen_is_active(&mut self) {
         self.fullscreen_is_active = !self.fullscreen_is_active;
     }
+    /// Resizes this [`Tab`] to fill a screen of size `new_screen_size`, scaling its panes
+    /// proportionally so that their content reflows.
+    ///
+    /// If its panes wouldn't all fit in their minimum size, the [`Tab`] keeps its current size.
+    pub fn resize_whole_tab(&mut self, new_screen_size: PositionAndSize) {
+        let new_screen_size = panes_area(new_screen_size);
+        if new_screen_size == self.full_screen_ws {
+            return;
+        }
+        let active_pane_id = self.get_active_pane_id();
+        if self.fullscreen_is_active {
+            // scale the panes as they are laid out behind the fullscreen one
+            if let Some(active_pane) = active_pane_id.and_then(|id| self.panes.get_mut(&id)) {
+                active_pane.reset_size_and_position_override();
+            }
+        }
+
+        // every pane is followed by a border, even the ones at the edges of the screen
+        let x_spans: Vec<PaneSpan> = self
+            .panes
+            .values()
+            .map(|pane| {
+                let end = pane.x() + pane.columns() + 1;
+                (pane.x(), end, pane.max_width().is_some())
+            })
+            .collect();
+        let y_spans: Vec<PaneSpan> = self
+            .panes
+            .values()
+            .map(|pane| {
+                let end = pane.y() + pane.rows() + 1;
+                (pane.y(), end, pane.max_height().is_some())
+            })
+            .collect();
+        let scaled_edges = scale_edges(
+            &x_spans,
+            self.full_screen_ws.columns + 1,
+            new_screen_size.columns + 1,
+        )
+        .zip(scale_edges(
+            &y_spans,
+            self.full_screen_ws.rows + 1,
+            new_screen_size.rows + 1,
+        ));
+        let new_positions = scaled_edges.and_then(|(x_edges, y_edges)| {
+            self.panes
+                .iter()
+                .map(|(id, pane)| {
+                    let x = x_edges[&pane.x()];
+                    let y = y_edges[&pane.y()];
+                    let columns = x_edges[&(pane.x() + pane.columns() + 1)]
+                        .checked_sub(x + 1)
+                        .filter(|c| *c >= pane.columns() || *c >= pane.min_width())?;
+                    let rows = y_edges[&(pane.y() + pane.rows() + 1)]
+                        .checked_sub(y + 1)
+                        .filter(|r| *r >= pane.rows() || *r >= pane.min_height())?;
+                    Some((
+                        *id,
+                        PositionAndSize {
+                            x,
+                            y,
+                            rows,
+                            columns,
+                        },
+                    ))
+                })
+                .collect::<Option<Vec<_>>>()
+        });
+        match new_positions {
+            Some(new_positions) => {
+                for (id, position_and_size) in new_positions {
+                    self.panes
+                        .get_mut(&id)
+                        .unwrap()
+                        .change_pos_and_size(&position_and_size);
+                    if let PaneId::Terminal(pid) = id {
+                        self.os_api.set_terminal_size_using_fd(
+                            pid,
+                            position_and_size.columns as u16,
+                            position_and_size.rows as u16,
+                        );
+                    }
+                }
+                self.full_screen_ws = new_screen_size;
+                // the part of the clients' terminals that's outside of this tab changed
+                self.clients_with_unfilled_area = self.connected_clients.keys().copied().collect();
+            }
+            None => {
+                let _ = debug_log_to_file(format!(
+                    "tab {} doesn't fit in {:?}, keeping its size",
+                    self.index, new_screen_size
+                ));
+            }
+        }
+
+        if self.fullscreen_is_active {
+            if let Some(active_pane_id) = active_pane_id {
+                let active_pane = self.panes.get_mut(&active_pane_id).unwrap();
+                active_pane.override_size_and_position(0, 0, &self.full_screen_ws);
+                if let PaneId::Terminal(active_pid) = active_pane_id {
+                    self.os_api.set_terminal_size_using_fd(
+                        active_pid,
+                        active_pane.columns() as u16,
+                        active_pane.rows() as u16,
+                    );
+                }
+            }
+        }
+    }
     pub fn render(&mut self) {
-        if self.active_terminal.is_none() {
+        if self.active_terminal.is_none() || self.connected_clients.is_empty() {
             // we might not have an active terminal if we closed the last pane
             // in that case, we should not render as the app is exiting
+            // there's also no point in rendering if nobody is attached to see it
             return;
         }
-        let mut stdout = self.os_api.get_stdout_writer();
+        let mut output = String::new();
+        let screen_size = self.screen_size();
         let mut boundaries = Boundaries::new(
-            self.full_screen_ws.columns as u16,
-            self.full_screen_ws.rows as u16,
+            screen_size.columns as u16,
+            screen_size.rows as u16,
+            &self.theme,
+            self.active_terminal,
         );
+        self.refresh_foreground_commands();
+        let titles: HashMap<PaneId, String> = self
+            .panes
+            .iter()
+            .filter(|(_, pane)| !pane.invisible_borders())
+            .filter_map(|(id, pane)| Some((*id, self.pane_title(*id, pane.as_ref())?)))
+            .collect();
         let hide_cursor = "\u{1b}[?25l";
-        stdout
-            .write_all(&hide_cursor.as_bytes())
-            .expect("cannot write to stdout");
+        output.push_str(hide_cursor);
         for (kind, terminal) in self.panes.iter_mut() {
             if !self.panes_to_hide.contains(&terminal.pid()) {
                 boundaries.add_rect(terminal.as_ref());
+                if let Some(title) = titles.get(kind) {
+                    boundaries.add_title(terminal.as_ref(), title);
+                }
                 if let Some(vte_output) = terminal.render() {
                     let vte_output = if let PaneId::Terminal(_) = kind {
                         vte_output
@@ -621,23 +1071,21 @@ impl Tab {
                     };
                     // FIXME: Use Termion for cursor and style clearing?
                     write!(
-                        stdout,
+                        output,
                         "\u{1b}[{};{}H\u{1b}[m{}",
-                        terminal.y() + 1,
+                        terminal.y() + TOP_BORDER_ROWS + 1,
                         terminal.x() + 1,
                         vte_output
                     )
-                    .expect("cannot write to stdout");
+                    .unwrap();
                 }
             }
         }
 
         // TODO: only render (and calculate) boundaries if there was a resize
-        let vte_output = boundaries.vte_output();
-        stdout
-            .write_all(&vte_output.as_bytes())
-            .expect("cannot write to stdout");
+        output.push_str(&boundaries.vte_output());
 
+        let mut cursor_output = String::new();
         match self.get_active_terminal_cursor_position() {
             Some((cursor_position_x, cursor_position_y)) => {
                 let show_cursor = "\u{1b}[?25h";
@@ -646,22 +1094,70 @@ impl Tab {
                     cursor_position_y + 1,
                     cursor_position_x + 1
                 ); // goto row/col
-                stdout
-                    .write_all(&show_cursor.as_bytes())
-                    .expect("cannot write to stdout");
-                stdout
-                    .write_all(&goto_cursor_position.as_bytes())
-                    .expect("cannot write to stdout");
-                stdout.flush().expect("could not flush");
+                cursor_output.push_str(show_cursor);
+                cursor_output.push_str(&goto_cursor_position);
             }
             None => {
                 let hide_cursor = "\u{1b}[?25l";
-                stdout
-                    .write_all(&hide_cursor.as_bytes())
-                    .expect("cannot write to stdout");
-                stdout.flush().expect("could not flush");
+                cursor_output.push_str(hide_cursor);
             }
         }
+        for (client_id, client_size) in self.connected_clients.iter() {
+            let client_output = if client_size.columns < screen_size.columns
+                || client_size.rows < screen_size.rows
+            {
+                self.too_small_message(client_size)
+            } else if self.clients_with_unfilled_area.contains(client_id) {
+                let unused_area = self.fill_unused_area(client_size);
+                [output.as_str(), &unused_area, &cursor_output].concat()
+            } else {
+                [output.as_str(), &cursor_output].concat()
+            };
+            self.os_api
+                .send_to_client(*client_id, ServerToClientMsg::Render(client_output));
+        }
+        self.clients_with_unfilled_area.clear();
+    }
+    /// Fills the part of a terminal of size `client_size` that's outside of this [`Tab`], for
+    /// clients whose terminal is larger than the session.
+    fn fill_unused_area(&self, client_size: &PositionAndSize) -> String {
+        let screen_size = self.screen_size();
+        let mut unused_area = String::new();
+        for row in 0..client_size.rows {
+            let first_unused_column = if row < screen_size.rows {
+                screen_size.columns
+            } else {
+                0
+            };
+            if first_unused_column < client_size.columns {
+                write!(
+                    unused_area,
+                    "\u{1b}[{};{}H\u{1b}[m\u{1b}[2m{}\u{1b}[m",
+                    row + 1,
+                    first_unused_column + 1,
+                    "·".repeat(client_size.columns - first_unused_column)
+                )
+                .unwrap();
+            }
+        }
+        unused_area
+    }
+    /// A message shown instead of this [`Tab`] to clients whose terminal is too small for it.
+    fn too_small_message(&self, client_size: &PositionAndSize) -> String {
+        let screen_size = self.screen_size();
+        let message = format!(
+            "Terminal too small, this session needs at least {}x{}",
+            screen_size.columns, screen_size.rows
+        );
+        let message: String = message.chars().take(client_size.columns).collect();
+        format!("\u{1b}[?25l\u{1b}[m\u{1b}[2J\u{1b}[1;1H{}", message)
+    }
+    /// The size of the screen this [`Tab`] takes: its panes, and the row of their top border.
+    fn screen_size(&self) -> PositionAndSize {
+        PositionAndSize {
+            rows: self.full_screen_ws.rows + TOP_BORDER_ROWS,
+            ..self.full_screen_ws
+        }
     }
     fn get_panes(&self) -> impl Iterator<Item = (&PaneId, &Box<dyn Pane>)> {
         self.panes.iter()
@@ -1890,6 +2386,30 @@ impl Tab {
     pub fn get_pane_ids(&self) -> Vec<PaneId> {
         self.get_panes().map(|(&pid, _)| pid).collect()
     }
+    /// Makes every pane of this [`Tab`] render itself in full the next time it's rendered.
+    pub fn set_force_render(&mut self) {
+        for pane in self.panes.values_mut() {
+            pane.set_should_render(true);
+        }
+        self.clients_with_unfilled_area = self.connected_clients.keys().copied().collect();
+    }
+    /// Shows this [`Tab`] to the client with id `client_id`, whose terminal is of size
+    /// `client_size`, or updates the size of its terminal if it's already shown.
+    pub fn connect_client(&mut self, client_id: ClientId, client_size: PositionAndSize) {
+        self.connected_clients.insert(client_id, client_size);
+        self.clients_with_unfilled_area.insert(client_id);
+    }
+    /// Stops showing this [`Tab`] to the client with id `client_id`.
+    pub fn disconnect_client(&mut self, client_id: ClientId) {
+        self.connected_clients.remove(&client_id);
+        self.clients_with_unfilled_area.remove(&client_id);
+    }
+    pub fn has_pane(&self, id: PaneId) -> bool {
+        self.panes.contains_key(&id)
+    }
+    pub fn get_pane(&self, id: PaneId) -> Option<&dyn Pane> {
+        self.panes.get(&id).map(Box::as_ref)
+    }
     pub fn set_pane_selectable(&mut self, id: PaneId, selectable: bool) {
         if let Some(pane) = self.panes.get_mut(&id) {
             pane.set_selectable(selectable);
@@ -1913,6 +2433,56 @@ impl Tab {
             pane.set_max_height(max_height);
         }
     }
+    /// Keeps the terminal pane with id `id` open now that what ran in it exited with `status`,
+    /// saying so at the end of its output.
+    pub fn hold_pane(&mut self, id: PaneId, status: ExitStatus) {
+        if let Some(pane) = self.panes.get_mut(&id) {
+            let message = format!("[{}, press Enter to rerun or x to close]", status);
+            if !matches!(pane.cursor_coordinates(), Some((0, _))) {
+                pane.handle_event(VteEvent::Execute(b'\r'));
+                pane.handle_event(VteEvent::Execute(b'\n'));
+            }
+            for c in message.chars() {
+                pane.handle_event(VteEvent::Print(c));
+            }
+            self.held_panes.insert(id, status);
+        }
+    }
+    /// Puts a new terminal pane with id `new_id` in the place of the pane with id `id`, closing
+    /// it.
+    pub fn replace_pane(&mut self, id: PaneId, new_id: PaneId) {
+        if let (Some(pane), PaneId::Terminal(term_pid)) = (self.panes.remove(&id), new_id) {
+            self.held_panes.remove(&id);
+            self.events.send(Event::PaneClosed {
+                tab: self.index,
+                pane: id,
+            });
+            let mut new_terminal = TerminalPane::new(term_pid, pane.position_and_size());
+            if let Some(position_and_size_override) = pane.position_and_size_override() {
+                new_terminal.override_size_and_position(
+                    position_and_size_override.x,
+                    position_and_size_override.y,
+                    &position_and_size_override,
+                );
+            }
+            self.os_api.set_terminal_size_using_fd(
+                new_terminal.pid,
+                new_terminal.columns() as u16,
+                new_terminal.rows() as u16,
+            );
+            if self.active_terminal == Some(id) {
+                self.active_terminal = Some(new_id);
+            }
+            if self.panes_to_hide.remove(&id) {
+                self.panes_to_hide.insert(new_id);
+            }
+            self.add_pane(new_id, Box::new(new_terminal));
+        }
+    }
+    /// Whether the pane with id `id` is kept open once what ran in it exited.
+    pub fn pane_is_held(&self, id: PaneId) -> bool {
+        self.held_panes.contains_key(&id)
+    }
     pub fn close_pane(&mut self, id: PaneId) {
         if self.panes.get(&id).is_some() {
             self.close_pane_without_rerender(id);
@@ -1957,6 +2527,11 @@ impl Tab {
             } else {
             }
             self.panes.remove(&id);
+            self.held_panes.remove(&id);
+            self.events.send(Event::PaneClosed {
+                tab: self.index,
+                pane: id,
+            });
             if self.active_terminal.is_none() {
                 self.active_terminal = self.next_active_pane(self.get_pane_ids());
             }
@@ -1,10 +1,14 @@
 #![allow(clippy::mutex_atomic)]
 use std::sync::{Arc, Condvar, Mutex};
 
+use crate::panes::PaneId;
+
 #[derive(Clone)]
 pub struct CommandIsExecuting {
     opening_new_pane: Arc<(Mutex<bool>, Condvar)>,
     closing_pane: Arc<(Mutex<bool>, Condvar)>,
+    /// The pane that was opened last, if it was opened on its own (eg. not as part of a layout).
+    new_pane_id: Arc<Mutex<Option<PaneId>>>,
 }
 
 impl CommandIsExecuting {
@@ -12,6 +16,7 @@ impl CommandIsExecuting {
         CommandIsExecuting {
             opening_new_pane: Arc::new((Mutex::new(false), Condvar::new())),
             closing_pane: Arc::new((Mutex::new(false), Condvar::new())),
+            new_pane_id: Arc::new(Mutex::new(None)),
         }
     }
     pub fn closing_pane(&mut self) {
@@ -30,9 +35,10 @@ impl CommandIsExecuting {
         let mut opening_new_pane = lock.lock().unwrap();
         *opening_new_pane = true;
     }
-    pub fn done_opening_new_pane(&mut self) {
+    pub fn done_opening_new_pane(&mut self, new_pane_id: Option<PaneId>) {
         let (lock, cvar) = &*self.opening_new_pane;
         let mut opening_new_pane = lock.lock().unwrap();
+        *self.new_pane_id.lock().unwrap() = new_pane_id;
         *opening_new_pane = false;
         cvar.notify_one();
     }
@@ -43,11 +49,13 @@ impl CommandIsExecuting {
             closing_pane = cvar.wait(closing_pane).unwrap();
         }
     }
-    pub fn wait_until_new_pane_is_opened(&self) {
+    /// Returns the id of the new pane once it's opened, if it was opened on its own.
+    pub fn wait_until_new_pane_is_opened(&self) -> Option<PaneId> {
         let (lock, cvar) = &*self.opening_new_pane;
         let mut opening_new_pane = lock.lock().unwrap();
         while *opening_new_pane {
             opening_new_pane = cvar.wait(opening_new_pane).unwrap();
         }
+        *self.new_pane_id.lock().unwrap()
     }
 }
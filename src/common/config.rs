@@ -0,0 +1,123 @@
+//! The user's config file, `config.yaml` in zellij's config directory (eg.
+//! `~/.config/zellij/config.yaml`) unless another one is given with `--config`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use directories_next::ProjectDirs;
+use serde::Deserialize;
+
+use crate::common::input::handler::InputMode;
+use crate::common::input::keybinds::{
+    get_default_keybinds, Keybinds, KeybindsConfig, KeybindsHelp, UnboundKeys,
+};
+use crate::common::theme::Theme;
+
+/// What the config file changes from the defaults. Every section of it is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFromYaml {
+    keybinds: KeybindsConfig,
+    theme: Theme,
+    allow_clipboard_writes: bool,
+    save_scrollback: bool,
+    scrollback_lines_to_save: Option<usize>,
+}
+
+/// How many lines of scrollback are saved for each pane, unless the config file or the command
+/// line says otherwise.
+const DEFAULT_SCROLLBACK_LINES_TO_SAVE: usize = 10_000;
+
+/// The configuration of a session, read once when its server starts.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub keybinds: Keybinds,
+    /// How long the keys of a sequence bound in `keybinds` can be apart.
+    pub key_sequence_timeout: Duration,
+    /// What the modes it's set for do with unbound keys, instead of [`UnboundKeys::default_for`].
+    pub unbound_keys: HashMap<InputMode, UnboundKeys>,
+    /// The help texts given to keybinds in `keybinds`, for the status bar.
+    pub keybinds_help: KeybindsHelp,
+    pub theme: Theme,
+    /// Whether programs can copy to the clipboard of the terminal zellij runs in (with OSC 52),
+    /// eg. vim over ssh. They can never read from it.
+    pub allow_clipboard_writes: bool,
+    /// Whether the scrollback of every pane is saved along with the session, like with
+    /// `--save-scrollback`.
+    pub save_scrollback: bool,
+    /// The most lines of scrollback saved for each pane, unless it's given on the command line.
+    pub scrollback_lines_to_save: usize,
+}
+
+impl Config {
+    /// Reads the config file at `config_path`, or else the one in the config directory if there
+    /// is one, falling back to the defaults for what it doesn't change.
+    ///
+    /// Errors are meant to be shown to the user as they are, with the line of the config file
+    /// they're on.
+    pub fn new(config_path: Option<&Path>) -> Result<Config, String> {
+        let config_path = match config_path {
+            Some(config_path) => config_path.to_path_buf(),
+            None => match default_config_path() {
+                Some(config_path) if config_path.exists() => config_path,
+                _ => return Config::from_yaml(""),
+            },
+        };
+        let config = fs::read_to_string(&config_path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                format!("There is no config file at {}.", config_path.display())
+            }
+            _ => format!(
+                "Could not read the config file {}: {}",
+                config_path.display(),
+                e
+            ),
+        })?;
+        Config::from_yaml(&config).map_err(|e| {
+            format!(
+                "Could not parse the config file {}: {}",
+                config_path.display(),
+                e
+            )
+        })
+    }
+
+    /// Parses the contents of a config file.
+    pub fn from_yaml(config: &str) -> Result<Config, String> {
+        // an empty document isn't valid YAML, but it's a fine config file
+        let config_from_yaml: ConfigFromYaml = if config.trim().is_empty() {
+            ConfigFromYaml::default()
+        } else {
+            serde_yaml::from_str(config).map_err(|e| e.to_string())?
+        };
+        let mut keybinds = get_default_keybinds()?;
+        let key_sequence_timeout = config_from_yaml.keybinds.sequence_timeout();
+        let unbound_keys = config_from_yaml.keybinds.unbound_keys();
+        let keybinds_help = config_from_yaml.keybinds.apply_to(&mut keybinds)?;
+        Ok(Config {
+            keybinds,
+            key_sequence_timeout,
+            unbound_keys,
+            keybinds_help,
+            theme: config_from_yaml.theme,
+            allow_clipboard_writes: config_from_yaml.allow_clipboard_writes,
+            save_scrollback: config_from_yaml.save_scrollback,
+            scrollback_lines_to_save: config_from_yaml
+                .scrollback_lines_to_save
+                .unwrap_or(DEFAULT_SCROLLBACK_LINES_TO_SAVE),
+        })
+    }
+}
+
+/// Returns the path of the config file in the config directory, which doesn't have to exist.
+/// Tests never use it, so that they don't depend on the config of whoever runs them.
+fn default_config_path() -> Option<PathBuf> {
+    if cfg!(test) {
+        return None;
+    }
+    let project_dirs = ProjectDirs::from("org", "Zellij Contributors", "Zellij")?;
+    Some(project_dirs.config_dir().join("config.yaml"))
+}
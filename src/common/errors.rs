@@ -135,6 +135,8 @@ pub enum ContextType {
     App(AppContext),
     IPCServer,
     StdinHandler,
+    SignalListener,
+    SessionSaver,
     AsyncTask,
     /// An empty, placeholder call. This should be thought of as representing no call at all.
     /// A call stack representation filled with these is the representation of an empty call stack.
@@ -156,6 +158,16 @@ impl Display for ContextType {
             ContextType::StdinHandler => {
                 write!(f, "{}stdin_handler_thread: {}AcceptInput", purple, green)
             }
+            ContextType::SignalListener => {
+                write!(
+                    f,
+                    "{}signal_listener_thread: {}ReceiveSignal",
+                    purple, green
+                )
+            }
+            ContextType::SessionSaver => {
+                write!(f, "{}session_saver_thread: {}SaveSession", purple, green)
+            }
             ContextType::AsyncTask => {
                 write!(f, "{}stream_terminal_bytes: {}AsyncTask", purple, green)
             }
@@ -188,15 +200,27 @@ pub enum ScreenContext {
     ClearScroll,
     CloseFocusedPane,
     ToggleActiveTerminalFullscreen,
+    RenamePane,
     SetSelectable,
     SetInvisibleBorders,
     SetMaxHeight,
     ClosePane,
+    HoldPane,
+    ReplacePane,
     ApplyLayout,
     NewTab,
     SwitchTabNext,
     SwitchTabPrev,
     CloseTab,
+    AddClient,
+    RemoveClient,
+    SetActiveClient,
+    TerminalResize,
+    GetTabCount,
+    GetSessionLayout,
+    DumpPane,
+    GetSessionStructure,
+    SendKeys,
 }
 
 impl From<&ScreenInstruction> for ScreenContext {
@@ -225,15 +249,27 @@ impl From<&ScreenInstruction> for ScreenContext {
             ScreenInstruction::ToggleActiveTerminalFullscreen => {
                 ScreenContext::ToggleActiveTerminalFullscreen
             }
+            ScreenInstruction::RenamePane(_) => ScreenContext::RenamePane,
             ScreenInstruction::SetSelectable(..) => ScreenContext::SetSelectable,
             ScreenInstruction::SetInvisibleBorders(..) => ScreenContext::SetInvisibleBorders,
             ScreenInstruction::SetMaxHeight(..) => ScreenContext::SetMaxHeight,
             ScreenInstruction::ClosePane(_) => ScreenContext::ClosePane,
+            ScreenInstruction::HoldPane(..) => ScreenContext::HoldPane,
+            ScreenInstruction::ReplacePane(..) => ScreenContext::ReplacePane,
             ScreenInstruction::ApplyLayout(_) => ScreenContext::ApplyLayout,
             ScreenInstruction::NewTab(_) => ScreenContext::NewTab,
             ScreenInstruction::SwitchTabNext => ScreenContext::SwitchTabNext,
             ScreenInstruction::SwitchTabPrev => ScreenContext::SwitchTabPrev,
             ScreenInstruction::CloseTab => ScreenContext::CloseTab,
+            ScreenInstruction::AddClient(..) => ScreenContext::AddClient,
+            ScreenInstruction::RemoveClient(_) => ScreenContext::RemoveClient,
+            ScreenInstruction::SetActiveClient(_) => ScreenContext::SetActiveClient,
+            ScreenInstruction::TerminalResize(..) => ScreenContext::TerminalResize,
+            ScreenInstruction::GetTabCount(_) => ScreenContext::GetTabCount,
+            ScreenInstruction::GetSessionLayout(..) => ScreenContext::GetSessionLayout,
+            ScreenInstruction::DumpPane(..) => ScreenContext::DumpPane,
+            ScreenInstruction::GetSessionStructure(..) => ScreenContext::GetSessionStructure,
+            ScreenInstruction::SendKeys(..) => ScreenContext::SendKeys,
         }
     }
 }
@@ -245,7 +281,10 @@ pub enum PtyContext {
     SpawnTerminalVertically,
     SpawnTerminalHorizontally,
     NewTab,
+    ResurrectTab,
     ClosePane,
+    RerunCommand,
+    TerminalExited,
     CloseTab,
     Quit,
 }
@@ -257,8 +296,11 @@ impl From<&PtyInstruction> for PtyContext {
             PtyInstruction::SpawnTerminalVertically(_) => PtyContext::SpawnTerminalVertically,
             PtyInstruction::SpawnTerminalHorizontally(_) => PtyContext::SpawnTerminalHorizontally,
             PtyInstruction::ClosePane(_) => PtyContext::ClosePane,
+            PtyInstruction::RerunCommand(_) => PtyContext::RerunCommand,
+            PtyInstruction::TerminalExited(..) => PtyContext::TerminalExited,
             PtyInstruction::CloseTab(_) => PtyContext::CloseTab,
             PtyInstruction::NewTab => PtyContext::NewTab,
+            PtyInstruction::ResurrectTab(_) => PtyContext::ResurrectTab,
             PtyInstruction::Quit => PtyContext::Quit,
         }
     }
@@ -0,0 +1,68 @@
+//! Events that happen in a session, sent to the clients that subscribed to them (eg. with
+//! `zellij subscribe`).
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::input::handler::InputMode;
+use crate::common::ipc::{ClientId, ServerToClientMsg};
+use crate::os_input_output::OsApi;
+use crate::panes::PaneId;
+use crate::pty_bus::ExitStatus;
+
+/// Something that happened in a session. Tabs are given by their index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Event {
+    /// A pane was opened.
+    PaneOpened { tab: usize, pane: PaneId },
+    /// A pane was closed.
+    PaneClosed { tab: usize, pane: PaneId },
+    /// A tab was opened.
+    TabOpened { tab: usize },
+    /// A tab was closed, along with all of its panes.
+    TabClosed { tab: usize },
+    /// A client is now looking at another tab.
+    TabSwitched { client: ClientId, tab: usize },
+    /// A client switched to another input mode.
+    ModeSwitched { client: ClientId, mode: InputMode },
+    /// What was running in a terminal pane exited.
+    CommandExited { pane: PaneId, status: ExitStatus },
+}
+
+/// Sends [`Event`]s to the clients that subscribed to them. Its clones all share the same
+/// subscribers.
+#[derive(Clone)]
+pub struct EventSender {
+    subscribers: Arc<Mutex<BTreeSet<ClientId>>>,
+    os_api: Box<dyn OsApi>,
+}
+
+impl EventSender {
+    pub fn new(os_api: Box<dyn OsApi>) -> Self {
+        EventSender {
+            subscribers: Arc::new(Mutex::new(BTreeSet::new())),
+            os_api,
+        }
+    }
+
+    /// Sends the events that happen from now on to the client with id `client_id`.
+    pub fn subscribe(&self, client_id: ClientId) {
+        self.subscribers.lock().unwrap().insert(client_id);
+    }
+
+    /// Stops sending events to the client with id `client_id`, if it subscribed to them.
+    pub fn unsubscribe(&self, client_id: ClientId) {
+        self.subscribers.lock().unwrap().remove(&client_id);
+    }
+
+    /// Sends `event` to every subscriber, without waiting for it to be written. Subscribers that
+    /// can't keep up with the events are unsubscribed.
+    pub fn send(&self, event: Event) {
+        self.subscribers.lock().unwrap().retain(|client_id| {
+            self.os_api
+                .send_to_client(*client_id, ServerToClientMsg::Event(event.clone()))
+        });
+    }
+}
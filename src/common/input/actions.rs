@@ -1,9 +1,14 @@
 //! Definition of the actions that can be bound to keys.
 
 use super::handler;
+use crate::cli::{CliAction, CliDirection};
+use crate::pty_bus::RunCommand;
+
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 
 /// The four directions (left, right, up, down).
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Direction {
     Left,
     Right,
@@ -12,7 +17,10 @@ pub enum Direction {
 }
 
 /// Actions that can be bound to keys.
-#[derive(Clone)]
+///
+/// Actions are sent by `zellij action`, so new ones go at the end, where they don't change how
+/// the others are encoded (see [`crate::common::ipc::IPC_PROTOCOL_VERSION`]).
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Action {
     /// Quit Zellij.
     Quit,
@@ -45,4 +53,51 @@ pub enum Action {
     GoToPreviousTab,
     /// Close the current tab.
     CloseTab,
+    /// Detach from the session, leaving it running in the background.
+    Detach,
+    /// Give the focus pane a title, shown on its border instead of what's running in it. An
+    /// empty title goes back to showing what's running.
+    RenamePane(String),
+    /// Open a new pane running a command, placed like [`Action::NewPane`].
+    Run(RunCommand, Option<Direction>),
+}
+
+impl From<CliDirection> for Direction {
+    fn from(direction: CliDirection) -> Self {
+        match direction {
+            CliDirection::Left => Direction::Left,
+            CliDirection::Right => Direction::Right,
+            CliDirection::Up => Direction::Up,
+            CliDirection::Down => Direction::Down,
+        }
+    }
+}
+
+/// Turns an action given to `zellij action` into the [`Action`] it stands for, failing if one of
+/// its arguments isn't valid.
+impl TryFrom<CliAction> for Action {
+    type Error = String;
+    fn try_from(cli_action: CliAction) -> Result<Self, Self::Error> {
+        let action = match cli_action {
+            CliAction::Quit => Action::Quit,
+            CliAction::Write { bytes } => Action::Write(bytes),
+            CliAction::WriteChars { chars } => Action::Write(chars.into_bytes()),
+            CliAction::SwitchMode { mode } => Action::SwitchToMode(mode.parse()?),
+            CliAction::Resize { direction } => Action::Resize(direction.into()),
+            CliAction::FocusNextPane => Action::SwitchFocus(Direction::Right),
+            CliAction::MoveFocus { direction } => Action::MoveFocus(direction.into()),
+            CliAction::ScrollUp => Action::ScrollUp,
+            CliAction::ScrollDown => Action::ScrollDown,
+            CliAction::ToggleFullscreen => Action::ToggleFocusFullscreen,
+            CliAction::NewPane { direction } => Action::NewPane(direction.map(Direction::from)),
+            CliAction::ClosePane => Action::CloseFocus,
+            CliAction::RenamePane { title } => Action::RenamePane(title),
+            CliAction::NewTab => Action::NewTab,
+            CliAction::GoToNextTab => Action::GoToNextTab,
+            CliAction::GoToPreviousTab => Action::GoToPreviousTab,
+            CliAction::CloseTab => Action::CloseTab,
+            CliAction::Detach => Action::Detach,
+        };
+        Ok(action)
+    }
 }
@@ -1,104 +1,267 @@
 //! Main input logic.
 
-use super::actions::Action;
-use super::keybinds::get_default_keybinds;
-use crate::common::{update_state, AppInstruction, AppState, SenderWithContext, OPENCALLS};
-use crate::errors::ContextType;
+use super::actions::{Action, Direction};
+use super::keybinds::{Keybinds, KeybindsHelp};
+use crate::common::events::{Event, EventSender};
+use crate::common::ipc::{ClientId, ClientType, ExitReason, ServerToClientMsg};
+use crate::common::{update_state, AppInstruction, AppState, SenderWithContext};
 use crate::os_input_output::OsApi;
-use crate::pty_bus::PtyInstruction;
+use crate::panes::PaneId;
+use crate::pty_bus::{PtyInstruction, RunCommand, TerminalAction};
 use crate::screen::ScreenInstruction;
 use crate::wasm_vm::PluginInstruction;
 use crate::CommandIsExecuting;
 
-use serde::{Deserialize, Serialize};
-use strum_macros::EnumIter;
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use termion::event::Key;
 use termion::input::TermReadEventsAndRaw;
 
-use super::keybinds::key_to_actions;
+use super::keybinds::{is_start_of_key_sequence, key_sequence_to_actions, UnboundKeys};
 
 /// Handles the dispatching of [`Action`]s according to the current
 /// [`InputMode`], and keep tracks of the current [`InputMode`].
-struct InputHandler {
+///
+/// The server keeps one [`InputHandler`] per connected client, so that every client has its
+/// own [`InputMode`].
+pub struct InputHandler {
+    /// The client whose input is being handled
+    client_id: ClientId,
+    /// Whether that client may change the session, or only watch it
+    client_type: ClientType,
     /// The current input mode
     mode: InputMode,
+    keybinds: Keybinds,
+    /// How long the keys of a sequence bound in `keybinds` can be apart
+    key_sequence_timeout: Duration,
+    /// What the modes it's set for do with the keys that aren't bound in `keybinds`
+    unbound_keys: HashMap<InputMode, UnboundKeys>,
+    /// The keys of a sequence that was started but isn't finished yet, each along with the bytes
+    /// it was read from, so that they can be written to the terminal if it's never finished
+    pending_keys: Vec<(Key, Vec<u8>)>,
+    /// When the pending keys stop waiting for the rest of their sequence
+    pending_keys_deadline: Option<Instant>,
     os_input: Box<dyn OsApi>,
     command_is_executing: CommandIsExecuting,
     send_screen_instructions: SenderWithContext<ScreenInstruction>,
     send_pty_instructions: SenderWithContext<PtyInstruction>,
     send_plugin_instructions: SenderWithContext<PluginInstruction>,
     send_app_instructions: SenderWithContext<AppInstruction>,
+    events: EventSender,
 }
 
 impl InputHandler {
     /// Returns a new [`InputHandler`] with the attributes specified as arguments.
-    fn new(
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        client_id: ClientId,
+        client_type: ClientType,
+        keybinds: Keybinds,
+        key_sequence_timeout: Duration,
+        unbound_keys: HashMap<InputMode, UnboundKeys>,
         os_input: Box<dyn OsApi>,
         command_is_executing: CommandIsExecuting,
         send_screen_instructions: SenderWithContext<ScreenInstruction>,
         send_pty_instructions: SenderWithContext<PtyInstruction>,
         send_plugin_instructions: SenderWithContext<PluginInstruction>,
         send_app_instructions: SenderWithContext<AppInstruction>,
+        events: EventSender,
     ) -> Self {
         InputHandler {
+            client_id,
+            client_type,
             mode: InputMode::Normal,
+            keybinds,
+            key_sequence_timeout,
+            unbound_keys,
+            pending_keys: vec![],
+            pending_keys_deadline: None,
             os_input,
             command_is_executing,
             send_screen_instructions,
             send_pty_instructions,
             send_plugin_instructions,
             send_app_instructions,
+            events,
         }
     }
 
-    /// Main input event loop. Interprets the terminal [`Event`](termion::event::Event)s
-    /// as [`Action`]s according to the current [`InputMode`], and dispatches those actions.
-    fn handle_input(&mut self) {
-        let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
-        err_ctx.add_call(ContextType::StdinHandler);
-        self.send_pty_instructions.update(err_ctx);
-        self.send_app_instructions.update(err_ctx);
-        self.send_screen_instructions.update(err_ctx);
-        if let Ok(keybinds) = get_default_keybinds() {
-            'input_loop: loop {
-                //@@@ I think this should actually just iterate over stdin directly
-                let stdin_buffer = self.os_input.read_from_stdin();
-                drop(
-                    self.send_plugin_instructions
-                        .send(PluginInstruction::GlobalInput(stdin_buffer.clone())),
-                );
-                for key_result in stdin_buffer.events_and_raw() {
-                    match key_result {
-                        Ok((event, raw_bytes)) => match event {
-                            termion::event::Event::Key(key) => {
-                                // FIXME this explicit break is needed because the current test
-                                // framework relies on it to not create dead threads that loop
-                                // and eat up CPUs. Do not remove until the test framework has
-                                // been revised. Sorry about this (@categorille)
-                                if {
-                                    let mut should_break = false;
-                                    for action in
-                                        key_to_actions(&key, raw_bytes, &self.mode, &keybinds)
-                                    {
-                                        should_break |= self.dispatch_action(action);
-                                    }
-                                    should_break
-                                } {
-                                    break 'input_loop;
-                                }
-                            }
-                            termion::event::Event::Mouse(_)
-                            | termion::event::Event::Unsupported(_) => {
-                                unimplemented!("Mouse and unsupported events aren't supported!");
-                            }
-                        },
-                        Err(err) => panic!("Encountered read error: {:?}", err),
+    /// Interprets a chunk of raw input read by the client as terminal
+    /// [`Event`](termion::event::Event)s, which are turned into [`Action`]s according to the
+    /// current [`InputMode`] and dispatched.
+    ///
+    /// Keys that start a sequence of keys that's bound are held back until the rest of it is
+    /// pressed. If another key is pressed instead, or if the rest of it takes too long (see
+    /// [`Self::handle_expired_pending_keys`]), they're handled as if they weren't part of a
+    /// sequence, which means that they're written to the terminal unless they're bound on their
+    /// own.
+    ///
+    /// The input of [`ClientType::Reader`] clients is ignored, except for the keys that detach
+    /// them from the session.
+    ///
+    /// Returns `true` if the client is leaving the session (it quit or detached, or its input
+    /// couldn't be read), in which case the rest of its input is dropped.
+    pub fn handle_input(&mut self, stdin_buffer: &[u8]) -> bool {
+        // nothing was typed if there's no input (as with the fake terminals of the tests, between
+        // their keys), so it doesn't make this client the one that was used last
+        if self.client_type == ClientType::Writer && !stdin_buffer.is_empty() {
+            // this client's input acts on the tab it's looking at
+            self.send_screen_instructions
+                .send(ScreenInstruction::SetActiveClient(self.client_id))
+                .unwrap();
+            drop(
+                self.send_plugin_instructions
+                    .send(PluginInstruction::GlobalInput(stdin_buffer.to_vec())),
+            );
+        }
+        // keys that waited too long for the rest of their sequence come before the new ones
+        if self.handle_expired_pending_keys() {
+            return true;
+        }
+        for key_result in stdin_buffer.events_and_raw() {
+            match key_result {
+                Ok((event, raw_bytes)) => match event {
+                    termion::event::Event::Key(key) => {
+                        self.pending_keys.push((key, raw_bytes));
+                        if self.handle_pending_keys(false) {
+                            return true;
+                        }
                     }
+                    // the mouse isn't supported yet
+                    termion::event::Event::Mouse(_) | termion::event::Event::Unsupported(_) => {}
+                },
+                Err(err) => {
+                    // only this client's input is broken, the others can carry on
+                    self.os_input.send_to_client(
+                        self.client_id,
+                        ServerToClientMsg::Exit(ExitReason::Error(format!(
+                            "Encountered read error: {:?}",
+                            err
+                        ))),
+                    );
+                    return true;
                 }
             }
+        }
+        false
+    }
+
+    /// Returns when the keys of a sequence that was started stop waiting for the rest of it, if
+    /// there are any.
+    pub fn pending_keys_deadline(&self) -> Option<Instant> {
+        self.pending_keys_deadline
+    }
+
+    /// Handles the keys of a sequence that was started as if they weren't part of a sequence, if
+    /// they waited too long for the rest of it.
+    ///
+    /// Returns `true` if the client is leaving the session, like [`Self::handle_input`].
+    pub fn handle_expired_pending_keys(&mut self) -> bool {
+        match self.pending_keys_deadline {
+            Some(deadline) if deadline <= Instant::now() => self.handle_pending_keys(true),
+            _ => false,
+        }
+    }
+
+    /// Turns the pending keys into [`Action`]s and dispatches them, as far as it can be done
+    /// without waiting for more keys. If the keys `timed_out`, the ones at the start stop
+    /// waiting for the rest of their sequence.
+    ///
+    /// Returns `true` if the client is leaving the session, like [`Self::handle_input`].
+    fn handle_pending_keys(&mut self, mut timed_out: bool) -> bool {
+        let was_waiting = self.pending_keys_deadline.is_some();
+        let mut client_is_leaving = false;
+        while !self.pending_keys.is_empty() && !client_is_leaving {
+            let keys: Vec<Key> = self.pending_keys.iter().map(|(key, _)| *key).collect();
+            if !timed_out && is_start_of_key_sequence(&keys, &self.mode, &self.keybinds) {
+                break;
+            }
+            timed_out = false;
+            let unbound_keys = self
+                .unbound_keys
+                .get(&self.mode)
+                .copied()
+                .unwrap_or_else(|| UnboundKeys::default_for(&self.mode));
+            let (key_count, actions) = key_sequence_to_actions(
+                &self.pending_keys,
+                &self.mode,
+                &self.keybinds,
+                unbound_keys,
+            );
+            self.pending_keys.drain(..key_count);
+            for action in actions {
+                client_is_leaving |= match self.client_type {
+                    ClientType::Writer => self.dispatch_action(action),
+                    ClientType::Reader => self.dispatch_reader_action(action),
+                };
+            }
+        }
+        if client_is_leaving {
+            self.pending_keys.clear();
+            self.pending_keys_deadline = None;
+            return true;
+        }
+        let is_waiting = !self.pending_keys.is_empty();
+        self.pending_keys_deadline = if is_waiting {
+            Some(Instant::now() + self.key_sequence_timeout)
         } else {
-            //@@@ Error handling?
-            self.exit();
+            None
+        };
+        if (was_waiting || is_waiting) && self.client_type == ClientType::Writer {
+            self.show_pending_keys();
         }
+        false
+    }
+
+    /// Shows the pending keys in the status bar, along with the input mode. Like the input mode,
+    /// only the pending keys of writers are shown.
+    fn show_pending_keys(&self) {
+        let pending_keys = self.pending_keys.iter().map(|(key, _)| *key).collect();
+        update_state(&self.send_app_instructions, |state| AppState {
+            pending_keys,
+            ..state
+        });
+        self.send_screen_instructions
+            .send(ScreenInstruction::Render)
+            .unwrap();
+    }
+
+    /// Dispatches an [`Action`] that didn't come from this client's input (eg. one sent with
+    /// `zellij action`) as if it was bound to a key the client pressed.
+    ///
+    /// Returns `true` if the client is leaving the session, like [`Self::handle_input`].
+    pub fn handle_action(&mut self, action: Action) -> bool {
+        match self.client_type {
+            ClientType::Writer => {
+                self.send_screen_instructions
+                    .send(ScreenInstruction::SetActiveClient(self.client_id))
+                    .unwrap();
+                self.dispatch_action(action)
+            }
+            ClientType::Reader => self.dispatch_reader_action(action),
+        }
+    }
+
+    /// Opens a new pane running `run_command` the same way as [`Action::Run`] does, returning
+    /// its id once it's opened. Only meant for writers.
+    pub fn run_command(
+        &mut self,
+        run_command: RunCommand,
+        direction: Option<Direction>,
+    ) -> Option<PaneId> {
+        self.send_screen_instructions
+            .send(ScreenInstruction::SetActiveClient(self.client_id))
+            .unwrap();
+        self.open_new_pane(direction, Some(TerminalAction::RunCommand(run_command)))
+    }
+
+    /// Returns whether this client may change the session, or only watch it.
+    pub fn client_type(&self) -> ClientType {
+        self.client_type
     }
 
     /// Dispatches an [`Action`].
@@ -107,11 +270,8 @@ impl InputHandler {
     /// dispatched.
     ///
     /// # Return value
-    /// Currently, this function returns a boolean that indicates whether
-    /// [`Self::handle_input()`] should break after this action is dispatched.
-    /// This is a temporary measure that is only necessary due to the way that the
-    /// framework works, and shouldn't be necessary anymore once the test framework
-    /// is revised. See [issue#183](https://github.com/zellij-org/zellij/issues/183).
+    /// This function returns a boolean that indicates whether the client is leaving the
+    /// session after this action is dispatched.
     fn dispatch_action(&mut self, action: Action) -> bool {
         let mut should_break = false;
 
@@ -128,15 +288,23 @@ impl InputHandler {
                 self.exit();
                 should_break = true;
             }
-            Action::SwitchToMode(mode) => {
-                self.mode = mode;
-                update_state(&self.send_app_instructions, |_| AppState {
-                    input_mode: self.mode,
+            // a mode that isn't defined (eg. a typo given to `zellij action switch-mode`) would
+            // have no keys to get out of it
+            Action::SwitchToMode(mode) if self.keybinds.contains_key(&mode) => {
+                self.mode = mode.clone();
+                update_state(&self.send_app_instructions, |state| AppState {
+                    input_mode: mode.clone(),
+                    ..state
+                });
+                self.events.send(Event::ModeSwitched {
+                    client: self.client_id,
+                    mode,
                 });
                 self.send_screen_instructions
                     .send(ScreenInstruction::Render)
                     .unwrap();
             }
+            Action::SwitchToMode(_) => {}
             Action::Resize(direction) => {
                 let screen_instr = match direction {
                     super::actions::Direction::Left => ScreenInstruction::ResizeLeft,
@@ -176,25 +344,10 @@ impl InputHandler {
                     .unwrap();
             }
             Action::NewPane(direction) => {
-                let pty_instr = match direction {
-                    Some(super::actions::Direction::Left) => {
-                        PtyInstruction::SpawnTerminalVertically(None)
-                    }
-                    Some(super::actions::Direction::Right) => {
-                        PtyInstruction::SpawnTerminalVertically(None)
-                    }
-                    Some(super::actions::Direction::Up) => {
-                        PtyInstruction::SpawnTerminalHorizontally(None)
-                    }
-                    Some(super::actions::Direction::Down) => {
-                        PtyInstruction::SpawnTerminalHorizontally(None)
-                    }
-                    // No direction specified - try to put it in the biggest available spot
-                    None => PtyInstruction::SpawnTerminal(None),
-                };
-                self.command_is_executing.opening_new_pane();
-                self.send_pty_instructions.send(pty_instr).unwrap();
-                self.command_is_executing.wait_until_new_pane_is_opened();
+                self.open_new_pane(direction, None);
+            }
+            Action::Run(run_command, direction) => {
+                self.open_new_pane(direction, Some(TerminalAction::RunCommand(run_command)));
             }
             Action::CloseFocus => {
                 self.command_is_executing.closing_pane();
@@ -203,6 +356,11 @@ impl InputHandler {
                     .unwrap();
                 self.command_is_executing.wait_until_pane_is_closed();
             }
+            Action::RenamePane(title) => {
+                self.send_screen_instructions
+                    .send(ScreenInstruction::RenamePane(title))
+                    .unwrap();
+            }
             Action::NewTab => {
                 self.command_is_executing.opening_new_pane();
                 self.send_pty_instructions
@@ -227,13 +385,58 @@ impl InputHandler {
                     .unwrap();
                 self.command_is_executing.wait_until_pane_is_closed();
             }
+            Action::Detach => {
+                self.os_input.send_to_client(
+                    self.client_id,
+                    ServerToClientMsg::Exit(ExitReason::Detached),
+                );
+                should_break = true;
+            }
         }
 
         should_break
     }
 
-    /// Routine to be called when the input handler exits (at the moment this is the
-    /// same as quitting Zellij).
+    /// Dispatches an [`Action`] of a [`ClientType::Reader`] client, which can only move between
+    /// its own input modes on the way to detaching. Its mode isn't shared with the other clients
+    /// through the [`AppState`], so the status bar keeps showing the mode of the writers.
+    ///
+    /// Returns whether the client is leaving the session, like [`Self::dispatch_action`].
+    fn dispatch_reader_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::SwitchToMode(mode) => {
+                if self.keybinds.contains_key(&mode) {
+                    self.mode = mode;
+                }
+                false
+            }
+            Action::Detach => self.dispatch_action(action),
+            _ => false,
+        }
+    }
+
+    /// Opens a new pane in `direction` (relative to the focused pane), or in the biggest space
+    /// available if no direction is given, running what `terminal_action` asks for if given.
+    fn open_new_pane(
+        &mut self,
+        direction: Option<Direction>,
+        terminal_action: Option<TerminalAction>,
+    ) -> Option<PaneId> {
+        let pty_instr = match direction {
+            Some(Direction::Left) | Some(Direction::Right) => {
+                PtyInstruction::SpawnTerminalVertically(terminal_action)
+            }
+            Some(Direction::Up) | Some(Direction::Down) => {
+                PtyInstruction::SpawnTerminalHorizontally(terminal_action)
+            }
+            None => PtyInstruction::SpawnTerminal(terminal_action),
+        };
+        self.command_is_executing.opening_new_pane();
+        self.send_pty_instructions.send(pty_instr).unwrap();
+        self.command_is_executing.wait_until_new_pane_is_opened()
+    }
+
+    /// Routine to be called when the user quits, which ends the session for every client.
     fn exit(&mut self) {
         self.send_app_instructions
             .send(AppInstruction::Exit)
@@ -242,7 +445,11 @@ impl InputHandler {
 }
 
 /// Describes the different input modes, which change the way that keystrokes will be interpreted.
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, EnumIter, Serialize, Deserialize)]
+///
+/// Besides the built-in modes, there are the ones defined in the config file, which are told
+/// apart by their names. Modes are (de)serialized as their names, so that the config file (eg.
+/// `SwitchToMode: pane`) and plugins can refer to any of them.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum InputMode {
     /// In `Normal` mode, input is always written to the terminal, except for one special input that
     /// triggers the switch to [`InputMode::Command`] mode.
@@ -259,6 +466,33 @@ pub enum InputMode {
     Tab,
     /// `Scroll` mode allows scrolling up and down within a pane.
     Scroll,
+    /// A mode defined in the config file, by its name.
+    Custom(String),
+}
+
+impl InputMode {
+    /// The modes that are always there, which are the ones with default keybinds.
+    pub const BUILT_IN: [InputMode; 6] = [
+        InputMode::Normal,
+        InputMode::Command,
+        InputMode::Resize,
+        InputMode::Pane,
+        InputMode::Tab,
+        InputMode::Scroll,
+    ];
+
+    /// Returns the name of the mode, which for built-in modes is the name of their variant.
+    pub fn name(&self) -> &str {
+        match self {
+            InputMode::Normal => "Normal",
+            InputMode::Command => "Command",
+            InputMode::Resize => "Resize",
+            InputMode::Pane => "Pane",
+            InputMode::Tab => "Tab",
+            InputMode::Scroll => "Scroll",
+            InputMode::Custom(name) => name,
+        }
+    }
 }
 
 /// Represents the contents of the help message that is printed in the status bar,
@@ -268,6 +502,12 @@ pub enum InputMode {
 pub struct Help {
     pub mode: InputMode,
     pub keybinds: Vec<(String, String)>, // <shortcut> => <shortcut description>
+    /// The names of the keys of a sequence that was started but isn't finished yet
+    pub pending_keys: Vec<String>,
+    /// How many read-only clients are attached to the session
+    pub readers: usize,
+    /// How many clients that can change the session are attached to it
+    pub writers: usize,
 }
 
 impl Default for InputMode {
@@ -276,10 +516,53 @@ impl Default for InputMode {
     }
 }
 
+/// Parses the name of a mode, which is a built-in mode if it's the name of one in any case (eg.
+/// `pane` or `Pane`), or else a mode defined in the config file.
+impl FromStr for InputMode {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("the name of an input mode can't be empty".to_string());
+        }
+        let built_in_mode = InputMode::BUILT_IN
+            .iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(s));
+        Ok(built_in_mode
+            .cloned()
+            .unwrap_or_else(|| InputMode::Custom(s.to_string())))
+    }
+}
+
+impl Serialize for InputMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for InputMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InputModeVisitor;
+
+        impl<'de> Visitor<'de> for InputModeVisitor {
+            type Value = InputMode;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("the name of an input mode")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<InputMode, E> {
+                s.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(InputModeVisitor)
+    }
+}
+
 /// Creates a [`Help`] struct indicating the current [`InputMode`] and its keybinds
-/// (as pairs of [`String`]s).
+/// (as pairs of [`String`]s), including the ones given a help text in the config file.
 // TODO this should probably be automatically generated in some way
-pub fn get_help(mode: InputMode) -> Help {
+pub fn get_help(mode: &InputMode, keybinds_help: &KeybindsHelp) -> Help {
     let mut keybinds: Vec<(String, String)> = vec![];
     match mode {
         InputMode::Normal | InputMode::Command => {
@@ -287,6 +570,7 @@ pub fn get_help(mode: InputMode) -> Help {
             keybinds.push((format!("t"), format!("TAB")));
             keybinds.push((format!("r"), format!("RESIZE")));
             keybinds.push((format!("s"), format!("SCROLL")));
+            keybinds.push((format!("d"), format!("DETACH")));
         }
         InputMode::Resize => {
             keybinds.push((format!("←↓↑→"), format!("Resize")));
@@ -308,29 +592,19 @@ pub fn get_help(mode: InputMode) -> Help {
         InputMode::Scroll => {
             keybinds.push((format!("↓↑"), format!("Scroll")));
         }
+        InputMode::Custom(_) => {}
+    }
+    if let Some(mode_help) = keybinds_help.get(mode) {
+        keybinds.extend(mode_help.iter().cloned());
+    }
+    // the keys that get out of the modes defined in the config file are up to it
+    if !matches!(mode, InputMode::Custom(_)) {
+        keybinds.push((format!("ESC"), format!("BACK")));
+        keybinds.push((format!("q"), format!("QUIT")));
+    }
+    Help {
+        mode: mode.clone(),
+        keybinds,
+        ..Default::default()
     }
-    keybinds.push((format!("ESC"), format!("BACK")));
-    keybinds.push((format!("q"), format!("QUIT")));
-    Help { mode, keybinds }
-}
-
-/// Entry point to the module. Instantiates an [`InputHandler`] and starts
-/// its [`InputHandler::handle_input()`] loop.
-pub fn input_loop(
-    os_input: Box<dyn OsApi>,
-    command_is_executing: CommandIsExecuting,
-    send_screen_instructions: SenderWithContext<ScreenInstruction>,
-    send_pty_instructions: SenderWithContext<PtyInstruction>,
-    send_plugin_instructions: SenderWithContext<PluginInstruction>,
-    send_app_instructions: SenderWithContext<AppInstruction>,
-) {
-    let _handler = InputHandler::new(
-        os_input,
-        command_is_executing,
-        send_screen_instructions,
-        send_pty_instructions,
-        send_plugin_instructions,
-        send_app_instructions,
-    )
-    .handle_input();
 }
@@ -2,30 +2,70 @@
 
 use super::actions::{Action, Direction};
 use super::handler::InputMode;
+use super::keys::{key_name, parse_key_sequence};
 
 use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
 
-use strum::IntoEnumIterator;
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
 use termion::event::Key;
 
-type Keybinds = HashMap<InputMode, ModeKeybinds>;
-type ModeKeybinds = HashMap<Key, Vec<Action>>;
+pub type Keybinds = HashMap<InputMode, ModeKeybinds>;
+/// The sequences of keys bound in a mode, most of which are a single key.
+pub type ModeKeybinds = HashMap<Vec<Key>, Vec<Action>>;
+/// The keybinds given a help text in the config file, per mode, as pairs of the names of their
+/// keys and their help text, in the order they're bound.
+pub type KeybindsHelp = HashMap<InputMode, Vec<(String, String)>>;
 
-/// Populates the default hashmap of keybinds.
-/// @@@khs26 What about an input config file?
+/// How long the keys of a sequence that's been started can be apart by default, before the keys
+/// pressed so far are handled on their own.
+const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// What an [`InputMode`] does with the keys that aren't bound in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnboundKeys {
+    /// Ignore them.
+    Swallow,
+    /// Write them to the focused pane.
+    PassThrough,
+    /// Switch to [`InputMode::Normal`], and then write them to the focused pane.
+    ExitAndPassThrough,
+}
+
+impl UnboundKeys {
+    /// Returns what `mode` does with unbound keys unless the config file says otherwise: only
+    /// [`InputMode::Normal`] writes them to the focused pane, so that a stray key in another mode
+    /// doesn't end up in the shell.
+    pub fn default_for(mode: &InputMode) -> UnboundKeys {
+        match mode {
+            InputMode::Normal => UnboundKeys::PassThrough,
+            _ => UnboundKeys::Swallow,
+        }
+    }
+}
+
+/// Populates the default hashmap of keybinds, which the `keybinds` section of the config file
+/// (see [`KeybindsConfig`]) is applied to.
 pub fn get_default_keybinds() -> Result<Keybinds, String> {
     let mut defaults = Keybinds::new();
 
-    for mode in InputMode::iter() {
-        defaults.insert(mode, get_defaults_for_mode(&mode)?);
+    for mode in InputMode::BUILT_IN.iter() {
+        let mode_defaults = get_defaults_for_mode(mode)?
+            .into_iter()
+            .map(|(key, actions)| (vec![key], actions))
+            .collect();
+        defaults.insert(mode.clone(), mode_defaults);
     }
 
     Ok(defaults)
 }
 
 /// Returns the default keybinds for a givent [`InputMode`].
-fn get_defaults_for_mode(mode: &InputMode) -> Result<ModeKeybinds, String> {
-    let mut defaults = ModeKeybinds::new();
+fn get_defaults_for_mode(mode: &InputMode) -> Result<HashMap<Key, Vec<Action>>, String> {
+    let mut defaults = HashMap::new();
 
     match *mode {
         InputMode::Normal => {
@@ -50,6 +90,7 @@ fn get_defaults_for_mode(mode: &InputMode) -> Result<ModeKeybinds, String> {
                 vec![Action::SwitchToMode(InputMode::Normal)],
             );
             defaults.insert(Key::Esc, vec![Action::SwitchToMode(InputMode::Normal)]);
+            defaults.insert(Key::Char('d'), vec![Action::Detach]);
             defaults.insert(Key::Char('q'), vec![Action::Quit]);
         }
         InputMode::Resize => {
@@ -152,28 +193,239 @@ fn get_defaults_for_mode(mode: &InputMode) -> Result<ModeKeybinds, String> {
             );
             defaults.insert(Key::Esc, vec![Action::SwitchToMode(InputMode::Command)]);
         }
+        InputMode::Custom(_) => {}
     }
 
     Ok(defaults)
 }
 
-/// Converts a [`Key`] terminal event to a sequence of [`Action`]s according to the current
-/// [`InputMode`] and [`Keybinds`].
-pub fn key_to_actions(
-    key: &Key,
-    input: Vec<u8>,
+/// The `keybinds` section of the config file, eg.
+///
+/// ```yaml
+/// keybinds:
+///   clear_defaults: false
+///   sequence_timeout_ms: 1000
+///   modes:
+///     normal:
+///       unbind: [Ctrl+g]
+///       bind:
+///         - keys: [Ctrl+a]
+///           actions: [SwitchToMode: Command]
+///         - keys: ["Ctrl+b c"]
+///           actions: [NewTab]
+///         - keys: [Ctrl+v]
+///           actions: [SwitchToMode: git]
+///     pane:
+///       unbound_keys: exit_and_pass_through
+///       bind:
+///         - keys: ["|"]
+///           actions: [NewPane: Right]
+///     git:
+///       bind:
+///         - keys: [s]
+///           actions: [Run: [{command: git, args: [status]}, Down], SwitchToMode: Normal]
+///           help: Status
+///         - keys: [Esc]
+///           actions: [SwitchToMode: Normal]
+/// ```
+///
+/// Keys are bound as sequences of keys pressed one after the other, given by their names
+/// separated by spaces, most of which only have one key. What happens to the keys that aren't
+/// bound can be set for each mode with `unbound_keys` (see [`UnboundKeys`]).
+///
+/// Modes other than the built-in ones (like `git` above) are defined by binding keys in them,
+/// and are entered with `SwitchToMode`. Nothing gets out of them but the keys they bind.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeybindsConfig {
+    /// Start from no keybinds at all instead of the default ones.
+    clear_defaults: bool,
+    /// How long to wait for the next key of a sequence, in milliseconds.
+    sequence_timeout_ms: u64,
+    modes: HashMap<InputMode, ModeKeybindsConfig>,
+}
+
+/// The changes made to the keybinds of an [`InputMode`], in this order: its default keybinds
+/// are dropped if asked for, then the sequences in `unbind` are unbound, and then the sequences
+/// in `bind` are bound, replacing what they were bound to.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ModeKeybindsConfig {
+    clear_defaults: bool,
+    unbound_keys: Option<UnboundKeys>,
+    unbind: Vec<KeySequence>,
+    bind: Vec<KeybindConfig>,
+}
+
+/// Binds each of `keys` to `actions`, showing `help` for them in the status bar if it's given.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeybindConfig {
+    keys: Vec<KeySequence>,
+    actions: Vec<Action>,
+    #[serde(default)]
+    help: Option<String>,
+}
+
+/// A sequence of [`Key`]s, given by their names (see [`parse_key_sequence`]).
+#[derive(Debug)]
+struct KeySequence(Vec<Key>);
+
+impl<'de> Deserialize<'de> for KeySequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_str(ParsedStrVisitor("the names of keys", parse_key_sequence))
+            .map(KeySequence)
+    }
+}
+
+/// Deserializes a string described by `.0` with the parsing function `.1`, failing while the
+/// deserializer still knows where the string is, so that the error says where it is.
+struct ParsedStrVisitor<T>(&'static str, fn(&str) -> Result<T, String>);
+
+impl<'de, T> Visitor<'de> for ParsedStrVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<T, E> {
+        (self.1)(s).map_err(E::custom)
+    }
+}
+
+impl Default for KeybindsConfig {
+    fn default() -> Self {
+        KeybindsConfig {
+            clear_defaults: false,
+            sequence_timeout_ms: DEFAULT_SEQUENCE_TIMEOUT.as_millis() as u64,
+            modes: HashMap::new(),
+        }
+    }
+}
+
+impl KeybindsConfig {
+    /// Returns how long the keys of a sequence can be apart.
+    pub fn sequence_timeout(&self) -> Duration {
+        Duration::from_millis(self.sequence_timeout_ms)
+    }
+
+    /// Returns what the modes for which it's set do with the keys that aren't bound in them.
+    pub fn unbound_keys(&self) -> HashMap<InputMode, UnboundKeys> {
+        self.modes
+            .iter()
+            .filter_map(|(mode, mode_config)| {
+                mode_config
+                    .unbound_keys
+                    .map(|unbound_keys| (mode.clone(), unbound_keys))
+            })
+            .collect()
+    }
+
+    /// Applies these changes to `keybinds`, returning the help texts of the keybinds they add.
+    ///
+    /// Fails if a mode is switched to but has no keybinds, since there would be no way out of it.
+    pub fn apply_to(self, keybinds: &mut Keybinds) -> Result<KeybindsHelp, String> {
+        let mut keybinds_help = KeybindsHelp::new();
+        if self.clear_defaults {
+            keybinds.values_mut().for_each(ModeKeybinds::clear);
+        }
+        for (mode, mode_config) in self.modes {
+            let mode_help = keybinds_help.entry(mode.clone()).or_default();
+            let mode_keybinds = keybinds.entry(mode).or_default();
+            if mode_config.clear_defaults {
+                mode_keybinds.clear();
+            }
+            for KeySequence(keys) in mode_config.unbind {
+                mode_keybinds.remove(&keys);
+            }
+            for keybind in mode_config.bind {
+                if let Some(help) = keybind.help {
+                    let key_names: Vec<String> = keybind
+                        .keys
+                        .iter()
+                        .map(|KeySequence(keys)| {
+                            let names: Vec<String> =
+                                keys.iter().map(|key| key_name(*key)).collect();
+                            names.join(" ")
+                        })
+                        .collect();
+                    mode_help.push((key_names.join("/"), help));
+                }
+                for KeySequence(keys) in keybind.keys {
+                    mode_keybinds.insert(keys, keybind.actions.clone());
+                }
+            }
+        }
+        keybinds_help.retain(|_, mode_help| !mode_help.is_empty());
+
+        let mut switched_to_modes: Vec<&InputMode> = keybinds
+            .values()
+            .flat_map(ModeKeybinds::values)
+            .flatten()
+            .filter_map(|action| match action {
+                Action::SwitchToMode(mode) => Some(mode),
+                _ => None,
+            })
+            .collect();
+        switched_to_modes.sort_by_key(|mode| mode.name());
+        match switched_to_modes.into_iter().find(|mode| {
+            keybinds
+                .get(mode)
+                .filter(|mode_keybinds| !mode_keybinds.is_empty())
+                .is_none()
+        }) {
+            Some(mode) => Err(format!(
+                "keybinds: the input mode \"{}\" is switched to but has no keybinds",
+                mode.name()
+            )),
+            None => Ok(keybinds_help),
+        }
+    }
+}
+
+/// Returns whether `keys` are the start of a longer sequence of keys bound in `mode`, in which
+/// case what they amount to depends on the next key.
+pub fn is_start_of_key_sequence(keys: &[Key], mode: &InputMode, keybinds: &Keybinds) -> bool {
+    keybinds[mode]
+        .keys()
+        .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
+}
+
+/// Converts the [`Key`] terminal events at the start of `keys` (each along with the bytes it was
+/// read from) to a sequence of [`Action`]s according to the current [`InputMode`] and
+/// [`Keybinds`]: the longest sequence of them that's bound, or else the first key, which is
+/// handled as `unbound_keys` says.
+///
+/// Returns the [`Action`]s along with how many keys they were made from.
+pub fn key_sequence_to_actions(
+    keys: &[(Key, Vec<u8>)],
     mode: &InputMode,
     keybinds: &Keybinds,
-) -> Vec<Action> {
-    if let Some(mode_keybinds) = keybinds.get(mode) {
-        mode_keybinds
-            .get(key)
-            .cloned()
-            // FIXME in command mode, unbound keystrokes should probably do nothing instead of
-            // writing to the terminal. Will be easier to implement after a big refactor of the
-            // input system (@categorille)
-            .unwrap_or(vec![Action::Write(input)])
-    } else {
-        unreachable!("Unrecognized mode: {:?}", mode);
-    }
+    unbound_keys: UnboundKeys,
+) -> (usize, Vec<Action>) {
+    let mode_keybinds = keybinds
+        .get(mode)
+        .unwrap_or_else(|| unreachable!("Unrecognized mode: {:?}", mode));
+    let sequence: Vec<Key> = keys.iter().map(|(key, _)| *key).collect();
+    (1..=sequence.len())
+        .rev()
+        .find_map(|len| {
+            mode_keybinds
+                .get(&sequence[..len])
+                .map(|actions| (len, actions.clone()))
+        })
+        .unwrap_or_else(|| {
+            let write = Action::Write(keys[0].1.clone());
+            let actions = match unbound_keys {
+                UnboundKeys::Swallow => vec![],
+                UnboundKeys::PassThrough => vec![write],
+                UnboundKeys::ExitAndPassThrough if *mode == InputMode::Normal => vec![write],
+                UnboundKeys::ExitAndPassThrough => {
+                    vec![Action::SwitchToMode(InputMode::Normal), write]
+                }
+            };
+            (1, actions)
+        })
 }
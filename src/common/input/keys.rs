@@ -0,0 +1,148 @@
+//! Key names, as they're given on the command line (eg. `Enter`, `C-c` or `Up`).
+
+use termion::event::Key;
+
+/// Parses the name of a key: a single character, a named key (`Enter`, `Tab`, `Space`, `Esc`,
+/// `Backspace`, `Up`, `Down`, `Left`, `Right`, `Home`, `End`, `PageUp`, `PageDown`, `Delete`,
+/// `Insert`, `BackTab` or `F1` to `F12`, in any case) or a character with a modifier, as
+/// `C-c` or `Ctrl+c` for control and `M-x` or `Alt+x` for alt.
+pub fn parse_key(name: &str) -> Result<Key, String> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(Key::Char(c));
+    }
+    if let Some(c) = strip_modifier(name, &["C-", "Ctrl+", "Ctrl-"]) {
+        let c = c.to_ascii_lowercase();
+        return if c.is_ascii_lowercase() {
+            Ok(Key::Ctrl(c))
+        } else {
+            Err(format!(
+                "invalid key {:?}: only letters can be combined with Ctrl",
+                name
+            ))
+        };
+    }
+    if let Some(c) = strip_modifier(name, &["M-", "Alt+", "Alt-"]) {
+        return Ok(Key::Alt(c));
+    }
+    let key = match name.to_ascii_lowercase().as_str() {
+        "enter" | "return" => Key::Char('\n'),
+        "tab" => Key::Char('\t'),
+        "space" => Key::Char(' '),
+        "esc" | "escape" => Key::Esc,
+        "backspace" | "bspace" => Key::Backspace,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" | "pgup" => Key::PageUp,
+        "pagedown" | "pgdn" => Key::PageDown,
+        "delete" | "del" => Key::Delete,
+        "insert" | "ins" => Key::Insert,
+        "backtab" => Key::BackTab,
+        function_key => match function_key
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<u8>().ok())
+        {
+            Some(n) if (1..=12).contains(&n) => Key::F(n),
+            _ => return Err(format!("unknown key {:?}", name)),
+        },
+    };
+    Ok(key)
+}
+
+/// Parses a sequence of keys pressed one after the other, given by their names separated by
+/// spaces (eg. `g g` or `Ctrl+a c`).
+pub fn parse_key_sequence(names: &str) -> Result<Vec<Key>, String> {
+    let keys = names
+        .split_whitespace()
+        .map(parse_key)
+        .collect::<Result<Vec<Key>, String>>()?;
+    if keys.is_empty() {
+        return Err(format!("invalid key sequence {:?}: it has no keys", names));
+    }
+    Ok(keys)
+}
+
+/// Returns the name of `key`, the way [`parse_key`] reads it back.
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char('\n') => "Enter".to_string(),
+        Key::Char('\t') => "Tab".to_string(),
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("Ctrl+{}", c),
+        Key::Alt(c) => format!("Alt+{}", c),
+        Key::F(n) => format!("F{}", n),
+        Key::Esc => "Esc".to_string(),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Up => "Up".to_string(),
+        Key::Down => "Down".to_string(),
+        Key::Left => "Left".to_string(),
+        Key::Right => "Right".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::PageUp => "PageUp".to_string(),
+        Key::PageDown => "PageDown".to_string(),
+        Key::Delete => "Delete".to_string(),
+        Key::Insert => "Insert".to_string(),
+        Key::BackTab => "BackTab".to_string(),
+        Key::Null => "Null".to_string(),
+        Key::__IsNotComplete => String::new(),
+    }
+}
+
+/// Returns the character following one of the `prefixes` in `name`, if it's all there is after
+/// it.
+fn strip_modifier(name: &str, prefixes: &[&str]) -> Option<char> {
+    prefixes.iter().find_map(|prefix| {
+        let rest = name.get(..prefix.len())?;
+        if !rest.eq_ignore_ascii_case(prefix) {
+            return None;
+        }
+        let mut chars = name[prefix.len()..].chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the bytes a terminal sends when `key` is pressed (in normal cursor key mode, see
+/// [`Pane::adjust_input_to_terminal`](crate::tab::Pane::adjust_input_to_terminal)).
+pub fn key_to_bytes(key: Key) -> Vec<u8> {
+    let escape_sequence = |sequence: &str| format!("\u{1b}{}", sequence).into_bytes();
+    match key {
+        Key::Char('\n') => vec![b'\r'],
+        Key::Char(c) => c.to_string().into_bytes(),
+        Key::Ctrl(c @ '4'..='7') => vec![c as u8 - b'4' + 0x1c],
+        Key::Ctrl(c) => vec![(c as u8).wrapping_sub(b'a').wrapping_add(1)],
+        Key::Alt(c) => escape_sequence(&c.to_string()),
+        Key::Esc => vec![0x1b],
+        Key::Backspace => vec![0x7f],
+        Key::Null => vec![0],
+        Key::Up => escape_sequence("[A"),
+        Key::Down => escape_sequence("[B"),
+        Key::Right => escape_sequence("[C"),
+        Key::Left => escape_sequence("[D"),
+        Key::Home => escape_sequence("[H"),
+        Key::End => escape_sequence("[F"),
+        Key::PageUp => escape_sequence("[5~"),
+        Key::PageDown => escape_sequence("[6~"),
+        Key::Delete => escape_sequence("[3~"),
+        Key::Insert => escape_sequence("[2~"),
+        Key::BackTab => escape_sequence("[Z"),
+        Key::F(n @ 1..=4) => escape_sequence(&format!("O{}", (b'P' + n - 1) as char)),
+        Key::F(n) => {
+            let code = match n {
+                5 => 15,
+                6..=10 => n + 11,
+                _ => n + 12,
+            };
+            escape_sequence(&format!("[{}~", code))
+        }
+        Key::__IsNotComplete => vec![],
+    }
+}
@@ -1,47 +1,328 @@
 //! IPC stuff for starting to split things into a client and server model.
 
-use serde::{Deserialize, Serialize};
+use crate::common::events::Event;
+use crate::common::input::actions::{Action, Direction};
+use crate::common::ApiCommand;
+use crate::panes::{DumpOptions, PaneId, PositionAndSize};
+use crate::pty_bus::{ExitStatus, RunCommand};
+use crate::utils::consts::ZELLIJ_SOCK_DIR;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
 
-type SessionID = u64;
+pub type SessionID = u64;
 
-#[derive(PartialEq, Eq, Serialize, Deserialize, Hash)]
+/// Identifies one of the clients connected to a server. These are handed out by the server as
+/// clients connect and are never reused during the server's lifetime.
+pub type ClientId = usize;
+
+#[derive(PartialEq, Eq, Serialize, Deserialize, Hash, Debug, Clone)]
 pub struct Session {
     // Unique ID for this session
-    id: SessionID,
+    pub id: SessionID,
     // Identifier for the underlying IPC primitive (socket, pipe)
-    conn_name: String,
+    pub conn_name: String,
     // User configured alias for the session
-    alias: String,
+    pub alias: String,
+    // When the session was created, in seconds since the unix epoch
+    pub created_at: u64,
+    // How many tabs are open in the session
+    pub tab_count: usize,
+    // How many clients are attached to the session
+    pub client_count: usize,
+}
+
+/// The structure of a session: its tabs and their panes.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionStructure {
+    pub tabs: Vec<TabInfo>,
+}
+
+/// A tab of a session, as reported in a [`SessionStructure`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TabInfo {
+    /// The index of the tab, which stays the same for as long as it's open.
+    pub index: usize,
+    /// Whether this is the tab that the client that was used last is looking at.
+    pub active: bool,
+    pub panes: Vec<PaneInfo>,
+}
+
+/// A pane of a tab, as reported in a [`SessionStructure`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PaneInfo {
+    pub id: PaneId,
+    /// The position and size of the pane in the layout of its tab.
+    pub geometry: PositionAndSize,
+    pub selectable: bool,
+    /// Whether the pane takes up the whole tab, hiding the others.
+    pub fullscreen: bool,
+    /// Whether this is the focused pane of its tab.
+    pub focused: bool,
+    /// The title of the pane: the one it was given, or else what's running in it.
+    pub title: Option<String>,
+    /// The command running in the pane, if it's a terminal that isn't just showing its shell.
+    pub command: Option<String>,
+    /// The working directory of what's running in the pane, if it's a terminal.
+    pub cwd: Option<PathBuf>,
+    /// How what ran in the pane exited, if it's a terminal that was kept open once it did.
+    pub exit_status: Option<ExitStatus>,
+}
+
+/// Which pane a request is about.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PaneTarget {
+    /// The pane with this id.
+    Id(PaneId),
+    /// The pane at this index in the list of panes of a tab, as printed by `zellij query`. The
+    /// tab is given by its index, or else it's the one the client that was used last is
+    /// looking at.
+    Index { tab: Option<usize>, pane: usize },
 }
 
 // How do we want to connect to a session?
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
 pub enum ClientType {
     Reader,
     Writer,
 }
 
+/// The version of the IPC protocol, sent along with every message. It must be bumped whenever
+/// the messages below change in a way that older clients or servers can't understand, which is
+/// anything but adding new variants at the end of an enum (adding a field to a struct, like
+/// [`PaneInfo`], does need a bump).
+pub const IPC_PROTOCOL_VERSION: u16 = 4;
+
+/// The largest message we're willing to read, so that a corrupt header can't make us allocate
+/// all of the memory there is.
+const MAX_IPC_MSG_LEN: usize = 64 * 1024 * 1024;
+
 // Types of messages sent from the client to the server
-#[derive(Serialize, Deserialize)]
+//
+// Other than the messages of attached clients (`DisconnectFromSession`, `TerminalResize` and
+// `Input`), which are answered by what they cause (eg. `Render`), these are one-shot requests
+// that are each answered with a `ServerToClientMsg::Response`
+#[derive(Serialize, Deserialize, Debug)]
 pub enum ClientToServerMsg {
     // List which sessions are available
     ListSessions,
-    // Create a new session
-    CreateSession,
-    // Attach to a running session
-    AttachToSession(SessionID, ClientType),
+    // Create a new session on a terminal of the given size
+    CreateSession(PositionAndSize),
+    // Attach to a running session from a terminal of the given size
+    AttachToSession(SessionID, ClientType, PositionAndSize),
     // Force detach
     DetachSession(SessionID),
     // Disconnect from the session we're connected to
     DisconnectFromSession,
+    // End the session, closing all of its panes
+    KillSession,
+    // The client's terminal was resized
+    TerminalResize(PositionAndSize),
+    // Raw bytes read from the client's stdin
+    Input(Vec<u8>),
+    // A one-shot command sent from the command line (eg. `zellij --split h`)
+    Command(ApiCommand),
+    // An action sent from the command line (eg. `zellij action new-tab`)
+    Action(Action),
+    // Get the contents of the given pane, or else of the focused one
+    DumpScreen(Option<PaneId>, DumpOptions),
+    // Get the structure of the session
+    QuerySession,
+    // Write input to the given pane, or else to the focused one. Each chunk of it (eg. a key) is
+    // adjusted to the state of the pane's terminal on its own
+    SendKeys(Option<PaneTarget>, Vec<Vec<u8>>),
+    // Get the events of the session as they happen, each in a `ServerToClientMsg::Event`, until
+    // disconnecting
+    Subscribe,
+    // Open a new pane running the given command, getting its id
+    Run(RunCommand, Option<Direction>),
 }
 
 // Types of messages sent from the server to the client
-// @@@ Implement Serialize and Deserialize for this...
-pub enum _ServerToClientMsg {
-    // Info about a particular session
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ServerToClientMsg {
+    // The answer to a one-shot request
+    Response(Response),
+    // A rendered frame to be written to the client's terminal as-is
+    Render(String),
+    // The last `Input` message was handled, more may be sent
+    UnblockInputThread,
+    // The client should exit, for the given reason
+    Exit(ExitReason),
+    // Something happened in the session, sent to the clients that subscribed to its events
+    Event(Event),
+}
+
+/// How a server answered a request of one of its clients.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Response {
+    /// The request was carried out.
+    Ok,
+    /// The request was carried out, and this is what it asked for.
+    Data(ResponseData),
+    /// The request couldn't be carried out, for the given reason.
+    Error(String),
+}
+
+/// What a [`Response`] to a request can carry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ResponseData {
+    /// Info about the session a client created or attached to.
     SessionInfo(Session),
-    // A list of sessions
+    /// A list of sessions.
     SessionList(HashSet<Session>),
+    /// The contents of a pane, as text.
+    PaneContents(String),
+    /// The structure of the session.
+    SessionStructure(SessionStructure),
+    /// The id of a pane.
+    PaneId(PaneId),
+}
+
+/// Why an IPC message couldn't be read.
+#[derive(Debug)]
+pub enum IpcError {
+    /// The connection was closed, or broke in a way that leaves no way of finding where the
+    /// next message starts.
+    Io(io::Error),
+    /// A whole message was read but couldn't be understood (eg. it was sent by another version
+    /// of zellij). The messages after it can still be read.
+    Malformed(String),
+}
+
+impl std::fmt::Display for IpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IpcError::Io(e) => write!(f, "{}", e),
+            IpcError::Malformed(reason) => write!(f, "malformed message: {}", reason),
+        }
+    }
+}
+
+/// Why a server told one of its clients to go away.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ExitReason {
+    /// The session ended (eg. the user quit or the last tab was closed).
+    Normal,
+    /// The client asked to be detached from the session.
+    Detached,
+    /// Another client forced this one to detach.
+    ForceDetached,
+    /// The server crashed, carrying the error to display.
+    Error(String),
+}
+
+/// Writes a single IPC message to `writer`, as a frame made of the protocol version (2 bytes),
+/// the length of the message (4 bytes), both big-endian, and then the message itself.
+pub fn send_ipc_msg<T: Serialize>(writer: &mut impl Write, msg: &T) -> io::Result<()> {
+    let body =
+        bincode::serialize(msg).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if body.len() > MAX_IPC_MSG_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes is too long to be sent", body.len()),
+        ));
+    }
+    let mut frame = Vec::with_capacity(6 + body.len());
+    frame.extend_from_slice(&IPC_PROTOCOL_VERSION.to_be_bytes());
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&body);
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Reads a single IPC message written by [`send_ipc_msg`] from `reader`, blocking until one is
+/// available.
+pub fn recv_ipc_msg<T: DeserializeOwned>(reader: &mut impl Read) -> Result<T, IpcError> {
+    let mut header = [0; 6];
+    reader.read_exact(&mut header).map_err(IpcError::Io)?;
+    let version = u16::from_be_bytes([header[0], header[1]]);
+    let len = u32::from_be_bytes([header[2], header[3], header[4], header[5]]) as usize;
+    if len > MAX_IPC_MSG_LEN {
+        return Err(IpcError::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message of {} bytes is too long to be received", len),
+        )));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).map_err(IpcError::Io)?;
+    if version != IPC_PROTOCOL_VERSION {
+        return Err(IpcError::Malformed(format!(
+            "unsupported protocol version {} (expected {})",
+            version, IPC_PROTOCOL_VERSION
+        )));
+    }
+    bincode::deserialize(&body).map_err(|e| IpcError::Malformed(e.to_string()))
+}
+
+/// Returns the path of the IPC socket of the session named `session_name`.
+pub fn session_socket_path(session_name: &str) -> PathBuf {
+    Path::new(ZELLIJ_SOCK_DIR).join(session_name)
+}
+
+/// Returns the names of all the sessions that have a socket, whether their server is still
+/// alive or not.
+pub fn session_names() -> Vec<String> {
+    session_names_in(Path::new(ZELLIJ_SOCK_DIR))
+}
+
+/// Returns the names of all the sessions that have a socket in `sock_dir`, whether their server
+/// is still alive or not.
+pub fn session_names_in(sock_dir: &Path) -> Vec<String> {
+    let mut session_names: Vec<String> = fs::read_dir(sock_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    session_names.sort();
+    session_names
+}
+
+/// Returns whether a server is listening on the socket of the session named `session_name`.
+pub fn session_is_alive(session_name: &str) -> bool {
+    UnixStream::connect(session_socket_path(session_name)).is_ok()
+}
+
+/// Returns the names of all the sessions whose server is still alive.
+pub fn live_session_names() -> Vec<String> {
+    session_names()
+        .into_iter()
+        .filter(|session_name| session_is_alive(session_name))
+        .collect()
+}
+
+/// Picks a name for a new session when the user didn't give one: the smallest number that
+/// isn't already taken by another session. The name is reserved by binding the session's socket
+/// with `bind`, trying the next one if that fails because another session got there first, so
+/// that sessions started at the same time can't end up with the same name.
+pub fn new_session_name(mut bind: impl FnMut(&Path) -> io::Result<()>) -> io::Result<String> {
+    let taken_names = session_names();
+    let mut names = (0..)
+        .map(|i: usize| i.to_string())
+        .filter(|name| !taken_names.contains(name));
+    loop {
+        let name = names.next().unwrap();
+        match bind(&session_socket_path(&name)) {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => continue,
+            result => return result.map(|()| name),
+        }
+    }
+}
+
+/// Checks that `session_name` can be used as the file name of a session's socket.
+pub fn validate_session_name(session_name: &str) -> Result<(), String> {
+    if session_name.is_empty() || session_name.contains('/') || session_name.starts_with('.') {
+        Err(format!(
+            "invalid session name {:?}: it must be non-empty, can't contain '/' and can't start with '.'",
+            session_name
+        ))
+    } else {
+        Ok(())
+    }
 }
@@ -1,4 +1,9 @@
+use crate::common::ipc::{
+    recv_ipc_msg, send_ipc_msg, ClientId, ClientToServerMsg, IpcError, Response, ServerToClientMsg,
+};
 use crate::panes::PositionAndSize;
+use crate::pty_bus::{ExitStatus, TerminalAction};
+use nix::errno::Errno;
 use nix::fcntl::{fcntl, FcntlArg, OFlag};
 use nix::pty::{forkpty, Winsize};
 use nix::sys::signal::{kill, Signal};
@@ -6,12 +11,19 @@ use nix::sys::termios;
 use nix::sys::wait::waitpid;
 use nix::unistd;
 use nix::unistd::{ForkResult, Pid};
+use std::collections::HashMap;
 use std::io;
 use std::io::prelude::*;
+use std::net::Shutdown;
 use std::os::unix::io::RawFd;
-use std::path::PathBuf;
-use std::process::{Child, Command};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command};
+use std::sync::mpsc::{channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 use std::env;
 
@@ -62,18 +74,18 @@ pub fn set_terminal_size_using_fd(fd: RawFd, columns: u16, rows: u16) {
 }
 
 /// Handle some signals for the child process. This will loop until the child
-/// process exits.
-fn handle_command_exit(mut child: Child) {
+/// process exits, returning how it did.
+fn handle_command_exit(mut child: Child) -> process::ExitStatus {
     // register the SIGINT signal (TODO handle more signals)
     let signals = ::signal_hook::iterator::Signals::new(&[::signal_hook::SIGINT]).unwrap();
     'handle_exit: loop {
         // test whether the child process has exited
         match child.try_wait() {
-            Ok(Some(_status)) => {
+            Ok(Some(status)) => {
                 // if the child process has exited, break outside of the loop
                 // and exit this function
                 // TODO: handle errors?
-                break 'handle_exit;
+                break 'handle_exit status;
             }
             Ok(None) => {
                 ::std::thread::sleep(::std::time::Duration::from_millis(100));
@@ -86,8 +98,7 @@ fn handle_command_exit(mut child: Child) {
             match signal {
                 signal_hook::SIGINT => {
                     child.kill().unwrap();
-                    child.wait().unwrap();
-                    break 'handle_exit;
+                    break 'handle_exit child.wait().unwrap();
                 }
                 _ => {}
             }
@@ -95,21 +106,62 @@ fn handle_command_exit(mut child: Child) {
     }
 }
 
+/// Exits the current process the same way as a child process that exited with `status`, so that
+/// whoever waits for it can find out how that child did.
+fn exit_like(status: process::ExitStatus) -> ! {
+    if let Some(signal) = status.signal() {
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        // the signal doesn't end processes
+        process::exit(128 + signal);
+    }
+    process::exit(status.code().unwrap_or(1))
+}
+
+/// Returns how the process `pid` exited, blocking until it does, without reaping it. `None` is
+/// returned if it can't be waited for (eg. if it was already reaped).
+fn wait_for_exit(pid: RawFd) -> Option<ExitStatus> {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    loop {
+        let flags = libc::WEXITED | libc::WNOWAIT;
+        if unsafe { libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, flags) } == 0 {
+            break;
+        }
+        if Errno::last() != Errno::EINTR {
+            return None;
+        }
+    }
+    let status = unsafe { info.si_status() };
+    match info.si_code {
+        libc::CLD_EXITED => Some(ExitStatus::Code(status)),
+        libc::CLD_KILLED | libc::CLD_DUMPED => Some(ExitStatus::Signal(status)),
+        _ => None,
+    }
+}
+
 /// Spawns a new terminal from the parent terminal with [`termios`](termios::Termios)
 /// `orig_termios`.
 ///
-/// If a `file_to_open` is given, the text editor specified by environment variable `EDITOR`
-/// (or `VISUAL`, if `EDITOR` is not set) will be started in the new terminal, with the given
-/// file open. If no file is given, the shell specified by environment variable `SHELL` will
-/// be started in the new terminal.
+/// If a file is to be opened with `terminal_action`, the text editor specified by environment
+/// variable `EDITOR` (or `VISUAL`, if `EDITOR` is not set) will be started in the new terminal,
+/// with the given file open. If a command is to be run, it's started in the new terminal as is.
+/// Otherwise, the shell specified by environment variable `SHELL` will be started in the new
+/// terminal.
+///
+/// Any of them is started in the directory `cwd` (or the one of the command, if it has one) if
+/// it's given and still exists, and in the current directory otherwise.
 ///
 /// # Panics
 ///
-/// This function will panic if both the `EDITOR` and `VISUAL` environment variables are not
-/// set.
-// FIXME this should probably be split into different functions, or at least have less levels
-// of indentation in some way
-fn spawn_terminal(file_to_open: Option<PathBuf>, orig_termios: termios::Termios) -> (RawFd, RawFd) {
+/// This function will panic if a file is to be opened and both the `EDITOR` and `VISUAL`
+/// environment variables are not set.
+fn spawn_terminal(
+    terminal_action: Option<TerminalAction>,
+    cwd: Option<PathBuf>,
+    orig_termios: termios::Termios,
+) -> (RawFd, RawFd) {
     let (pid_primary, pid_secondary): (RawFd, RawFd) = {
         match forkpty(None, Some(&orig_termios)) {
             Ok(fork_pty_res) => {
@@ -121,29 +173,37 @@ fn spawn_terminal(file_to_open: Option<PathBuf>, orig_termios: termios::Termios)
                             .expect("could not fcntl");
                         child
                     }
-                    ForkResult::Child => match file_to_open {
-                        Some(file_to_open) => {
-                            if env::var("EDITOR").is_err() && env::var("VISUAL").is_err() {
-                                panic!("Can't edit files if an editor is not defined. To fix: define the EDITOR or VISUAL environment variables with the path to your editor (eg. /usr/bin/vim)");
+                    ForkResult::Child => {
+                        let (mut command, cwd) = match terminal_action {
+                            Some(TerminalAction::OpenFile(file_to_open)) => {
+                                if env::var("EDITOR").is_err() && env::var("VISUAL").is_err() {
+                                    panic!("Can't edit files if an editor is not defined. To fix: define the EDITOR or VISUAL environment variables with the path to your editor (eg. /usr/bin/vim)");
+                                }
+                                let editor = env::var("EDITOR")
+                                    .unwrap_or_else(|_| env::var("VISUAL").unwrap());
+                                let mut command = Command::new(editor);
+                                command.arg(file_to_open);
+                                (command, cwd)
                             }
-                            let editor =
-                                env::var("EDITOR").unwrap_or_else(|_| env::var("VISUAL").unwrap());
-
-                            let child = Command::new(editor)
-                                .args(&[file_to_open])
-                                .spawn()
-                                .expect("failed to spawn");
-                            handle_command_exit(child);
-                            ::std::process::exit(0);
+                            Some(TerminalAction::RunCommand(run_command)) => {
+                                let mut command = Command::new(&run_command.command);
+                                command.args(&run_command.args).envs(run_command.env);
+                                (command, run_command.cwd.or(cwd))
+                            }
+                            None => (Command::new(env::var("SHELL").unwrap()), cwd),
+                        };
+                        if let Some(cwd) = cwd.filter(|cwd| cwd.is_dir()) {
+                            command.current_dir(cwd);
                         }
-                        None => {
-                            let child = Command::new(env::var("SHELL").unwrap())
-                                .spawn()
-                                .expect("failed to spawn");
-                            handle_command_exit(child);
-                            ::std::process::exit(0);
+                        match command.spawn() {
+                            Ok(child) => exit_like(handle_command_exit(child)),
+                            Err(e) => {
+                                // shown in the new terminal
+                                eprintln!("failed to run {:?}: {}", command, e);
+                                ::std::process::exit(127);
+                            }
                         }
-                    },
+                    }
                 };
                 (pid_primary, pid_secondary.as_raw())
             }
@@ -155,9 +215,171 @@ fn spawn_terminal(file_to_open: Option<PathBuf>, orig_termios: termios::Termios)
     (pid_primary, pid_secondary)
 }
 
+/// Returns the process in the foreground of the virtual terminal referred to by `fd`, along with
+/// the process that started the terminal's shell (the leader of its session).
+fn get_foreground_process(fd: RawFd) -> Option<(libc::pid_t, libc::pid_t)> {
+    let foreground_process = unsafe { libc::tcgetpgrp(fd) };
+    let session_leader = unsafe { libc::tcgetsid(fd) };
+    if foreground_process <= 0 || session_leader <= 0 {
+        return None;
+    }
+    Some((foreground_process, session_leader))
+}
+
+/// Returns the id of the parent of process `pid`, as found in `/proc`.
+fn get_parent_process(pid: libc::pid_t) -> Option<libc::pid_t> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // the process name comes in parentheses right before, and may contain spaces itself
+    let (_, stat) = stat.split_at(stat.rfind(')')? + 1);
+    stat.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Quotes `arg` for a POSIX shell, unless it's only made of characters that don't need it.
+fn quote_shell_arg(arg: &str) -> String {
+    let needs_quoting = arg.is_empty()
+        || !arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if needs_quoting {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// The messages received by a server, tagged with the id of the client that sent them.
+type ClientMessages = Receiver<(ClientId, ClientToServerMsg)>;
+type ClientConnections = Arc<Mutex<HashMap<ClientId, ClientConnection>>>;
+
+/// How many messages can be waiting to be written to a client. A client that lets more than
+/// this pile up isn't keeping up with the session, and is dropped.
+const CLIENT_QUEUE_LEN: usize = 256;
+
+/// How long writing a message to a client can take before giving up on that client.
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The connection of the server to one of its clients. Messages are written to it by a thread
+/// of its own, so that a client that doesn't read them can't hold up the rest of the server.
+struct ClientConnection {
+    /// The messages waiting to be written to the client.
+    queue: SyncSender<ServerToClientMsg>,
+    /// The socket of the client, kept to shut it down when the client is dropped.
+    stream: UnixStream,
+    /// The thread writing the messages in `queue` to the client.
+    writer: thread::JoinHandle<()>,
+}
+
+impl ClientConnection {
+    fn new(client_id: ClientId, stream: UnixStream) -> io::Result<Self> {
+        let mut writer_stream = stream.try_clone()?;
+        writer_stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT))?;
+        let (queue, queued_msgs) = sync_channel::<ServerToClientMsg>(CLIENT_QUEUE_LEN);
+        let writer = thread::Builder::new()
+            .name(format!("ipc_client_writer_{}", client_id))
+            .spawn(move || {
+                for msg in queued_msgs {
+                    if send_ipc_msg(&mut writer_stream, &msg).is_err() {
+                        // the reader of the client notices and disconnects it
+                        let _ = writer_stream.shutdown(Shutdown::Both);
+                        break;
+                    }
+                }
+            })?;
+        Ok(ClientConnection {
+            queue,
+            stream,
+            writer,
+        })
+    }
+}
+
+/// Queues `msg` to be written to the client with id `client_id`, returning whether it was. A
+/// client that has fallen too far behind is dropped instead, and its socket is shut down so that
+/// it gets disconnected from the session.
+fn queue_msg_to_client(
+    client_connections: &Mutex<HashMap<ClientId, ClientConnection>>,
+    client_id: ClientId,
+    msg: ServerToClientMsg,
+) -> bool {
+    let mut client_connections = client_connections.lock().unwrap();
+    let queued = match client_connections.get(&client_id) {
+        Some(connection) => connection.queue.try_send(msg).is_ok(),
+        None => return false,
+    };
+    if !queued {
+        if let Some(connection) = client_connections.remove(&client_id) {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+    }
+    queued
+}
+
+/// Accepts client connections on `listener`, reading the messages of every client on its own
+/// thread and funnelling them into `send_client_msgs`. Messages that can't be understood are
+/// answered with an error right away, without ever reaching the server.
+fn accept_clients(
+    listener: UnixListener,
+    client_connections: ClientConnections,
+    send_client_msgs: Sender<(ClientId, ClientToServerMsg)>,
+) {
+    for (client_id, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let mut reader = match stream.try_clone() {
+            Ok(reader) => io::BufReader::new(reader),
+            Err(_) => continue,
+        };
+        let connection = match ClientConnection::new(client_id, stream) {
+            Ok(connection) => connection,
+            Err(_) => continue,
+        };
+        client_connections
+            .lock()
+            .unwrap()
+            .insert(client_id, connection);
+        let client_connections = client_connections.clone();
+        let send_client_msgs = send_client_msgs.clone();
+        thread::Builder::new()
+            .name(format!("ipc_client_{}", client_id))
+            .spawn(move || {
+                loop {
+                    match recv_ipc_msg(&mut reader) {
+                        Ok(msg) => {
+                            if send_client_msgs.send((client_id, msg)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e @ IpcError::Malformed(_)) => {
+                            let reply = ServerToClientMsg::Response(Response::Error(e.to_string()));
+                            queue_msg_to_client(&client_connections, client_id, reply);
+                        }
+                        Err(IpcError::Io(_)) => break,
+                    }
+                }
+                // the client went away, whether it said goodbye or not
+                client_connections.lock().unwrap().remove(&client_id);
+                let _ =
+                    send_client_msgs.send((client_id, ClientToServerMsg::DisconnectFromSession));
+            })
+            .unwrap();
+    }
+}
+
 #[derive(Clone)]
 pub struct OsInputOutput {
     orig_termios: Arc<Mutex<termios::Termios>>,
+    /// The connection to the server, when acting as a client.
+    server_stream: Arc<Mutex<Option<UnixStream>>>,
+    /// The read end of [`Self::server_stream`], kept apart so that reading never blocks sending.
+    server_reader: Arc<Mutex<Option<io::BufReader<UnixStream>>>>,
+    /// The socket of the session, once it's bound and until it's served.
+    listener: Arc<Mutex<Option<UnixListener>>>,
+    /// The connections to every client, when acting as a server.
+    client_connections: ClientConnections,
+    /// The messages received from every client, when acting as a server.
+    client_receiver: Arc<Mutex<Option<ClientMessages>>>,
 }
 
 /// The `OsApi` trait represents an abstract interface to the features of an operating system that
@@ -173,8 +395,19 @@ pub trait OsApi: Send + Sync {
     /// Set the terminal associated to file descriptor `fd` to
     /// [cooked mode](https://en.wikipedia.org/wiki/Terminal_mode).
     fn unset_raw_mode(&mut self, fd: RawFd);
-    /// Spawn a new terminal, with an optional file to open in a terminal program.
-    fn spawn_terminal(&mut self, file_to_open: Option<PathBuf>) -> (RawFd, RawFd);
+    /// Spawn a new terminal, running what `terminal_action` asks for instead of the shell if
+    /// given, in the directory `cwd` (or in the current one).
+    fn spawn_terminal(
+        &mut self,
+        terminal_action: Option<TerminalAction>,
+        cwd: Option<PathBuf>,
+    ) -> (RawFd, RawFd);
+    /// Returns the working directory of the process in the foreground of the virtual terminal
+    /// referred to by `fd`.
+    fn get_cwd(&self, fd: RawFd) -> Option<PathBuf>;
+    /// Returns the command line of the process in the foreground of the virtual terminal
+    /// referred to by `fd`, or `None` if that's the shell the terminal was started with.
+    fn get_foreground_command(&self, fd: RawFd) -> Option<String>;
     /// Read bytes from the standard output of the virtual terminal referred to by `fd`.
     fn read_from_tty_stdout(&mut self, fd: RawFd, buf: &mut [u8]) -> Result<usize, nix::Error>;
     /// Write bytes to the standard input of the virtual terminal referred to by `fd`.
@@ -186,12 +419,40 @@ pub trait OsApi: Send + Sync {
     // or a nix::unistd::Pid. See `man kill.3`, nix::sys::signal::kill (both take an argument
     // called `pid` and of type `pid_t`, and not `fd`)
     fn kill(&mut self, pid: RawFd) -> Result<(), nix::Error>;
+    /// Blocks until the process with process ID `pid` exits, returning how it did, or `None` if
+    /// it can't be waited for. The process is left for [`kill`](OsApi::kill) to clean up.
+    fn wait_for_exit(&self, pid: RawFd) -> Option<ExitStatus>;
     /// Returns the raw contents of standard input.
     fn read_from_stdin(&self) -> Vec<u8>;
     /// Returns the writer that allows writing to standard output.
     fn get_stdout_writer(&self) -> Box<dyn io::Write>;
+    /// Blocks forever, calling `cb` whenever the terminal is resized (ie. on every `SIGWINCH`).
+    fn receive_sigwinch(&self, cb: Box<dyn Fn()>);
     /// Returns a [`Box`] pointer to this [`OsApi`] struct.
     fn box_clone(&self) -> Box<dyn OsApi>;
+    /// Binds the IPC socket at `socket_path`, so that no other server can take it before
+    /// [`Self::serve`] starts listening on it.
+    fn bind(&mut self, socket_path: &Path) -> io::Result<()>;
+    /// Starts listening for clients on the IPC socket at `socket_path`, binding it unless
+    /// [`Self::bind`] already did.
+    fn serve(&mut self, socket_path: &Path) -> io::Result<()>;
+    /// Blocks until a message is received from any of the connected clients.
+    fn recv_from_client(&self) -> (ClientId, ClientToServerMsg);
+    /// Like [`Self::recv_from_client`], but gives up after `timeout`, returning `None`.
+    fn recv_from_client_timeout(&self, timeout: Duration) -> Option<(ClientId, ClientToServerMsg)>;
+    /// Sends a message to the client with id `client_id`, without waiting for it to be written.
+    /// Messages to clients that are already gone are dropped, and clients that fall too far
+    /// behind are disconnected. Returns whether the message is on its way.
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> bool;
+    /// Disconnects all the clients, once the messages sent to them have been written.
+    fn disconnect_clients(&self);
+    /// Connects to the server listening on the IPC socket at `socket_path`.
+    fn connect_to_server(&mut self, socket_path: &Path) -> io::Result<()>;
+    /// Sends a message to the server this client is connected to.
+    fn send_to_server(&self, msg: ClientToServerMsg);
+    /// Blocks until a message is received from the server, returning `None` if the connection
+    /// was lost.
+    fn recv_from_server(&self) -> Option<ServerToClientMsg>;
 }
 
 impl OsApi for OsInputOutput {
@@ -208,9 +469,36 @@ impl OsApi for OsInputOutput {
         let orig_termios = self.orig_termios.lock().unwrap();
         unset_raw_mode(fd, orig_termios.clone());
     }
-    fn spawn_terminal(&mut self, file_to_open: Option<PathBuf>) -> (RawFd, RawFd) {
+    fn spawn_terminal(
+        &mut self,
+        terminal_action: Option<TerminalAction>,
+        cwd: Option<PathBuf>,
+    ) -> (RawFd, RawFd) {
         let orig_termios = self.orig_termios.lock().unwrap();
-        spawn_terminal(file_to_open, orig_termios.clone())
+        spawn_terminal(terminal_action, cwd, orig_termios.clone())
+    }
+    fn get_cwd(&self, fd: RawFd) -> Option<PathBuf> {
+        let (foreground_process, _) = get_foreground_process(fd)?;
+        std::fs::read_link(format!("/proc/{}/cwd", foreground_process)).ok()
+    }
+    fn get_foreground_command(&self, fd: RawFd) -> Option<String> {
+        let (foreground_process, session_leader) = get_foreground_process(fd)?;
+        if foreground_process == session_leader
+            || get_parent_process(foreground_process)? == session_leader
+        {
+            // the shell itself (or what started it), nothing is running in it
+            return None;
+        }
+        let cmdline = std::fs::read(format!("/proc/{}/cmdline", foreground_process)).ok()?;
+        let args: Vec<String> = cmdline
+            .split(|byte| *byte == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| quote_shell_arg(&String::from_utf8_lossy(arg)))
+            .collect();
+        if args.is_empty() {
+            return None;
+        }
+        Some(args.join(" "))
     }
     fn read_from_tty_stdout(&mut self, fd: RawFd, buf: &mut [u8]) -> Result<usize, nix::Error> {
         unistd::read(fd, buf)
@@ -233,15 +521,98 @@ impl OsApi for OsInputOutput {
         stdin.consume(length);
         read_bytes
     }
+    fn receive_sigwinch(&self, cb: Box<dyn Fn()>) {
+        let signals = ::signal_hook::iterator::Signals::new([::signal_hook::SIGWINCH]).unwrap();
+        for _ in signals.forever() {
+            cb();
+        }
+    }
     fn get_stdout_writer(&self) -> Box<dyn io::Write> {
         let stdout = ::std::io::stdout();
         Box::new(stdout)
     }
     fn kill(&mut self, pid: RawFd) -> Result<(), nix::Error> {
-        kill(Pid::from_raw(pid), Some(Signal::SIGINT)).unwrap();
-        waitpid(Pid::from_raw(pid), None).unwrap();
+        kill(Pid::from_raw(pid), Some(Signal::SIGINT))?;
+        waitpid(Pid::from_raw(pid), None)?;
         Ok(())
     }
+    fn wait_for_exit(&self, pid: RawFd) -> Option<ExitStatus> {
+        wait_for_exit(pid)
+    }
+    fn bind(&mut self, socket_path: &Path) -> io::Result<()> {
+        *self.listener.lock().unwrap() = Some(UnixListener::bind(socket_path)?);
+        Ok(())
+    }
+    fn serve(&mut self, socket_path: &Path) -> io::Result<()> {
+        let listener = match self.listener.lock().unwrap().take() {
+            Some(listener) => listener,
+            None => UnixListener::bind(socket_path)?,
+        };
+        let (send_client_msgs, receive_client_msgs) = channel();
+        *self.client_receiver.lock().unwrap() = Some(receive_client_msgs);
+        let client_connections = self.client_connections.clone();
+        thread::Builder::new()
+            .name("ipc_listener".to_string())
+            .spawn(move || accept_clients(listener, client_connections, send_client_msgs))?;
+        Ok(())
+    }
+    fn recv_from_client(&self) -> (ClientId, ClientToServerMsg) {
+        self.client_receiver
+            .lock()
+            .unwrap()
+            .as_ref()
+            .expect("not serving any clients")
+            .recv()
+            .expect("failed to receive client message")
+    }
+    fn recv_from_client_timeout(&self, timeout: Duration) -> Option<(ClientId, ClientToServerMsg)> {
+        match self
+            .client_receiver
+            .lock()
+            .unwrap()
+            .as_ref()
+            .expect("not serving any clients")
+            .recv_timeout(timeout)
+        {
+            Ok(client_msg) => Some(client_msg),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => panic!("failed to receive client message"),
+        }
+    }
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> bool {
+        queue_msg_to_client(&self.client_connections, client_id, msg)
+    }
+    fn disconnect_clients(&self) {
+        let client_connections: Vec<ClientConnection> = self
+            .client_connections
+            .lock()
+            .unwrap()
+            .drain()
+            .map(|(_, connection)| connection)
+            .collect();
+        for connection in client_connections {
+            // dropping the queue lets the writer finish once it's empty
+            drop(connection.queue);
+            let _ = connection.writer.join();
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+    }
+    fn connect_to_server(&mut self, socket_path: &Path) -> io::Result<()> {
+        let stream = UnixStream::connect(socket_path)?;
+        *self.server_reader.lock().unwrap() = Some(io::BufReader::new(stream.try_clone()?));
+        *self.server_stream.lock().unwrap() = Some(stream);
+        Ok(())
+    }
+    fn send_to_server(&self, msg: ClientToServerMsg) {
+        let mut server_stream = self.server_stream.lock().unwrap();
+        let stream = server_stream.as_mut().expect("not connected to a server");
+        let _ = send_ipc_msg(stream, &msg);
+    }
+    fn recv_from_server(&self) -> Option<ServerToClientMsg> {
+        let mut server_reader = self.server_reader.lock().unwrap();
+        let reader = server_reader.as_mut().expect("not connected to a server");
+        recv_ipc_msg(reader).ok()
+    }
 }
 
 impl Clone for Box<dyn OsApi> {
@@ -253,5 +624,12 @@ impl Clone for Box<dyn OsApi> {
 pub fn get_os_input() -> OsInputOutput {
     let current_termios = termios::tcgetattr(0).unwrap();
     let orig_termios = Arc::new(Mutex::new(current_termios));
-    OsInputOutput { orig_termios }
+    OsInputOutput {
+        orig_termios,
+        server_stream: Arc::new(Mutex::new(None)),
+        server_reader: Arc::new(Mutex::new(None)),
+        listener: Arc::new(Mutex::new(None)),
+        client_connections: Arc::new(Mutex::new(HashMap::new())),
+        client_receiver: Arc::new(Mutex::new(None)),
+    }
 }
@@ -7,9 +7,12 @@ use ::std::pin::*;
 use ::std::sync::mpsc::Receiver;
 use ::std::time::{Duration, Instant};
 use ::vte;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
 
-use super::{ScreenInstruction, SenderWithContext, OPENCALLS};
+use super::{screen::ScreenInstruction, SenderWithContext, OPENCALLS};
+use crate::common::events::{Event, EventSender};
 use crate::os_input_output::OsApi;
 use crate::utils::logging::debug_to_file;
 use crate::{
@@ -145,33 +148,108 @@ impl vte::Perform for VteEventSender {
     }
 }
 
+/// What to run in a new terminal, instead of the user's shell.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum TerminalAction {
+    /// Open a file in the user's editor.
+    OpenFile(PathBuf),
+    /// Run a command.
+    RunCommand(RunCommand),
+}
+
+impl TerminalAction {
+    /// Whether the pane of the terminal should stay open once what runs in it exits.
+    pub fn holds_on_exit(&self) -> bool {
+        match self {
+            TerminalAction::OpenFile(_) => false,
+            TerminalAction::RunCommand(run_command) => run_command.hold_on_exit,
+        }
+    }
+}
+
+/// A command to run in a new terminal. Only the command has to be given in the config file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunCommand {
+    /// The program to run, looked up in the `PATH` unless it's a path.
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The directory to run the command in, instead of the one the server was started in.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// Environment variables to set for the command, on top of the server's environment.
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+    /// Keep the pane open once the command exits, with its output still in it.
+    #[serde(default)]
+    pub hold_on_exit: bool,
+}
+
+/// How what ran in a terminal exited.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExitStatus {
+    /// It exited on its own, with this exit code.
+    Code(i32),
+    /// It was killed by this signal.
+    Signal(i32),
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExitStatus::Code(code) => write!(f, "exited with code {}", code),
+            ExitStatus::Signal(signal) => write!(f, "was killed by signal {}", signal),
+        }
+    }
+}
+
 /// Instructions related to PTYs (pseudoterminals).
 #[derive(Clone, Debug)]
 pub enum PtyInstruction {
-    SpawnTerminal(Option<PathBuf>),
-    SpawnTerminalVertically(Option<PathBuf>),
-    SpawnTerminalHorizontally(Option<PathBuf>),
+    SpawnTerminal(Option<TerminalAction>),
+    SpawnTerminalVertically(Option<TerminalAction>),
+    SpawnTerminalHorizontally(Option<TerminalAction>),
     NewTab,
+    /// Opens a tab laid out as `Layout`, as part of resurrecting a saved session.
+    ResurrectTab(Layout),
     ClosePane(PaneId),
+    /// Runs the command of a held terminal pane again, in a new terminal that takes the place of
+    /// its own.
+    RerunCommand(RawFd),
+    /// The process (second) running in the terminal (first) exited, as told by its status if
+    /// it could be waited for.
+    TerminalExited(RawFd, RawFd, Option<ExitStatus>),
     CloseTab(Vec<PaneId>),
     Quit,
 }
 
 pub struct PtyBus {
+    send_pty_instructions: SenderWithContext<PtyInstruction>,
     pub send_screen_instructions: SenderWithContext<ScreenInstruction>,
     pub send_plugin_instructions: SenderWithContext<PluginInstruction>,
     pub receive_pty_instructions: Receiver<(PtyInstruction, ErrorContext)>,
     pub id_to_child_pid: HashMap<RawFd, RawFd>,
     os_input: Box<dyn OsApi>,
+    events: EventSender,
     debug_to_file: bool,
     task_handles: HashMap<RawFd, JoinHandle<()>>,
+    /// What runs in the terminals whose pane is held open once it exits, so that it can be run
+    /// again.
+    held_terminal_actions: HashMap<RawFd, TerminalAction>,
 }
 
+/// Reads the output of the terminal `pid` until it ends, typing `command_to_type` into the
+/// terminal once it's shown its first output (ie. once its shell is ready for input). Once the
+/// process `child_pid` that runs in the terminal exits, the pty thread is told how it did with
+/// [`PtyInstruction::TerminalExited`].
 fn stream_terminal_bytes(
     pid: RawFd,
+    child_pid: RawFd,
+    send_pty_instructions: SenderWithContext<PtyInstruction>,
     mut send_screen_instructions: SenderWithContext<ScreenInstruction>,
     os_input: Box<dyn OsApi>,
     debug: bool,
+    mut command_to_type: Option<String>,
 ) -> JoinHandle<()> {
     let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
     task::spawn({
@@ -180,7 +258,8 @@ fn stream_terminal_bytes(
             send_screen_instructions.update(err_ctx);
             let mut vte_parser = vte::Parser::new();
             let mut vte_event_sender = VteEventSender::new(pid, send_screen_instructions.clone());
-            let mut terminal_bytes = ReadFromPid::new(&pid, os_input);
+            let mut terminal_bytes = ReadFromPid::new(&pid, os_input.clone());
+            let mut os_input = os_input;
 
             let mut last_byte_receive_time: Option<Instant> = None;
             let mut pending_render = false;
@@ -195,6 +274,9 @@ fn stream_terminal_bytes(
                     vte_parser.advance(&mut vte_event_sender, byte);
                 }
                 if !bytes_is_empty {
+                    if let Some(command) = command_to_type.take() {
+                        let _ = os_input.write_to_tty_stdin(pid, &mut command.into_bytes());
+                    }
                     // for UX reasons, if we got something on the wire, we only send the render notice if:
                     // 1. there aren't any more bytes on the wire afterwards
                     // 2. a certain period (currently 30ms) has elapsed since the last render
@@ -228,13 +310,10 @@ fn stream_terminal_bytes(
             send_screen_instructions
                 .send(ScreenInstruction::Render)
                 .unwrap();
-            #[cfg(not(test))]
-            // this is a little hacky, and is because the tests end the file as soon as
-            // we read everything, rather than hanging until there is new data
-            // a better solution would be to fix the test fakes, but this will do for now
-            send_screen_instructions
-                .send(ScreenInstruction::ClosePane(PaneId::Terminal(pid)))
-                .unwrap();
+            // waiting blocks, so it can't be done on the threads that run the tasks
+            let status = task::spawn_blocking(move || os_input.wait_for_exit(child_pid)).await;
+            let _ =
+                send_pty_instructions.send(PtyInstruction::TerminalExited(pid, child_pid, status));
         }
     })
 }
@@ -242,29 +321,44 @@ fn stream_terminal_bytes(
 impl PtyBus {
     pub fn new(
         receive_pty_instructions: Receiver<(PtyInstruction, ErrorContext)>,
+        send_pty_instructions: SenderWithContext<PtyInstruction>,
         send_screen_instructions: SenderWithContext<ScreenInstruction>,
         send_plugin_instructions: SenderWithContext<PluginInstruction>,
         os_input: Box<dyn OsApi>,
+        events: EventSender,
         debug_to_file: bool,
     ) -> Self {
         PtyBus {
+            send_pty_instructions,
             send_screen_instructions,
             send_plugin_instructions,
             receive_pty_instructions,
             os_input,
+            events,
             id_to_child_pid: HashMap::new(),
             debug_to_file,
             task_handles: HashMap::new(),
+            held_terminal_actions: HashMap::new(),
         }
     }
-    pub fn spawn_terminal(&mut self, file_to_open: Option<PathBuf>) -> RawFd {
+    pub fn spawn_terminal(&mut self, terminal_action: Option<TerminalAction>) -> RawFd {
+        let held_terminal_action = terminal_action
+            .clone()
+            .filter(|action| action.holds_on_exit());
         let (pid_primary, pid_secondary): (RawFd, RawFd) =
-            self.os_input.spawn_terminal(file_to_open);
+            self.os_input.spawn_terminal(terminal_action, None);
+        if let Some(terminal_action) = held_terminal_action {
+            self.held_terminal_actions
+                .insert(pid_primary, terminal_action);
+        }
         let task_handle = stream_terminal_bytes(
             pid_primary,
+            pid_secondary,
+            self.send_pty_instructions.clone(),
             self.send_screen_instructions.clone(),
             self.os_input.clone(),
             self.debug_to_file,
+            None,
         );
         self.task_handles.insert(pid_primary, task_handle);
         self.id_to_child_pid.insert(pid_primary, pid_secondary);
@@ -272,11 +366,19 @@ impl PtyBus {
     }
     pub fn spawn_terminals_for_layout(&mut self, layout: Layout) {
         let total_panes = layout.total_terminal_panes();
+        let mut terminal_panes = layout.terminal_panes().into_iter();
         let mut new_pane_pids = vec![];
+        let mut commands_to_type = vec![];
         for _ in 0..total_panes {
-            let (pid_primary, pid_secondary): (RawFd, RawFd) = self.os_input.spawn_terminal(None);
+            let (cwd, command) = terminal_panes
+                .next()
+                .map(|pane| (pane.cwd.clone(), pane.command.clone()))
+                .unwrap_or_default();
+            let (pid_primary, pid_secondary): (RawFd, RawFd) =
+                self.os_input.spawn_terminal(None, cwd);
             self.id_to_child_pid.insert(pid_primary, pid_secondary);
             new_pane_pids.push(pid_primary);
+            commands_to_type.push(command);
         }
         self.send_screen_instructions
             .send(ScreenInstruction::ApplyLayout((
@@ -284,12 +386,15 @@ impl PtyBus {
                 new_pane_pids.clone(),
             )))
             .unwrap();
-        for id in new_pane_pids {
+        for (id, command_to_type) in new_pane_pids.into_iter().zip(commands_to_type) {
             let task_handle = stream_terminal_bytes(
                 id,
+                self.id_to_child_pid[&id],
+                self.send_pty_instructions.clone(),
                 self.send_screen_instructions.clone(),
                 self.os_input.clone(),
                 self.debug_to_file,
+                command_to_type,
             );
             self.task_handles.insert(id, task_handle);
         }
@@ -297,12 +402,20 @@ impl PtyBus {
     pub fn close_pane(&mut self, id: PaneId) {
         match id {
             PaneId::Terminal(id) => {
-                let child_pid = self.id_to_child_pid.remove(&id).unwrap();
-                let handle = self.task_handles.remove(&id).unwrap();
-                self.os_input.kill(child_pid).unwrap();
-                task::block_on(async {
-                    handle.cancel().await;
-                });
+                self.held_terminal_actions.remove(&id);
+                // the pane may have been closed already, eg. by what ran in it exiting
+                if let Some(child_pid) = self.id_to_child_pid.remove(&id) {
+                    match self.os_input.kill(child_pid) {
+                        // it's already gone
+                        Ok(()) | Err(nix::Error::Sys(nix::errno::Errno::ESRCH)) => {}
+                        Err(e) => panic!("failed to kill the process of terminal {}: {:?}", id, e),
+                    }
+                }
+                if let Some(handle) = self.task_handles.remove(&id) {
+                    task::block_on(async {
+                        handle.cancel().await;
+                    });
+                }
             }
             PaneId::Plugin(pid) => drop(
                 self.send_plugin_instructions
@@ -310,6 +423,36 @@ impl PtyBus {
             ),
         }
     }
+    /// Handles the exit of the process `child_pid` that ran in the terminal `id`: subscribers to
+    /// the session's events are told how it did, and the pane of the terminal is closed, unless
+    /// it's held open. Nothing is done if the pane was closed already, as the process was then
+    /// killed on purpose.
+    pub fn terminal_exited(&mut self, id: RawFd, child_pid: RawFd, status: Option<ExitStatus>) {
+        // the id of a closed terminal can be reused by a new one
+        if self.id_to_child_pid.get(&id) != Some(&child_pid) {
+            return;
+        }
+        let pane = PaneId::Terminal(id);
+        let instruction = match status {
+            Some(status) => {
+                self.events.send(Event::CommandExited { pane, status });
+                if self.held_terminal_actions.contains_key(&id) {
+                    ScreenInstruction::HoldPane(pane, status)
+                } else {
+                    ScreenInstruction::ClosePane(pane)
+                }
+            }
+            None => ScreenInstruction::ClosePane(pane),
+        };
+        self.send_screen_instructions.send(instruction).unwrap();
+    }
+    /// Closes the held terminal `id` and runs what ran in it again in a new terminal, returning
+    /// the new terminal's id, or `None` if `id` isn't held.
+    pub fn rerun_command(&mut self, id: RawFd) -> Option<RawFd> {
+        let terminal_action = self.held_terminal_actions.get(&id)?.clone();
+        self.close_pane(PaneId::Terminal(id));
+        Some(self.spawn_terminal(Some(terminal_action)))
+    }
     pub fn close_tab(&mut self, ids: Vec<PaneId>) {
         ids.iter().for_each(|&id| {
             self.close_pane(id);
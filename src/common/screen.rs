@@ -2,15 +2,22 @@
 
 use std::collections::BTreeMap;
 use std::os::unix::io::RawFd;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{Receiver, Sender};
 
 use super::{AppInstruction, SenderWithContext};
+use crate::cli::SizePolicy;
+use crate::common::events::{Event, EventSender};
+use crate::common::ipc::{ClientId, PaneTarget, ServerToClientMsg, SessionStructure, TabInfo};
+use crate::common::theme::Theme;
 use crate::os_input_output::OsApi;
-use crate::panes::PositionAndSize;
-use crate::pty_bus::{PtyInstruction, VteEvent};
+use crate::panes::{DumpOptions, PositionAndSize};
+use crate::pty_bus::{ExitStatus, PtyInstruction, VteEvent};
 use crate::tab::Tab;
 use crate::{errors::ErrorContext, wasm_vm::PluginInstruction};
-use crate::{layout::Layout, panes::PaneId};
+use crate::{
+    layout::{Layout, SessionLayout},
+    panes::PaneId,
+};
 
 /// Instructions that can be sent to the [`Screen`].
 #[derive(Debug, Clone)]
@@ -36,24 +43,67 @@ pub enum ScreenInstruction {
     ClearScroll,
     CloseFocusedPane,
     ToggleActiveTerminalFullscreen,
+    RenamePane(String),
     SetSelectable(PaneId, bool),
     SetMaxHeight(PaneId, usize),
     SetInvisibleBorders(PaneId, bool),
     ClosePane(PaneId),
+    /// Keeps a terminal pane open now that what ran in it exited, the way it did.
+    HoldPane(PaneId, ExitStatus),
+    /// Puts a new terminal pane in the place of another one, which is closed.
+    ReplacePane(PaneId, PaneId),
     ApplyLayout((Layout, Vec<RawFd>)),
     NewTab(RawFd),
     SwitchTabNext,
     SwitchTabPrev,
     CloseTab,
+    AddClient(ClientId, PositionAndSize),
+    RemoveClient(ClientId),
+    SetActiveClient(ClientId),
+    TerminalResize(ClientId, PositionAndSize),
+    GetTabCount(Sender<usize>),
+    GetSessionLayout(Option<usize>, Sender<SessionLayout>),
+    DumpPane(
+        Option<PaneId>,
+        Option<ClientId>,
+        DumpOptions,
+        Sender<Result<String, String>>,
+    ),
+    GetSessionStructure(Option<ClientId>, Sender<SessionStructure>),
+    SendKeys(
+        Option<PaneTarget>,
+        Option<ClientId>,
+        Vec<Vec<u8>>,
+        Sender<Result<(), String>>,
+    ),
+}
+
+/// The settings a [`Screen`] is created with, which its [`Tab`]s are created with as well.
+#[derive(Clone)]
+pub struct ScreenSettings {
+    /// The full size of the [`Screen`].
+    pub full_screen_ws: PositionAndSize,
+    /// An optional maximal amount of panes allowed per [`Tab`].
+    pub max_panes: Option<usize>,
+    /// Which of the attached clients the [`Screen`] is sized after.
+    pub size_policy: SizePolicy,
+    /// The colors the [`Tab`]s are drawn with.
+    pub theme: Theme,
+    /// Whether what runs in the panes can copy to the clipboard of the clients' terminals.
+    pub allow_clipboard_writes: bool,
 }
 
 /// A [`Screen`] holds multiple [`Tab`]s, each one holding multiple [`panes`](crate::client::panes).
 /// It only directly controls which tab is active, delegating the rest to the individual `Tab`.
+///
+/// Every attached client looks at a [`Tab`] of its own choosing, and the instructions that
+/// act on "the active tab" act on the tab of the current client: the one whose input is
+/// being handled, as set with [`ScreenInstruction::SetActiveClient`].
 pub struct Screen {
     /// A [`ScreenInstruction`] and [`ErrorContext`] receiver.
     pub receiver: Receiver<(ScreenInstruction, ErrorContext)>,
-    /// An optional maximal amount of panes allowed per [`Tab`] in this [`Screen`] instance.
-    max_panes: Option<usize>,
+    /// The settings of this [`Screen`], whose size changes along with its clients.
+    settings: ScreenSettings,
     /// A map between this [`Screen`]'s tabs and their ID/key.
     tabs: BTreeMap<usize, Tab>,
     /// A [`PtyInstruction`] and [`ErrorContext`] sender.
@@ -62,12 +112,18 @@ pub struct Screen {
     pub send_plugin_instructions: SenderWithContext<PluginInstruction>,
     /// An [`AppInstruction`] and [`ErrorContext`] sender.
     pub send_app_instructions: SenderWithContext<AppInstruction>,
-    /// The full size of this [`Screen`].
-    full_screen_ws: PositionAndSize,
     /// The index of this [`Screen`]'s active [`Tab`].
     active_tab_index: Option<usize>,
+    /// The client whose input is being handled, if it's still attached.
+    active_client: Option<ClientId>,
+    /// The clients currently attached to this [`Screen`], with the size of their terminals.
+    client_sizes: BTreeMap<ClientId, PositionAndSize>,
+    /// The index of the [`Tab`] every attached client is looking at.
+    client_tab_indices: BTreeMap<ClientId, usize>,
     /// The [`OsApi`] this [`Screen`] uses.
     os_api: Box<dyn OsApi>,
+    /// Where the [`Event`]s of this [`Screen`] and its tabs are sent.
+    events: EventSender,
 }
 
 impl Screen {
@@ -77,20 +133,23 @@ impl Screen {
         send_pty_instructions: SenderWithContext<PtyInstruction>,
         send_plugin_instructions: SenderWithContext<PluginInstruction>,
         send_app_instructions: SenderWithContext<AppInstruction>,
-        full_screen_ws: &PositionAndSize,
+        settings: ScreenSettings,
         os_api: Box<dyn OsApi>,
-        max_panes: Option<usize>,
+        events: EventSender,
     ) -> Self {
         Screen {
             receiver: receive_screen_instructions,
-            max_panes,
+            settings,
             send_pty_instructions,
             send_plugin_instructions,
             send_app_instructions,
-            full_screen_ws: *full_screen_ws,
             active_tab_index: None,
+            active_client: None,
+            client_sizes: BTreeMap::new(),
+            client_tab_indices: BTreeMap::new(),
             tabs: BTreeMap::new(),
             os_api,
+            events,
         }
     }
 
@@ -98,18 +157,18 @@ impl Screen {
     /// [pane](crate::client::panes) with PTY file descriptor `pane_id`.
     pub fn new_tab(&mut self, pane_id: RawFd) {
         let tab_index = self.get_new_tab_index();
+        self.events.send(Event::TabOpened { tab: tab_index });
         let tab = Tab::new(
             tab_index,
-            &self.full_screen_ws,
+            &self.settings,
             self.os_api.clone(),
             self.send_pty_instructions.clone(),
             self.send_plugin_instructions.clone(),
-            self.send_app_instructions.clone(),
-            self.max_panes,
+            self.events.clone(),
             Some(PaneId::Terminal(pane_id)),
         );
-        self.active_tab_index = Some(tab_index);
         self.tabs.insert(tab_index, tab);
+        self.set_active_tab(tab_index);
         self.render();
     }
 
@@ -131,9 +190,9 @@ impl Screen {
         let first_tab = tab_ids.get(0).unwrap();
         let active_tab_id_position = tab_ids.iter().position(|id| id == &active_tab_id).unwrap();
         if let Some(next_tab) = tab_ids.get(active_tab_id_position + 1) {
-            self.active_tab_index = Some(*next_tab);
+            self.set_active_tab(*next_tab);
         } else {
-            self.active_tab_index = Some(*first_tab);
+            self.set_active_tab(*first_tab);
         }
         self.render();
     }
@@ -141,28 +200,173 @@ impl Screen {
     /// Sets this [`Screen`]'s active [`Tab`] to the previous tab.
     pub fn switch_tab_prev(&mut self) {
         let active_tab_id = self.get_active_tab().unwrap().index;
-        let tab_ids: Vec<usize> = self.tabs.keys().copied().collect();
-        let first_tab = tab_ids.get(0).unwrap();
-        let last_tab = tab_ids.last().unwrap();
+        let previous_tab = self.get_previous_tab_index(active_tab_id);
+        self.set_active_tab(previous_tab);
+        self.render();
+    }
 
-        let active_tab_id_position = tab_ids.iter().position(|id| id == &active_tab_id).unwrap();
-        if active_tab_id == *first_tab {
-            self.active_tab_index = Some(*last_tab)
-        } else if let Some(prev_tab) = tab_ids.get(active_tab_id_position - 1) {
-            self.active_tab_index = Some(*prev_tab)
+    /// Returns the index of the [`Tab`] before the one with index `tab_index`, wrapping around
+    /// to the last tab.
+    fn get_previous_tab_index(&self, tab_index: usize) -> usize {
+        self.tabs
+            .range(..tab_index)
+            .next_back()
+            .or_else(|| self.tabs.iter().next_back())
+            .map(|(index, _)| *index)
+            .unwrap()
+    }
+
+    /// Makes the [`Tab`] with index `tab_index` the active one, moving the current client over
+    /// to it. The other clients stay on the tab they're looking at.
+    fn set_active_tab(&mut self, tab_index: usize) {
+        self.active_tab_index = Some(tab_index);
+        if let Some(client_id) = self.active_client {
+            self.move_client_to_tab(client_id, tab_index);
+        }
+    }
+
+    /// Shows the [`Tab`] with index `tab_index` to the client with id `client_id`.
+    fn move_client_to_tab(&mut self, client_id: ClientId, tab_index: usize) {
+        let previous_tab_index = self.client_tab_indices.insert(client_id, tab_index);
+        if let Some(previous_tab_index) = previous_tab_index {
+            if let Some(previous_tab) = self.tabs.get_mut(&previous_tab_index) {
+                previous_tab.disconnect_client(client_id);
+            }
+        }
+        if previous_tab_index != Some(tab_index) {
+            self.events.send(Event::TabSwitched {
+                client: client_id,
+                tab: tab_index,
+            });
+        }
+        let client_size = self.client_sizes[&client_id];
+        if let Some(tab) = self.tabs.get_mut(&tab_index) {
+            tab.connect_client(client_id, client_size);
+        }
+    }
+
+    /// Attaches the client with id `client_id`, whose terminal is of size `client_size`, to
+    /// this [`Screen`], showing it the active [`Tab`].
+    pub fn add_client(&mut self, client_id: ClientId, client_size: PositionAndSize) {
+        self.client_sizes.insert(client_id, client_size);
+        // the new client is the one the user is at
+        self.active_client = Some(client_id);
+        if let Some(tab_index) = self.active_tab_index {
+            self.move_client_to_tab(client_id, tab_index);
         }
+        self.resize_to_clients();
         self.render();
     }
 
+    /// Detaches the client with id `client_id` from this [`Screen`].
+    pub fn remove_client(&mut self, client_id: ClientId) {
+        self.client_sizes.remove(&client_id);
+        if let Some(tab_index) = self.client_tab_indices.remove(&client_id) {
+            if let Some(tab) = self.tabs.get_mut(&tab_index) {
+                tab.disconnect_client(client_id);
+            }
+        }
+        if self.active_client == Some(client_id) {
+            self.active_client = None;
+        }
+        self.resize_to_clients();
+        // the clients that are left need to know that one of them is gone
+        self.render();
+    }
+
+    /// Handles the terminal of the client with id `client_id` being resized to `new_size`,
+    /// resizing this [`Screen`] along with it if needed, and redrawing that client's terminal
+    /// from scratch.
+    pub fn resize_client(&mut self, client_id: ClientId, new_size: PositionAndSize) {
+        if !self.client_sizes.contains_key(&client_id) {
+            return;
+        }
+        self.client_sizes.insert(client_id, new_size);
+        let tab_index = self.client_tab_indices.get(&client_id).copied();
+        if let Some(tab) = tab_index.and_then(|tab_index| self.tabs.get_mut(&tab_index)) {
+            tab.connect_client(client_id, new_size);
+            tab.set_force_render();
+        }
+        // whatever the terminal emulator did with the old content is of no use
+        let clear_screen = "\u{1b}[2J".to_string();
+        self.os_api
+            .send_to_client(client_id, ServerToClientMsg::Render(clear_screen));
+        self.resize_to_clients();
+        self.render();
+    }
+
+    /// Makes the client with id `client_id` the current one, so that the following
+    /// instructions act on the [`Tab`] it's looking at.
+    pub fn set_active_client(&mut self, client_id: ClientId) {
+        if self.active_client == Some(client_id) || !self.client_sizes.contains_key(&client_id) {
+            return;
+        }
+        self.active_client = Some(client_id);
+        if let Some(tab_index) = self.client_tab_indices.get(&client_id) {
+            self.active_tab_index = Some(*tab_index);
+        }
+        if self.settings.size_policy == SizePolicy::Latest {
+            self.resize_to_clients();
+            self.render();
+        }
+    }
+
+    /// Resizes this [`Screen`] and all of its tabs according to its [`SizePolicy`] and the
+    /// size of the terminals of its clients. Nothing changes if no client is attached.
+    fn resize_to_clients(&mut self) {
+        let latest_client_size = self
+            .active_client
+            .and_then(|client_id| self.client_sizes.get(&client_id));
+        let mut client_sizes = self.client_sizes.values().copied();
+        let new_size = match (self.settings.size_policy, latest_client_size) {
+            (SizePolicy::Latest, Some(latest_client_size)) => Some(*latest_client_size),
+            _ => client_sizes.next().map(|first_size| {
+                client_sizes.fold(first_size, |smallest, size| PositionAndSize {
+                    rows: smallest.rows.min(size.rows),
+                    columns: smallest.columns.min(size.columns),
+                    ..smallest
+                })
+            }),
+        };
+        if let Some(new_size) = new_size {
+            if new_size != self.settings.full_screen_ws {
+                self.settings.full_screen_ws = new_size;
+                for tab in self.tabs.values_mut() {
+                    tab.resize_whole_tab(new_size);
+                }
+            }
+        }
+    }
+
     /// Closes this [`Screen`]'s active [`Tab`], exiting the application if it happens
     /// to be the last tab.
     pub fn close_tab(&mut self) {
         let active_tab_index = self.active_tab_index.unwrap();
+        self.close_tab_at(active_tab_index);
+    }
+
+    /// Closes the [`Tab`] with index `tab_index`, moving the clients looking at it to the
+    /// previous tab, and exiting the application if it happens to be the last tab.
+    fn close_tab_at(&mut self, tab_index: usize) {
         if self.tabs.len() > 1 {
-            self.switch_tab_prev();
+            let previous_tab_index = self.get_previous_tab_index(tab_index);
+            let clients_on_tab: Vec<ClientId> = self
+                .client_tab_indices
+                .iter()
+                .filter(|(_, index)| **index == tab_index)
+                .map(|(client_id, _)| *client_id)
+                .collect();
+            for client_id in clients_on_tab {
+                self.move_client_to_tab(client_id, previous_tab_index);
+            }
+            if self.active_tab_index == Some(tab_index) {
+                self.active_tab_index = Some(previous_tab_index);
+            }
+            self.render();
         }
-        let active_tab = self.tabs.remove(&active_tab_index).unwrap();
-        let pane_ids = active_tab.get_pane_ids();
+        let closed_tab = self.tabs.remove(&tab_index).unwrap();
+        self.events.send(Event::TabClosed { tab: tab_index });
+        let pane_ids = closed_tab.get_pane_ids();
         // below we don't check the result of sending the CloseTab instruction to the pty thread
         // because this might be happening when the app is closing, at which point the pty thread
         // has already closed and this would result in an error
@@ -177,15 +381,55 @@ impl Screen {
         }
     }
 
-    /// Renders this [`Screen`], which amounts to rendering its active [`Tab`].
+    /// Renders this [`Screen`], which amounts to rendering every [`Tab`] a client is looking at.
     pub fn render(&mut self) {
-        if let Some(active_tab) = self.get_active_tab_mut() {
-            if active_tab.get_active_pane().is_some() {
-                active_tab.render();
-            } else {
-                self.close_tab();
+        match self.get_active_tab() {
+            Some(active_tab) if active_tab.get_active_pane().is_none() => self.close_tab(),
+            _ => {
+                for tab in self.tabs.values_mut() {
+                    tab.render();
+                }
             }
-        };
+        }
+    }
+
+    /// Passes `event`, the output of the terminal with PTY file descriptor `pid`, to the
+    /// [`Tab`] that terminal is in.
+    pub fn handle_pty_event(&mut self, pid: RawFd, event: VteEvent) {
+        if let Some(tab) = self.get_tab_with_pane_mut(PaneId::Terminal(pid)) {
+            tab.handle_pty_event(pid, event);
+        }
+    }
+
+    /// Closes the pane with id `id`, in whichever [`Tab`] it is.
+    pub fn close_pane(&mut self, id: PaneId) {
+        if let Some(tab) = self.get_tab_with_pane_mut(id) {
+            tab.close_pane(id);
+            let tab_index = tab.index;
+            if tab.get_active_pane().is_none() && self.active_tab_index != Some(tab_index) {
+                // the active tab is closed when it's rendered
+                self.close_tab_at(tab_index);
+            }
+        }
+        self.render();
+    }
+
+    /// Keeps the pane with id `id` open, in whichever [`Tab`] it is, telling its users that what
+    /// ran in it exited with `status`.
+    pub fn hold_pane(&mut self, id: PaneId, status: ExitStatus) {
+        if let Some(tab) = self.get_tab_with_pane_mut(id) {
+            tab.hold_pane(id, status);
+        }
+        self.render();
+    }
+
+    /// Puts the new pane `new_id` in the place of the pane with id `id`, in whichever [`Tab`] it
+    /// is.
+    pub fn replace_pane(&mut self, id: PaneId, new_id: PaneId) {
+        if let Some(tab) = self.get_tab_with_pane_mut(id) {
+            tab.replace_pane(id, new_id);
+        }
+        self.render();
     }
 
     /// Returns a mutable reference to this [`Screen`]'s tabs.
@@ -193,6 +437,160 @@ impl Screen {
         &mut self.tabs
     }
 
+    /// Returns a [`SessionLayout`] that recreates all of this [`Screen`]'s tabs, along with the
+    /// last `scrollback_lines` lines of each of their terminals if given.
+    pub fn session_layout(&self, scrollback_lines: Option<usize>) -> SessionLayout {
+        SessionLayout {
+            tabs: self
+                .tabs
+                .values()
+                .filter_map(|tab| tab.layout(scrollback_lines))
+                .collect(),
+        }
+    }
+
+    /// Returns the tabs of this [`Screen`] and their panes, marking the one the client with id
+    /// `client_id` is looking at as active.
+    pub fn session_structure(&self, client_id: Option<ClientId>) -> SessionStructure {
+        let active_tab_index = self.client_tab_index(client_id);
+        SessionStructure {
+            tabs: self
+                .tabs
+                .values()
+                .map(|tab| TabInfo {
+                    index: tab.index,
+                    active: active_tab_index == Some(tab.index),
+                    panes: tab.pane_infos(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the index of the [`Tab`] the client with id `client_id` is looking at, or else of
+    /// the active one.
+    fn client_tab_index(&self, client_id: Option<ClientId>) -> Option<usize> {
+        client_id
+            .and_then(|client_id| self.client_tab_indices.get(&client_id))
+            .or(self.active_tab_index.as_ref())
+            .copied()
+    }
+
+    /// Returns the contents of the pane with id `pane_id`, or else of the focused pane of the
+    /// [`Tab`] the client with id `client_id` is looking at, as asked for in `options`.
+    pub fn dump_pane(
+        &self,
+        pane_id: Option<PaneId>,
+        client_id: Option<ClientId>,
+        options: DumpOptions,
+    ) -> Result<String, String> {
+        let pane = match pane_id {
+            Some(pane_id) => self
+                .tabs
+                .values()
+                .find_map(|tab| tab.get_pane(pane_id))
+                .ok_or_else(|| match pane_id {
+                    PaneId::Terminal(id) => format!("there is no terminal pane with id {}", id),
+                    PaneId::Plugin(id) => format!("there is no plugin pane with id {}", id),
+                })?,
+            None => self
+                .client_tab_index(client_id)
+                .and_then(|tab_index| self.tabs.get(&tab_index))
+                .and_then(|tab| tab.get_active_pane())
+                .ok_or_else(|| "there is no focused pane".to_string())?,
+        };
+        pane.dump(options)
+            .ok_or_else(|| "only the contents of terminal panes can be dumped".to_string())
+    }
+
+    /// Writes each of the chunks of `input` to the pane `pane_target`, or else to the focused pane
+    /// of the [`Tab`] the client with id `client_id` is looking at.
+    pub fn send_keys(
+        &mut self,
+        pane_target: Option<PaneTarget>,
+        client_id: Option<ClientId>,
+        input: Vec<Vec<u8>>,
+    ) -> Result<(), String> {
+        let (tab_index, pane_id) = match pane_target {
+            Some(PaneTarget::Id(pane_id)) => self
+                .tabs
+                .values()
+                .find(|tab| tab.has_pane(pane_id))
+                .map(|tab| (tab.index, pane_id))
+                .ok_or_else(|| match pane_id {
+                    PaneId::Terminal(id) => format!("there is no terminal pane with id {}", id),
+                    PaneId::Plugin(id) => format!("there is no plugin pane with id {}", id),
+                })?,
+            Some(PaneTarget::Index { tab, pane }) => {
+                let tab_index = tab
+                    .or_else(|| self.client_tab_index(client_id))
+                    .ok_or_else(|| "there is no tab".to_string())?;
+                let tab = self
+                    .tabs
+                    .get(&tab_index)
+                    .ok_or_else(|| format!("there is no tab with index {}", tab_index))?;
+                let pane_id = tab.get_pane_ids().get(pane).copied().ok_or_else(|| {
+                    format!("the tab with index {} has no pane {}", tab_index, pane)
+                })?;
+                (tab_index, pane_id)
+            }
+            None => self
+                .client_tab_index(client_id)
+                .and_then(|tab_index| {
+                    let active_pane = self.tabs.get(&tab_index)?.get_active_pane()?;
+                    Some((tab_index, active_pane.pid()))
+                })
+                .ok_or_else(|| "there is no focused pane".to_string())?,
+        };
+        for input_bytes in input {
+            self.write_to_pane(tab_index, pane_id, input_bytes);
+        }
+        Ok(())
+    }
+
+    /// Writes `input_bytes` to the focused pane of the active [`Tab`].
+    pub fn write_to_active_terminal(&mut self, input_bytes: Vec<u8>) {
+        let active_tab = match self.get_active_tab() {
+            Some(active_tab) => active_tab,
+            None => return,
+        };
+        if let Some(active_pane) = active_tab.get_active_pane() {
+            let (tab_index, pane_id) = (active_tab.index, active_pane.pid());
+            self.write_to_pane(tab_index, pane_id, input_bytes);
+        }
+    }
+
+    /// Writes `input_bytes` to the pane with id `pane_id` of the [`Tab`] with index `tab_index`.
+    /// Held panes only take Enter, to run their command again, and `x`, to close them.
+    fn write_to_pane(&mut self, tab_index: usize, pane_id: PaneId, input_bytes: Vec<u8>) {
+        let tab = match self.tabs.get_mut(&tab_index) {
+            Some(tab) => tab,
+            None => return,
+        };
+        if !tab.pane_is_held(pane_id) {
+            tab.write_to_pane(pane_id, input_bytes);
+            return;
+        }
+        match (input_bytes.as_slice(), pane_id) {
+            (b"\r", PaneId::Terminal(id)) | (b"\n", PaneId::Terminal(id)) => {
+                self.send_pty_instructions
+                    .send(PtyInstruction::RerunCommand(id))
+                    .unwrap();
+            }
+            (b"x", _) => {
+                self.close_pane(pane_id);
+                self.send_pty_instructions
+                    .send(PtyInstruction::ClosePane(pane_id))
+                    .unwrap();
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns a mutable reference to the [`Tab`] holding the pane with id `pane_id`.
+    fn get_tab_with_pane_mut(&mut self, pane_id: PaneId) -> Option<&mut Tab> {
+        self.tabs.values_mut().find(|tab| tab.has_pane(pane_id))
+    }
+
     /// Returns an immutable reference to this [`Screen`]'s active [`Tab`].
     pub fn get_active_tab(&self) -> Option<&Tab> {
         match self.active_tab_index {
@@ -213,18 +611,20 @@ impl Screen {
     /// and switching to it.
     pub fn apply_layout(&mut self, layout: Layout, new_pids: Vec<RawFd>) {
         let tab_index = self.get_new_tab_index();
-        let mut tab = Tab::new(
+        self.events.send(Event::TabOpened { tab: tab_index });
+        let tab = Tab::new(
             tab_index,
-            &self.full_screen_ws,
+            &self.settings,
             self.os_api.clone(),
             self.send_pty_instructions.clone(),
             self.send_plugin_instructions.clone(),
-            self.send_app_instructions.clone(),
-            self.max_panes,
+            self.events.clone(),
             None,
         );
-        tab.apply_layout(layout, new_pids);
-        self.active_tab_index = Some(tab_index);
         self.tabs.insert(tab_index, tab);
+        self.set_active_tab(tab_index);
+        self.get_active_tab_mut()
+            .unwrap()
+            .apply_layout(layout, new_pids);
     }
 }
@@ -0,0 +1,220 @@
+//! The look of zellij's own UI (pane borders, and the status bar through its plugin), from the
+//! `theme` section of the config file.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize};
+
+use crate::common::input::handler::InputMode;
+use crate::panes::{AnsiCode, CharacterStyles, NamedColor};
+
+/// The `theme` section of the config file, eg.
+///
+/// ```yaml
+/// theme:
+///   fg: white
+///   bg: black
+///   accent: magenta
+///   focused_border: green
+///   inactive_border: "#606060"
+///   border_style: rounded
+///   modes:
+///     normal: green
+///     git: 202
+/// ```
+///
+/// It's also what plugins get from `get_theme`, as JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub fg: PaletteColor,
+    pub bg: PaletteColor,
+    /// What stands out, like the current input mode in the status bar.
+    pub accent: PaletteColor,
+    /// The border of the focused pane.
+    pub focused_border: PaletteColor,
+    /// The borders of the other panes.
+    pub inactive_border: PaletteColor,
+    /// The lines the borders are drawn with.
+    pub border_style: BorderStyle,
+    /// The color of each input mode in the status bar, for the modes that don't use `accent`.
+    pub modes: HashMap<InputMode, PaletteColor>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        let mut modes = HashMap::new();
+        modes.insert(InputMode::Normal, PaletteColor::Index(2));
+        Theme {
+            fg: PaletteColor::Index(7),
+            bg: PaletteColor::Index(0),
+            accent: PaletteColor::Index(5),
+            focused_border: PaletteColor::Index(2),
+            inactive_border: PaletteColor::Default,
+            border_style: BorderStyle::Single,
+            modes,
+        }
+    }
+}
+
+/// The set of glyphs pane borders are drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderStyle {
+    /// `┌─┐`
+    Single,
+    /// `╭─╮`
+    Rounded,
+    /// `╔═╗`
+    Double,
+    /// `┏━┓`
+    Heavy,
+    /// `+-+`, for fonts without box-drawing characters.
+    Ascii,
+    /// Borders drawn with spaces: the panes are laid out as with the other styles, so the rows
+    /// and columns of their borders are still taken, but they're left blank.
+    Blank,
+}
+
+/// A color of the [`Theme`]. It's given in the config file by its name (eg. `magenta`, or
+/// `default` for the terminal's own), by its index among the 256 colors of the terminal (eg.
+/// `208`) or as RGB (eg. `"#ff8700"`), but it's sent to plugins as the variant it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PaletteColor {
+    /// The default foreground or background color of the terminal.
+    Default,
+    /// One of the 256 indexed colors of the terminal, the first 8 of which are black, red,
+    /// green, yellow, blue, magenta, cyan and white.
+    Index(u8),
+    Rgb(u8, u8, u8),
+}
+
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+impl PaletteColor {
+    /// Returns the styles that draw characters in this color.
+    pub fn foreground_styles(self) -> CharacterStyles {
+        CharacterStyles::new().foreground(Some(self.into()))
+    }
+
+    /// Returns the RGB components of this color, as xterm draws the indexed colors, or `None`
+    /// for the default color of the terminal.
+    pub fn rgb(self) -> Option<(u8, u8, u8)> {
+        const BASE_COLORS: [(u8, u8, u8); 16] = [
+            (0x00, 0x00, 0x00),
+            (0xcd, 0x00, 0x00),
+            (0x00, 0xcd, 0x00),
+            (0xcd, 0xcd, 0x00),
+            (0x00, 0x00, 0xee),
+            (0xcd, 0x00, 0xcd),
+            (0x00, 0xcd, 0xcd),
+            (0xe5, 0xe5, 0xe5),
+            (0x7f, 0x7f, 0x7f),
+            (0xff, 0x00, 0x00),
+            (0x00, 0xff, 0x00),
+            (0xff, 0xff, 0x00),
+            (0x5c, 0x5c, 0xff),
+            (0xff, 0x00, 0xff),
+            (0x00, 0xff, 0xff),
+            (0xff, 0xff, 0xff),
+        ];
+        match self {
+            PaletteColor::Default => None,
+            PaletteColor::Index(index) if index < 16 => Some(BASE_COLORS[index as usize]),
+            // the 6x6x6 color cube
+            PaletteColor::Index(index) if index < 232 => {
+                let level = |i: u8| if i == 0 { 0 } else { 55 + i * 40 };
+                let index = index - 16;
+                Some((level(index / 36), level(index / 6 % 6), level(index % 6)))
+            }
+            // the shades of gray
+            PaletteColor::Index(index) => {
+                let gray = 8 + (index - 232) * 10;
+                Some((gray, gray, gray))
+            }
+            PaletteColor::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+
+    fn parse(s: &str) -> Result<PaletteColor, String> {
+        if s == "default" {
+            return Ok(PaletteColor::Default);
+        }
+        if let Some(index) = COLOR_NAMES.iter().position(|name| *name == s) {
+            return Ok(PaletteColor::Index(index as u8));
+        }
+        if let Ok(index) = s.parse() {
+            return Ok(PaletteColor::Index(index));
+        }
+        let rgb = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .and_then(|hex| {
+                let component = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+                Some(PaletteColor::Rgb(
+                    component(0)?,
+                    component(2)?,
+                    component(4)?,
+                ))
+            });
+        rgb.ok_or_else(|| {
+            format!(
+                "invalid color \"{}\": expected the name of a color, a number from 0 to 255 or \
+                 \"#rrggbb\"",
+                s
+            )
+        })
+    }
+}
+
+impl From<PaletteColor> for AnsiCode {
+    fn from(color: PaletteColor) -> AnsiCode {
+        const NAMED_COLORS: [NamedColor; 8] = [
+            NamedColor::Black,
+            NamedColor::Red,
+            NamedColor::Green,
+            NamedColor::Yellow,
+            NamedColor::Blue,
+            NamedColor::Magenta,
+            NamedColor::Cyan,
+            NamedColor::White,
+        ];
+        match color {
+            PaletteColor::Default => AnsiCode::Reset,
+            PaletteColor::Index(index) if (index as usize) < NAMED_COLORS.len() => {
+                AnsiCode::NamedColor(NAMED_COLORS[index as usize])
+            }
+            PaletteColor::Index(index) => AnsiCode::ColorIndex(index),
+            PaletteColor::Rgb(r, g, b) => AnsiCode::RGBCode((r, g, b)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PaletteColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PaletteColorVisitor;
+
+        impl<'de> Visitor<'de> for PaletteColorVisitor {
+            type Value = PaletteColor;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a color")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<PaletteColor, E> {
+                PaletteColor::parse(s).map_err(E::custom)
+            }
+
+            // unquoted numbers are numbers in YAML
+            fn visit_u64<E: de::Error>(self, n: u64) -> Result<PaletteColor, E> {
+                PaletteColor::parse(&n.to_string()).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(PaletteColorVisitor)
+    }
+}
@@ -1,15 +1,28 @@
 use std::{
     path::PathBuf,
-    sync::mpsc::{channel, Sender},
+    sync::mpsc::{channel, Sender, TrySendError},
+    thread,
+    time::{Duration, Instant},
 };
 use wasmer::{imports, Function, ImportObject, Store, WasmerEnv};
 use wasmer_wasi::WasiEnv;
 // use crate::utils::logging::debug_log_to_file;
 
 use super::{
-    input::handler::get_help, pty_bus::PtyInstruction, screen::ScreenInstruction, AppInstruction,
-    PaneId, SenderWithContext,
+    input::handler::{get_help, Help},
+    input::keybinds::KeybindsHelp,
+    input::keys::key_name,
+    ipc::ClientType,
+    pty_bus::{PtyInstruction, TerminalAction},
+    screen::ScreenInstruction,
+    theme::Theme,
+    AppInstruction, SenderWithContext,
 };
+use crate::panes::PaneId;
+
+/// How long a plugin keeps asking for the state of the app while the app is busy with something
+/// else, before it does without (eg. because the app is shutting down).
+const GET_STATE_TIMEOUT: Duration = Duration::from_millis(50);
 
 #[derive(Clone, Debug)]
 pub enum PluginInstruction {
@@ -27,6 +40,10 @@ pub struct PluginEnv {
     pub send_screen_instructions: SenderWithContext<ScreenInstruction>,
     pub send_app_instructions: SenderWithContext<AppInstruction>,
     pub send_pty_instructions: SenderWithContext<PtyInstruction>, // FIXME: This should be a big bundle of all of the channels
+    /// The help texts given to keybinds in the config file, shown along with the default ones
+    pub keybinds_help: KeybindsHelp,
+    /// The colors of the UI, for plugins to draw with
+    pub theme: Theme,
     pub wasi_env: WasiEnv,
 }
 
@@ -40,6 +57,7 @@ pub fn zellij_imports(store: &Store, plugin_env: &PluginEnv) -> ImportObject {
             "host_set_max_height" => Function::new_native_with_env(store, plugin_env.clone(), host_set_max_height),
             "host_set_selectable" => Function::new_native_with_env(store, plugin_env.clone(), host_set_selectable),
             "host_get_help" => Function::new_native_with_env(store, plugin_env.clone(), host_get_help),
+            "host_get_theme" => Function::new_native_with_env(store, plugin_env.clone(), host_get_theme),
         }
     }
 }
@@ -49,7 +67,9 @@ fn host_open_file(plugin_env: &PluginEnv) {
     let path = PathBuf::from(wasi_stdout(&plugin_env.wasi_env).lines().next().unwrap());
     plugin_env
         .send_pty_instructions
-        .send(PtyInstruction::SpawnTerminal(Some(path)))
+        .send(PtyInstruction::SpawnTerminal(Some(
+            TerminalAction::OpenFile(path),
+        )))
         .unwrap();
 }
 
@@ -91,17 +111,45 @@ fn host_get_help(plugin_env: &PluginEnv) {
     let (state_tx, state_rx) = channel();
     // FIXME: If I changed the application so that threads were sent the termination
     // signal and joined one at a time, there would be an order to shutdown, so I
-    // could get rid of this .is_ok() check and the .try_send()
-    if plugin_env
-        .send_app_instructions
-        .try_send(AppInstruction::GetState(state_tx))
-        .is_ok()
-    {
-        let help = get_help(state_rx.recv().unwrap().input_mode);
+    // could get rid of this timeout and the .try_send()
+    let give_up_at = Instant::now() + GET_STATE_TIMEOUT;
+    let state_requested = loop {
+        match plugin_env
+            .send_app_instructions
+            .try_send(AppInstruction::GetState(state_tx.clone()))
+        {
+            Ok(()) => break true,
+            // the app only takes the request once it's done with the one before
+            Err(TrySendError::Full(_)) if Instant::now() < give_up_at => {
+                thread::sleep(Duration::from_millis(1))
+            }
+            Err(_) => break false,
+        }
+    };
+    if state_requested {
+        let state = state_rx.recv().unwrap();
+        let readers = state
+            .clients
+            .values()
+            .filter(|client_type| **client_type == ClientType::Reader)
+            .count();
+        let help = Help {
+            pending_keys: state.pending_keys.into_iter().map(key_name).collect(),
+            readers,
+            writers: state.clients.len() - readers,
+            ..get_help(&state.input_mode, &plugin_env.keybinds_help)
+        };
         wasi_write_string(&plugin_env.wasi_env, &serde_json::to_string(&help).unwrap());
     }
 }
 
+fn host_get_theme(plugin_env: &PluginEnv) {
+    wasi_write_string(
+        &plugin_env.wasi_env,
+        &serde_json::to_string(&plugin_env.theme).unwrap(),
+    );
+}
+
 // Helper Functions ---------------------------------------------------------------------------------------------------
 
 // FIXME: Unwrap city
@@ -3,27 +3,42 @@ mod tests;
 
 mod cli;
 mod common;
-// TODO mod server;
+mod server;
 mod client;
 
-use client::{boundaries, layout, panes, tab};
+use client::{boundaries, layout, panes, start_client, tab};
 use common::{
-    command_is_executing, errors, os_input_output, pty_bus, screen, start, utils, wasm_vm,
+    command_is_executing, errors, ipc, os_input_output, pty_bus, screen, start, utils, wasm_vm,
     ApiCommand,
 };
 use directories_next::ProjectDirs;
 
+use std::convert::TryFrom;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::io::AsRawFd;
 use std::os::unix::net::UnixStream;
-use std::{fs, io::Write};
+use std::path::{Path, PathBuf};
 
 use structopt::StructOpt;
 
-use crate::cli::CliArgs;
+use crate::cli::{CliArgs, Command};
 use crate::command_is_executing::CommandIsExecuting;
-use crate::os_input_output::get_os_input;
-use crate::pty_bus::VteEvent;
+use crate::common::config::Config;
+use crate::common::events::Event;
+use crate::common::input::actions::Action;
+use crate::common::input::keys::{key_to_bytes, parse_key};
+use crate::ipc::{
+    live_session_names, recv_ipc_msg, send_ipc_msg, session_is_alive, session_names_in,
+    session_socket_path, validate_session_name, ClientToServerMsg, PaneTarget, Response,
+    ResponseData, ServerToClientMsg, Session,
+};
+use crate::layout::{default_sessions_dir, saved_session_name, SessionLayout};
+use crate::os_input_output::{get_os_input, OsApi};
+use crate::panes::{DumpOptions, PaneId};
+use crate::pty_bus::{ExitStatus, RunCommand, VteEvent};
 use crate::utils::{
-    consts::{ZELLIJ_IPC_PIPE, ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR},
+    consts::{ZELLIJ_SESSION_NAME_ENV, ZELLIJ_SOCK_DIR, ZELLIJ_TMP_DIR, ZELLIJ_TMP_LOG_DIR},
     logging::*,
 };
 
@@ -46,33 +61,547 @@ pub fn main() {
         }
     }
 
-    let opts = CliArgs::from_args();
-    if let Some(split_dir) = opts.split {
-        match split_dir {
-            'h' => {
-                let mut stream = UnixStream::connect(ZELLIJ_IPC_PIPE).unwrap();
-                let api_command = bincode::serialize(&ApiCommand::SplitHorizontally).unwrap();
-                stream.write_all(&api_command).unwrap();
+    let mut opts = CliArgs::from_args();
+    opts.attach |= opts.read_only;
+    if let Some(session_name) = &opts.session {
+        if let Err(e) = validate_session_name(session_name) {
+            exit_with_error(&e);
+        }
+    }
+    if let Some(command) = opts.command.clone() {
+        match command {
+            Command::ListSessions => list_sessions(),
+            Command::KillSession { name } => kill_session(&name),
+            Command::Action(cli_action) => match Action::try_from(cli_action) {
+                Ok(action) => {
+                    send_to_session(&opts, ClientToServerMsg::Action(action));
+                }
+                Err(e) => exit_with_error(&e),
+            },
+            Command::DumpScreen {
+                pane_id,
+                full,
+                ansi,
+                join_wrapped,
+            } => {
+                let options = DumpOptions {
+                    full,
+                    ansi,
+                    join_wrapped,
+                };
+                let msg = ClientToServerMsg::DumpScreen(pane_id.map(PaneId::Terminal), options);
+                if let Some(ResponseData::PaneContents(contents)) = send_to_session(&opts, msg) {
+                    print!("{}", contents);
+                }
             }
-            'v' => {
-                let mut stream = UnixStream::connect(ZELLIJ_IPC_PIPE).unwrap();
-                let api_command = bincode::serialize(&ApiCommand::SplitVertically).unwrap();
-                stream.write_all(&api_command).unwrap();
+            Command::Run {
+                direction,
+                cwd,
+                env,
+                hold,
+                wait,
+                command,
+            } => {
+                // relative to where we are, not to where the server was started
+                let cwd = match std::env::current_dir() {
+                    Ok(current_dir) => current_dir.join(cwd.unwrap_or_default()),
+                    Err(e) => {
+                        exit_with_error(&format!("Could not find the current directory: {}", e))
+                    }
+                };
+                if !cwd.is_dir() {
+                    exit_with_error(&format!("{:?} is not a directory.", cwd));
+                }
+                let mut command = command.into_iter();
+                let run_command = RunCommand {
+                    command: PathBuf::from(command.next().unwrap()),
+                    args: command.collect(),
+                    cwd: Some(cwd),
+                    env,
+                    hold_on_exit: hold,
+                };
+                let msg = ClientToServerMsg::Run(run_command, direction.map(Into::into));
+                if wait {
+                    let exit_status = run_and_wait(&opts, msg);
+                    std::process::exit(match exit_status {
+                        ExitStatus::Code(code) => code,
+                        // the way shells report it
+                        ExitStatus::Signal(signal) => 128 + signal,
+                    });
+                }
+                send_to_session(&opts, msg);
             }
+            Command::SendKeys {
+                pane_id,
+                pane_index,
+                tab,
+                literal,
+                bytes,
+                keys,
+            } => {
+                let pane_target = match (pane_id, pane_index) {
+                    (Some(pane_id), _) => Some(PaneTarget::Id(PaneId::Terminal(pane_id))),
+                    (None, Some(pane)) => Some(PaneTarget::Index { tab, pane }),
+                    (None, None) => None,
+                };
+                let input = match keys_to_input(keys, literal, bytes) {
+                    Ok(input) => input,
+                    Err(e) => exit_with_error(&e),
+                };
+                send_to_session(&opts, ClientToServerMsg::SendKeys(pane_target, input));
+            }
+            Command::Subscribe => subscribe(&opts),
+            Command::Query => {
+                if let Some(ResponseData::SessionStructure(structure)) =
+                    send_to_session(&opts, ClientToServerMsg::QuerySession)
+                {
+                    println!("{}", serde_json::to_string_pretty(&structure).unwrap());
+                }
+            }
+        }
+    } else if let Some(split_dir) = opts.split {
+        match split_dir {
+            'h' => send_command_to_session(&opts, ApiCommand::SplitHorizontally),
+            'v' => send_command_to_session(&opts, ApiCommand::SplitVertically),
             _ => {}
         };
     } else if opts.move_focus {
-        let mut stream = UnixStream::connect(ZELLIJ_IPC_PIPE).unwrap();
-        let api_command = bincode::serialize(&ApiCommand::MoveFocus).unwrap();
-        stream.write_all(&api_command).unwrap();
-    } else if let Some(file_to_open) = opts.open_file {
-        let mut stream = UnixStream::connect(ZELLIJ_IPC_PIPE).unwrap();
-        let api_command = bincode::serialize(&ApiCommand::OpenFile(file_to_open)).unwrap();
-        stream.write_all(&api_command).unwrap();
+        send_command_to_session(&opts, ApiCommand::MoveFocus);
+    } else if let Some(file_to_open) = opts.open_file.clone() {
+        send_command_to_session(&opts, ApiCommand::OpenFile(file_to_open));
     } else {
-        let os_input = get_os_input();
+        if let Some(saved_session) = opts.resurrect.clone() {
+            name_resurrected_session(&mut opts, &saved_session);
+        }
+        let mut os_input = get_os_input();
         atomic_create_dir(ZELLIJ_TMP_DIR).unwrap();
         atomic_create_dir(ZELLIJ_TMP_LOG_DIR).unwrap();
-        start(Box::new(os_input), opts);
+        atomic_create_dir(ZELLIJ_SOCK_DIR).unwrap();
+        match opts.session.clone() {
+            Some(session_name) if session_is_alive(&session_name) => {
+                opts.attach = true;
+                start_client(Box::new(os_input), opts, session_name);
+            }
+            Some(session_name) if opts.attach => {
+                exit_with_error(&format!("There is no session named {:?}.", session_name));
+            }
+            Some(session_name) => {
+                let socket_path = session_socket_path(&session_name);
+                if let Err(e) = bind_session_socket(&socket_path, |path| os_input.bind(path)) {
+                    exit_with_error(&format!(
+                        "Could not create a session named {:?}: {}",
+                        session_name, e
+                    ));
+                }
+                check_config(&opts);
+                start(Box::new(os_input), opts);
+            }
+            None if opts.attach => {
+                let session_name = only_live_session_name();
+                start_client(Box::new(os_input), opts, session_name);
+            }
+            None => {
+                check_config(&opts);
+                start(Box::new(os_input), opts);
+            }
+        }
+    }
+}
+
+/// Binds the socket of a new session at `socket_path` with `bind`, taking over the socket of a
+/// previous session by that name whose server went away without cleaning up after itself.
+fn bind_session_socket(
+    socket_path: &Path,
+    mut bind: impl FnMut(&Path) -> io::Result<()>,
+) -> io::Result<()> {
+    match bind(socket_path) {
+        Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+            // sessions started at the same time with this name would otherwise all find the
+            // socket stale, and each would remove the socket that the previous one just bound
+            let sock_dir = fs::File::open(socket_path.parent().unwrap())?;
+            if unsafe { libc::flock(sock_dir.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            match UnixStream::connect(socket_path) {
+                Err(refused) if refused.raw_os_error() == Some(libc::ECONNREFUSED) => {
+                    fs::remove_file(socket_path)?;
+                    bind(socket_path)
+                }
+                _ => Err(e),
+            }
+            // the lock goes away with the directory's file descriptor
+        }
+        result => result,
+    }
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// Names the session resurrected from `saved_session` after the session that was saved, unless
+/// it was given another name, exiting if the save is broken or if that session is running.
+fn name_resurrected_session(opts: &mut CliArgs, saved_session: &Path) {
+    // the server would only find out about a broken save after we've left the terminal to it
+    let sessions_dir = opts
+        .sessions_dir
+        .clone()
+        .unwrap_or_else(default_sessions_dir);
+    if let Err(e) = SessionLayout::new(saved_session, &sessions_dir) {
+        exit_with_error(&e);
+    }
+    let session_name = opts
+        .session
+        .clone()
+        .unwrap_or_else(|| saved_session_name(saved_session));
+    if let Err(e) = validate_session_name(&session_name) {
+        exit_with_error(&e);
+    }
+    if session_is_alive(&session_name) {
+        exit_with_error(&format!(
+            "The session {0:?} is still running, run `zellij --session {0}` to attach to it.",
+            session_name
+        ));
+    }
+    opts.session = Some(session_name);
+}
+
+/// Exits if the config file that a new session would be started with is broken.
+fn check_config(opts: &CliArgs) {
+    // the server would only find out about it after we've left the terminal to it
+    if let Err(e) = Config::new(opts.config.as_deref()) {
+        exit_with_error(&e);
+    }
+}
+
+/// Returns the name of the only running session, exiting if there are none or several of them.
+fn only_live_session_name() -> String {
+    let mut session_names = live_session_names();
+    match session_names.len() {
+        0 => exit_with_error("No zellij session is running."),
+        1 => session_names.remove(0),
+        _ => exit_with_error(&format!(
+            "Several zellij sessions are running ({}), pick one with --session.",
+            session_names.join(", ")
+        )),
+    }
+}
+
+/// Sends a one-shot command to the session given with `--session`, or else to the session
+/// we're running inside of, or else to the only running session.
+fn send_command_to_session(opts: &CliArgs, api_command: ApiCommand) {
+    send_to_session(opts, ClientToServerMsg::Command(api_command));
+}
+
+/// Returns the name of the session given with `--session`, or else of the session we're running
+/// inside of, or else of the only running session.
+fn session_name(opts: &CliArgs) -> String {
+    opts.session
+        .clone()
+        .or_else(|| std::env::var(ZELLIJ_SESSION_NAME_ENV).ok())
+        .unwrap_or_else(only_live_session_name)
+}
+
+/// Connects to the server of the session named `session_name`, exiting if it isn't running.
+fn connect_to_session(session_name: &str) -> UnixStream {
+    match UnixStream::connect(session_socket_path(session_name)) {
+        Ok(stream) => stream,
+        Err(e) => exit_with_error(&format!(
+            "Could not connect to the zellij session {:?}: {}",
+            session_name, e
+        )),
+    }
+}
+
+/// Sends `msg` to the session given with `--session`, or else to the session we're running
+/// inside of, or else to the only running session, exiting if it couldn't be carried out.
+/// Returns the data the session answered with, if any.
+fn send_to_session(opts: &CliArgs, msg: ClientToServerMsg) -> Option<ResponseData> {
+    let session_name = session_name(opts);
+    let mut stream = connect_to_session(&session_name);
+    match request(&mut stream, &msg) {
+        Ok(data) => data,
+        Err(e) => exit_with_error(&format!("The zellij session {:?} {}", session_name, e)),
+    }
+}
+
+/// Prints the events of the session given with `--session`, or else of the session we're
+/// running inside of, or else of the only running session, one JSON object per line, until it
+/// ends.
+fn subscribe(opts: &CliArgs) {
+    let session_name = session_name(opts);
+    let mut stream = connect_to_session(&session_name);
+    if let Err(e) = request(&mut stream, &ClientToServerMsg::Subscribe) {
+        exit_with_error(&format!("The zellij session {:?} {}", session_name, e));
+    }
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    while let Ok(msg) = recv_ipc_msg(&mut stream) {
+        if let ServerToClientMsg::Event(event) = msg {
+            let line = serde_json::to_string(&event).unwrap();
+            // stop once whoever reads the events goes away
+            if writeln!(stdout, "{}", line)
+                .and_then(|_| stdout.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+}
+
+/// Sends `msg`, a request to run a command, to the session given with `--session`, or else to
+/// the session we're running inside of, or else to the only running session, and waits for the
+/// command to exit, returning how it did. Exits if the pane of the command is closed first.
+fn run_and_wait(opts: &CliArgs, msg: ClientToServerMsg) -> ExitStatus {
+    let session_name = session_name(opts);
+    let mut stream = connect_to_session(&session_name);
+    wait_for_command(&mut stream, &session_name, msg)
+}
+
+/// Sends `msg`, a request to run a command, to the server of the session named `session_name`
+/// at the other end of `stream`, and waits for the command to exit, returning how it did.
+fn wait_for_command(
+    stream: &mut UnixStream,
+    session_name: &str,
+    msg: ClientToServerMsg,
+) -> ExitStatus {
+    // before the command is run, or we could miss it exiting
+    if let Err(e) = request(stream, &ClientToServerMsg::Subscribe) {
+        exit_with_error(&format!("The zellij session {:?} {}", session_name, e));
+    }
+    if let Err(e) = send_ipc_msg(stream, &msg) {
+        exit_with_error(&format!(
+            "The zellij session {:?} could not be reached: {}",
+            session_name, e
+        ));
+    }
+    // the events that come before the answer might already be about the new pane
+    let mut early_events = vec![];
+    let pane_id = loop {
+        match recv_ipc_msg(stream) {
+            Ok(ServerToClientMsg::Response(Response::Data(ResponseData::PaneId(pane_id)))) => {
+                break pane_id
+            }
+            Ok(ServerToClientMsg::Response(Response::Error(e))) => exit_with_error(&format!(
+                "The zellij session {:?} refused the request: {}",
+                session_name, e
+            )),
+            Ok(ServerToClientMsg::Event(event)) => early_events.push(event),
+            Ok(_) => {}
+            Err(e) => exit_with_error(&format!(
+                "The zellij session {:?} went away without answering: {}",
+                session_name, e
+            )),
+        }
+    };
+    for event in early_events {
+        if let Some(status) = command_exit_status(event, pane_id) {
+            return status;
+        }
+    }
+    loop {
+        match recv_ipc_msg(stream) {
+            Ok(ServerToClientMsg::Event(event)) => {
+                if let Some(status) = command_exit_status(event, pane_id) {
+                    return status;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => exit_with_error(&format!(
+                "The zellij session {:?} ended before the command exited: {}",
+                session_name, e
+            )),
+        }
+    }
+}
+
+/// Returns how the command running in the pane `pane_id` exited if that's what `event` is
+/// about, exiting if it's about the pane being closed before that.
+fn command_exit_status(event: Event, pane_id: PaneId) -> Option<ExitStatus> {
+    match event {
+        Event::CommandExited { pane, status } if pane == pane_id => Some(status),
+        Event::PaneClosed { pane, .. } if pane == pane_id => {
+            exit_with_error("The pane of the command was closed before it exited.")
+        }
+        _ => None,
+    }
+}
+
+/// Sends the one-shot request `msg` to the server at the other end of `stream` and waits for
+/// its answer, returning the data it carries if any.
+fn request(
+    stream: &mut UnixStream,
+    msg: &ClientToServerMsg,
+) -> Result<Option<ResponseData>, String> {
+    send_ipc_msg(stream, msg).map_err(|e| format!("could not be reached: {}", e))?;
+    loop {
+        match recv_ipc_msg(stream) {
+            Ok(ServerToClientMsg::Response(Response::Ok)) => return Ok(None),
+            Ok(ServerToClientMsg::Response(Response::Data(data))) => return Ok(Some(data)),
+            Ok(ServerToClientMsg::Response(Response::Error(e))) => {
+                return Err(format!("refused the request: {}", e))
+            }
+            // not meant for us, we're not attached to the session
+            Ok(_) => {}
+            Err(e) => return Err(format!("went away without answering: {}", e)),
+        }
+    }
+}
+
+/// Turns the arguments of `zellij send-keys` into the chunks of input to write: the bytes of
+/// each key whose name is given (unless they're all `literal` text), or else of the text itself,
+/// or all of the `bytes` given as numbers at once.
+fn keys_to_input(keys: Vec<String>, literal: bool, bytes: bool) -> Result<Vec<Vec<u8>>, String> {
+    if bytes {
+        let bytes = keys
+            .iter()
+            .map(|byte| {
+                byte.parse::<u8>().map_err(|_| {
+                    format!("Invalid byte {:?}, expected a number from 0 to 255.", byte)
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        return Ok(vec![bytes]);
+    }
+    Ok(keys
+        .into_iter()
+        .map(|key| match parse_key(&key) {
+            Ok(parsed_key) if !literal => key_to_bytes(parsed_key),
+            _ => key.into_bytes(),
+        })
+        .collect())
+}
+
+/// A session that has a socket, as listed by `zellij list-sessions`.
+#[derive(Debug, PartialEq)]
+enum ListedSession {
+    Running(Session),
+    /// A session whose server is alive but couldn't tell us about it, carrying its name and why
+    /// (usually because it runs another version of zellij).
+    Unknown(String, String),
+}
+
+/// Asks the servers of the sessions that have a socket in `sock_dir` about their state,
+/// removing the sockets that no server listens on anymore.
+fn find_sessions(sock_dir: &Path) -> Vec<ListedSession> {
+    let mut sessions = vec![];
+    for session_name in session_names_in(sock_dir) {
+        let socket_path = sock_dir.join(&session_name);
+        let mut stream = match UnixStream::connect(&socket_path) {
+            Ok(stream) => stream,
+            // the server died without cleaning up after itself
+            Err(e) if e.raw_os_error() == Some(libc::ECONNREFUSED) => {
+                let _ = fs::remove_file(&socket_path);
+                continue;
+            }
+            // eg. the socket went away in the meantime, or it isn't ours to connect to
+            Err(_) => continue,
+        };
+        let session = match request(&mut stream, &ClientToServerMsg::ListSessions) {
+            Ok(Some(ResponseData::SessionList(list))) if !list.is_empty() => {
+                ListedSession::Running(list.into_iter().next().unwrap())
+            }
+            Ok(_) => ListedSession::Unknown(
+                session_name,
+                "answered with something else than its state".to_string(),
+            ),
+            Err(e) => ListedSession::Unknown(session_name, e),
+        };
+        sessions.push(session);
+    }
+    sessions
+}
+
+/// Prints a table of the running sessions, removing the sockets of the ones whose server died
+/// without cleaning up after itself.
+fn list_sessions() {
+    let sessions = find_sessions(Path::new(ZELLIJ_SOCK_DIR));
+    if sessions.is_empty() {
+        println!("No zellij session is running.");
+        return;
+    }
+    let name_width = sessions
+        .iter()
+        .map(|session| match session {
+            ListedSession::Running(session) => session.alias.len(),
+            ListedSession::Unknown(session_name, _) => session_name.len(),
+        })
+        .max()
+        .unwrap_or_default()
+        .max("NAME".len());
+    println!(
+        "{:<name_width$}  {:<19}  {:>4}  {:>7}",
+        "NAME",
+        "CREATED",
+        "TABS",
+        "CLIENTS",
+        name_width = name_width
+    );
+    let mut unknown_sessions = vec![];
+    for session in sessions {
+        match session {
+            ListedSession::Running(session) => println!(
+                "{:<name_width$}  {:<19}  {:>4}  {:>7}",
+                session.alias,
+                format_local_time(session.created_at),
+                session.tab_count,
+                session.client_count,
+                name_width = name_width
+            ),
+            ListedSession::Unknown(session_name, reason) => {
+                println!(
+                    "{:<name_width$}  {:<19}  {:>4}  {:>7}",
+                    session_name,
+                    "?",
+                    "?",
+                    "?",
+                    name_width = name_width
+                );
+                unknown_sessions.push((session_name, reason));
+            }
+        }
+    }
+    for (session_name, reason) in unknown_sessions {
+        println!(
+            "The zellij session {:?} {}, it may be running another version of zellij.",
+            session_name, reason
+        );
+    }
+}
+
+/// Ends the session named `session_name`, waiting for its server to go away.
+fn kill_session(session_name: &str) {
+    if let Err(e) = validate_session_name(session_name) {
+        exit_with_error(&e);
+    }
+    let mut stream = match UnixStream::connect(session_socket_path(session_name)) {
+        Ok(stream) => stream,
+        Err(_) => exit_with_error(&format!("There is no session named {:?}.", session_name)),
+    };
+    if let Err(e) = request(&mut stream, &ClientToServerMsg::KillSession) {
+        exit_with_error(&format!("The zellij session {:?} {}", session_name, e));
+    }
+    // the server closes our connection once it's done closing the session's panes
+    while recv_ipc_msg::<ServerToClientMsg>(&mut stream).is_ok() {}
+    println!("Killed session {:?}.", session_name);
+}
+
+/// Formats a unix timestamp as a date and time in the local timezone.
+fn format_local_time(timestamp: u64) -> String {
+    let timestamp = timestamp as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&timestamp, &mut tm) }.is_null() {
+        return "?".to_string();
     }
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
 }
@@ -1,5 +1,1159 @@
-use super::super::common::{screen};
+//! The Zellij server, which owns a session's panes and plugins and renders them to every
+//! client attached to it.
 
-pub fn start_server() {
-  // TODO
-}
\ No newline at end of file
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+#[cfg(not(test))]
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use directories_next::ProjectDirs;
+use wasmer::{ChainableNamedResolver, Instance, Module, Store, Value};
+use wasmer_wasi::{Pipe, WasiState};
+
+use crate::cli::CliArgs;
+use crate::command_is_executing::CommandIsExecuting;
+use crate::common::config::Config;
+use crate::common::events::EventSender;
+use crate::common::input::{
+    actions::{Action, Direction},
+    handler::InputHandler,
+};
+use crate::common::ipc::{
+    session_socket_path, ClientId, ClientToServerMsg, ClientType, ExitReason, PaneTarget, Response,
+    ResponseData, ServerToClientMsg, Session, SessionID, SessionStructure,
+};
+use crate::common::{
+    update_state, ApiCommand, AppInstruction, AppState, ChannelWithContext, SenderType,
+    SenderWithContext, SyncChannelWithContext, OPENCALLS,
+};
+use crate::errors::{AppContext, ContextType, PluginContext, PtyContext, ScreenContext};
+use crate::layout::{default_sessions_dir, Layout, SessionLayout};
+use crate::os_input_output::OsApi;
+use crate::panes::{DumpOptions, PaneId, PositionAndSize};
+use crate::pty_bus::{PtyBus, PtyInstruction, RunCommand, TerminalAction};
+use crate::screen::{Screen, ScreenInstruction, ScreenSettings};
+use crate::utils::consts::ZELLIJ_ROOT_PLUGIN_DIR;
+use crate::utils::logging::debug_log_to_file;
+use crate::wasm_vm::{
+    wasi_stdout, wasi_write_string, zellij_imports, PluginEnv, PluginInstruction,
+};
+use termion::input::TermRead;
+
+/// How often a running session is saved, so that it can be resurrected later on. Tests don't
+/// save their sessions unless they ask to, so that they don't leave saved sessions behind.
+#[cfg(not(test))]
+const SESSION_SAVE_INTERVAL: Option<Duration> = Some(Duration::from_secs(30));
+#[cfg(test)]
+const SESSION_SAVE_INTERVAL: Option<Duration> = None;
+
+/// Forks off a server for a new session named `session_name`, detached from the current
+/// terminal so that the session outlives it. Only returns in the parent process.
+#[cfg(not(test))]
+pub fn start_server_daemon(os_input: Box<dyn OsApi>, opts: CliArgs, session_name: String) {
+    use crate::utils::consts::ZELLIJ_SESSION_NAME_ENV;
+    use nix::fcntl::OFlag;
+    use nix::sys::stat::Mode;
+    use nix::unistd::{dup2, fork, setsid, ForkResult};
+
+    // this is only safe because we haven't spawned any threads yet
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { .. }) => {}
+        Ok(ForkResult::Child) => {
+            // leave the terminal's session, so that we don't get a SIGHUP when it closes
+            let _ = setsid();
+            if let Ok(dev_null) = nix::fcntl::open("/dev/null", OFlag::O_RDWR, Mode::empty()) {
+                for fd in 0..3 {
+                    let _ = dup2(dev_null, fd);
+                }
+            }
+            // lets commands run from inside the session's panes target it by default
+            std::env::set_var(ZELLIJ_SESSION_NAME_ENV, &session_name);
+            let socket_path = session_socket_path(&session_name);
+            start_server(os_input, opts, session_name);
+            let _ = fs::remove_file(socket_path);
+            std::process::exit(0);
+        }
+        Err(e) => panic!("failed to fork the server process: {:?}", e),
+    }
+}
+
+/// Starts a server for a new session named `session_name`, and blocks until the session ends.
+///
+/// The session's panes are only created once the first client asks for them with
+/// [`ClientToServerMsg::CreateSession`], as they are sized after that client's terminal.
+pub fn start_server(mut os_input: Box<dyn OsApi>, opts: CliArgs, session_name: String) {
+    let socket_path = session_socket_path(&session_name);
+    os_input
+        .serve(&socket_path)
+        .expect("could not listen on ipc socket");
+    let (first_client_id, full_screen_ws) = loop {
+        if let (client_id, ClientToServerMsg::CreateSession(full_screen_ws)) =
+            os_input.recv_from_client()
+        {
+            break (client_id, full_screen_ws);
+        }
+    };
+    let session = Session {
+        id: std::process::id() as u64,
+        conn_name: socket_path.to_string_lossy().into_owned(),
+        alias: session_name,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or_default(),
+        tab_count: 0,
+        client_count: 1,
+    };
+    let mut app_state = AppState::default();
+    app_state
+        .clients
+        .insert(first_client_id, ClientType::Writer);
+
+    let command_is_executing = CommandIsExecuting::new();
+
+    let (send_screen_instructions, receive_screen_instructions): ChannelWithContext<
+        ScreenInstruction,
+    > = mpsc::channel();
+    let err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
+    let mut send_screen_instructions =
+        SenderWithContext::new(err_ctx, SenderType::Sender(send_screen_instructions));
+    // attach the client that created the session before anything gets rendered
+    send_screen_instructions
+        .send(ScreenInstruction::AddClient(
+            first_client_id,
+            full_screen_ws,
+        ))
+        .unwrap();
+
+    let (send_pty_instructions, receive_pty_instructions): ChannelWithContext<PtyInstruction> =
+        mpsc::channel();
+    let mut send_pty_instructions =
+        SenderWithContext::new(err_ctx, SenderType::Sender(send_pty_instructions));
+
+    let (send_plugin_instructions, receive_plugin_instructions): ChannelWithContext<
+        PluginInstruction,
+    > = mpsc::channel();
+    let send_plugin_instructions =
+        SenderWithContext::new(err_ctx, SenderType::Sender(send_plugin_instructions));
+
+    let (send_app_instructions, receive_app_instructions): SyncChannelWithContext<AppInstruction> =
+        mpsc::sync_channel(0);
+    let send_app_instructions =
+        SenderWithContext::new(err_ctx, SenderType::SyncSender(send_app_instructions));
+
+    let events = EventSender::new(os_input.clone());
+
+    let mut pty_bus = PtyBus::new(
+        receive_pty_instructions,
+        send_pty_instructions.clone(),
+        send_screen_instructions.clone(),
+        send_plugin_instructions.clone(),
+        os_input.clone(),
+        events.clone(),
+        opts.debug,
+    );
+
+    // Don't use default layouts in tests, but do everywhere else
+    #[cfg(not(test))]
+    let default_layout = Some(PathBuf::from("default"));
+    #[cfg(test)]
+    let default_layout = None;
+    let maybe_layout = opts.layout.or(default_layout).map(Layout::new);
+    let sessions_dir = opts
+        .sessions_dir
+        .clone()
+        .unwrap_or_else(default_sessions_dir);
+    let resurrected_session = opts.resurrect.as_ref().map(|saved_session| {
+        SessionLayout::new(saved_session, &sessions_dir).unwrap_or_else(|e| panic!("{}", e))
+    });
+    let config = Config::new(opts.config.as_deref()).unwrap_or_else(|e| panic!("{}", e));
+
+    #[cfg(not(test))]
+    std::panic::set_hook({
+        use crate::errors::handle_panic;
+        let send_app_instructions = send_app_instructions.clone();
+        Box::new(move |info| {
+            handle_panic(info, &send_app_instructions);
+        })
+    });
+
+    let pty_thread = thread::Builder::new()
+        .name("pty".to_string())
+        .spawn({
+            let mut command_is_executing = command_is_executing.clone();
+            match resurrected_session {
+                Some(session_layout) => {
+                    for tab_layout in session_layout.tabs {
+                        send_pty_instructions
+                            .send(PtyInstruction::ResurrectTab(tab_layout))
+                            .unwrap();
+                    }
+                }
+                None => send_pty_instructions.send(PtyInstruction::NewTab).unwrap(),
+            }
+            move || loop {
+                let (event, mut err_ctx) = pty_bus
+                    .receive_pty_instructions
+                    .recv()
+                    .expect("failed to receive event on channel");
+                err_ctx.add_call(ContextType::Pty(PtyContext::from(&event)));
+                pty_bus.send_screen_instructions.update(err_ctx);
+                match event {
+                    PtyInstruction::SpawnTerminal(terminal_action) => {
+                        let pid = pty_bus.spawn_terminal(terminal_action);
+                        pty_bus
+                            .send_screen_instructions
+                            .send(ScreenInstruction::NewPane(PaneId::Terminal(pid)))
+                            .unwrap();
+                    }
+                    PtyInstruction::SpawnTerminalVertically(terminal_action) => {
+                        let pid = pty_bus.spawn_terminal(terminal_action);
+                        pty_bus
+                            .send_screen_instructions
+                            .send(ScreenInstruction::VerticalSplit(PaneId::Terminal(pid)))
+                            .unwrap();
+                    }
+                    PtyInstruction::SpawnTerminalHorizontally(terminal_action) => {
+                        let pid = pty_bus.spawn_terminal(terminal_action);
+                        pty_bus
+                            .send_screen_instructions
+                            .send(ScreenInstruction::HorizontalSplit(PaneId::Terminal(pid)))
+                            .unwrap();
+                    }
+                    PtyInstruction::NewTab => {
+                        if let Some(layout) = maybe_layout.clone() {
+                            pty_bus.spawn_terminals_for_layout(layout);
+                        } else {
+                            let pid = pty_bus.spawn_terminal(None);
+                            pty_bus
+                                .send_screen_instructions
+                                .send(ScreenInstruction::NewTab(pid))
+                                .unwrap();
+                        }
+                    }
+                    PtyInstruction::ResurrectTab(layout) => {
+                        pty_bus.spawn_terminals_for_layout(layout);
+                    }
+                    PtyInstruction::ClosePane(id) => {
+                        pty_bus.close_pane(id);
+                        command_is_executing.done_closing_pane();
+                    }
+                    PtyInstruction::RerunCommand(id) => {
+                        if let Some(new_id) = pty_bus.rerun_command(id) {
+                            pty_bus
+                                .send_screen_instructions
+                                .send(ScreenInstruction::ReplacePane(
+                                    PaneId::Terminal(id),
+                                    PaneId::Terminal(new_id),
+                                ))
+                                .unwrap();
+                        }
+                    }
+                    PtyInstruction::TerminalExited(id, child_pid, status) => {
+                        pty_bus.terminal_exited(id, child_pid, status);
+                    }
+                    PtyInstruction::CloseTab(ids) => {
+                        pty_bus.close_tab(ids);
+                        command_is_executing.done_closing_pane();
+                    }
+                    PtyInstruction::Quit => {
+                        break;
+                    }
+                }
+            }
+        })
+        .unwrap();
+
+    let screen_thread = thread::Builder::new()
+        .name("screen".to_string())
+        .spawn({
+            let mut command_is_executing = command_is_executing.clone();
+            let os_input = os_input.clone();
+            let send_pty_instructions = send_pty_instructions.clone();
+            let send_plugin_instructions = send_plugin_instructions.clone();
+            let send_app_instructions = send_app_instructions.clone();
+            let events = events.clone();
+            let settings = ScreenSettings {
+                full_screen_ws,
+                max_panes: opts.max_panes,
+                size_policy: opts.size_policy,
+                theme: config.theme.clone(),
+                allow_clipboard_writes: config.allow_clipboard_writes,
+            };
+
+            move || {
+                let mut screen = Screen::new(
+                    receive_screen_instructions,
+                    send_pty_instructions,
+                    send_plugin_instructions,
+                    send_app_instructions,
+                    settings,
+                    os_input,
+                    events,
+                );
+                loop {
+                    let (event, mut err_ctx) = screen
+                        .receiver
+                        .recv()
+                        .expect("failed to receive event on channel");
+                    err_ctx.add_call(ContextType::Screen(ScreenContext::from(&event)));
+                    screen.send_app_instructions.update(err_ctx);
+                    screen.send_pty_instructions.update(err_ctx);
+                    match event {
+                        ScreenInstruction::Pty(pid, vte_event) => {
+                            screen.handle_pty_event(pid, vte_event);
+                        }
+                        ScreenInstruction::Render => {
+                            screen.render();
+                        }
+                        ScreenInstruction::NewPane(pid) => {
+                            screen.get_active_tab_mut().unwrap().new_pane(pid);
+                            command_is_executing.done_opening_new_pane(Some(pid));
+                        }
+                        ScreenInstruction::HorizontalSplit(pid) => {
+                            screen.get_active_tab_mut().unwrap().horizontal_split(pid);
+                            command_is_executing.done_opening_new_pane(Some(pid));
+                        }
+                        ScreenInstruction::VerticalSplit(pid) => {
+                            screen.get_active_tab_mut().unwrap().vertical_split(pid);
+                            command_is_executing.done_opening_new_pane(Some(pid));
+                        }
+                        ScreenInstruction::WriteCharacter(bytes) => {
+                            screen.write_to_active_terminal(bytes);
+                        }
+                        ScreenInstruction::ResizeLeft => {
+                            screen.get_active_tab_mut().unwrap().resize_left();
+                        }
+                        ScreenInstruction::ResizeRight => {
+                            screen.get_active_tab_mut().unwrap().resize_right();
+                        }
+                        ScreenInstruction::ResizeDown => {
+                            screen.get_active_tab_mut().unwrap().resize_down();
+                        }
+                        ScreenInstruction::ResizeUp => {
+                            screen.get_active_tab_mut().unwrap().resize_up();
+                        }
+                        ScreenInstruction::MoveFocus => {
+                            screen.get_active_tab_mut().unwrap().move_focus();
+                        }
+                        ScreenInstruction::MoveFocusLeft => {
+                            screen.get_active_tab_mut().unwrap().move_focus_left();
+                        }
+                        ScreenInstruction::MoveFocusDown => {
+                            screen.get_active_tab_mut().unwrap().move_focus_down();
+                        }
+                        ScreenInstruction::MoveFocusRight => {
+                            screen.get_active_tab_mut().unwrap().move_focus_right();
+                        }
+                        ScreenInstruction::MoveFocusUp => {
+                            screen.get_active_tab_mut().unwrap().move_focus_up();
+                        }
+                        ScreenInstruction::ScrollUp => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .scroll_active_terminal_up();
+                        }
+                        ScreenInstruction::ScrollDown => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .scroll_active_terminal_down();
+                        }
+                        ScreenInstruction::ClearScroll => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .clear_active_terminal_scroll();
+                        }
+                        ScreenInstruction::CloseFocusedPane => {
+                            screen.get_active_tab_mut().unwrap().close_focused_pane();
+                            screen.render();
+                        }
+                        ScreenInstruction::SetSelectable(id, selectable) => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .set_pane_selectable(id, selectable);
+                            // FIXME: Is this needed?
+                            screen.render();
+                        }
+                        ScreenInstruction::SetMaxHeight(id, max_height) => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .set_pane_max_height(id, max_height);
+                        }
+                        ScreenInstruction::SetInvisibleBorders(id, invisible_borders) => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .set_pane_invisible_borders(id, invisible_borders);
+                            screen.render();
+                        }
+                        ScreenInstruction::ClosePane(id) => {
+                            screen.close_pane(id);
+                        }
+                        ScreenInstruction::HoldPane(id, status) => {
+                            screen.hold_pane(id, status);
+                        }
+                        ScreenInstruction::ReplacePane(id, new_id) => {
+                            screen.replace_pane(id, new_id);
+                        }
+                        ScreenInstruction::ToggleActiveTerminalFullscreen => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .toggle_active_pane_fullscreen();
+                        }
+                        ScreenInstruction::RenamePane(title) => {
+                            screen
+                                .get_active_tab_mut()
+                                .unwrap()
+                                .rename_active_pane(title);
+                        }
+                        ScreenInstruction::NewTab(pane_id) => {
+                            screen.new_tab(pane_id);
+                            command_is_executing
+                                .done_opening_new_pane(Some(PaneId::Terminal(pane_id)));
+                        }
+                        ScreenInstruction::SwitchTabNext => screen.switch_tab_next(),
+                        ScreenInstruction::SwitchTabPrev => screen.switch_tab_prev(),
+                        ScreenInstruction::CloseTab => screen.close_tab(),
+                        ScreenInstruction::ApplyLayout((layout, new_pane_pids)) => {
+                            screen.apply_layout(layout, new_pane_pids);
+                            command_is_executing.done_opening_new_pane(None);
+                        }
+                        ScreenInstruction::GetTabCount(tab_count_tx) => {
+                            let _ = tab_count_tx.send(screen.get_tabs_mut().len());
+                        }
+                        ScreenInstruction::GetSessionLayout(
+                            scrollback_lines,
+                            session_layout_tx,
+                        ) => {
+                            let _ = session_layout_tx.send(screen.session_layout(scrollback_lines));
+                        }
+                        ScreenInstruction::DumpPane(pane_id, client_id, options, dump_tx) => {
+                            let _ = dump_tx.send(screen.dump_pane(pane_id, client_id, options));
+                        }
+                        ScreenInstruction::GetSessionStructure(client_id, structure_tx) => {
+                            let _ = structure_tx.send(screen.session_structure(client_id));
+                        }
+                        ScreenInstruction::SendKeys(pane_target, client_id, input, sent_tx) => {
+                            let _ = sent_tx.send(screen.send_keys(pane_target, client_id, input));
+                        }
+                        ScreenInstruction::AddClient(client_id, client_size) => {
+                            screen.add_client(client_id, client_size);
+                        }
+                        ScreenInstruction::RemoveClient(client_id) => {
+                            screen.remove_client(client_id);
+                        }
+                        ScreenInstruction::SetActiveClient(client_id) => {
+                            screen.set_active_client(client_id);
+                        }
+                        ScreenInstruction::TerminalResize(client_id, new_size) => {
+                            screen.resize_client(client_id, new_size);
+                        }
+                        ScreenInstruction::Quit => {
+                            break;
+                        }
+                    }
+                }
+            }
+        })
+        .unwrap();
+
+    let wasm_thread = thread::Builder::new()
+        .name("wasm".to_string())
+        .spawn({
+            let mut send_pty_instructions = send_pty_instructions.clone();
+            let mut send_screen_instructions = send_screen_instructions.clone();
+            let mut send_app_instructions = send_app_instructions.clone();
+            let keybinds_help = config.keybinds_help.clone();
+            let theme = config.theme.clone();
+
+            let store = Store::default();
+            let mut plugin_id = 0;
+            let mut plugin_map = HashMap::new();
+
+            move || loop {
+                let (event, mut err_ctx) = receive_plugin_instructions
+                    .recv()
+                    .expect("failed to receive event on channel");
+                err_ctx.add_call(ContextType::Plugin(PluginContext::from(&event)));
+                send_screen_instructions.update(err_ctx);
+                send_pty_instructions.update(err_ctx);
+                send_app_instructions.update(err_ctx);
+                match event {
+                    PluginInstruction::Load(pid_tx, path) => {
+                        let project_dirs =
+                            ProjectDirs::from("org", "Zellij Contributors", "Zellij").unwrap();
+                        let plugin_dir = project_dirs.data_dir().join("plugins/");
+                        // FIXME: This really shouldn't need to exist anymore, let's get rid of it!
+                        let root_plugin_dir = Path::new(ZELLIJ_ROOT_PLUGIN_DIR);
+                        let wasm_bytes = fs::read(&path)
+                            .or_else(|_| fs::read(&path.with_extension("wasm")))
+                            .or_else(|_| fs::read(&plugin_dir.join(&path).with_extension("wasm")))
+                            .or_else(|_| {
+                                fs::read(&root_plugin_dir.join(&path).with_extension("wasm"))
+                            })
+                            .unwrap_or_else(|_| panic!("cannot find plugin {}", &path.display()));
+
+                        // FIXME: Cache this compiled module on disk. I could use `(de)serialize_to_file()` for that
+                        let module = Module::new(&store, &wasm_bytes).unwrap();
+
+                        let output = Pipe::new();
+                        let input = Pipe::new();
+                        let mut wasi_env = WasiState::new("Zellij")
+                            .env("CLICOLOR_FORCE", "1")
+                            .preopen(|p| {
+                                p.directory(".") // FIXME: Change this to a more meaningful dir
+                                    .alias(".")
+                                    .read(true)
+                                    .write(true)
+                                    .create(true)
+                            })
+                            .unwrap()
+                            .stdin(Box::new(input))
+                            .stdout(Box::new(output))
+                            .finalize()
+                            .unwrap();
+
+                        let wasi = wasi_env.import_object(&module).unwrap();
+
+                        let plugin_env = PluginEnv {
+                            plugin_id,
+                            send_pty_instructions: send_pty_instructions.clone(),
+                            send_screen_instructions: send_screen_instructions.clone(),
+                            send_app_instructions: send_app_instructions.clone(),
+                            keybinds_help: keybinds_help.clone(),
+                            theme: theme.clone(),
+                            wasi_env,
+                        };
+
+                        let zellij = zellij_imports(&store, &plugin_env);
+                        let instance = Instance::new(&module, &zellij.chain_back(wasi)).unwrap();
+
+                        let start = instance.exports.get_function("_start").unwrap();
+
+                        // This eventually calls the `.init()` method
+                        start.call(&[]).unwrap();
+
+                        plugin_map.insert(plugin_id, (instance, plugin_env));
+                        pid_tx.send(plugin_id).unwrap();
+                        plugin_id += 1;
+                    }
+                    PluginInstruction::Draw(buf_tx, pid, rows, cols) => {
+                        let (instance, plugin_env) = plugin_map.get(&pid).unwrap();
+
+                        let draw = instance.exports.get_function("draw").unwrap();
+
+                        draw.call(&[Value::I32(rows as i32), Value::I32(cols as i32)])
+                            .unwrap();
+
+                        buf_tx.send(wasi_stdout(&plugin_env.wasi_env)).unwrap();
+                    }
+                    // FIXME: Deduplicate this with the callback below!
+                    PluginInstruction::Input(pid, input_bytes) => {
+                        let (instance, plugin_env) = plugin_map.get(&pid).unwrap();
+
+                        let handle_key = instance.exports.get_function("handle_key").unwrap();
+                        for key in input_bytes.keys() {
+                            if let Ok(key) = key {
+                                wasi_write_string(
+                                    &plugin_env.wasi_env,
+                                    &serde_json::to_string(&key).unwrap(),
+                                );
+                                handle_key.call(&[]).unwrap();
+                            }
+                        }
+
+                        drop(send_screen_instructions.send(ScreenInstruction::Render));
+                    }
+                    PluginInstruction::GlobalInput(input_bytes) => {
+                        // FIXME: Set up an event subscription system, and timed callbacks
+                        for (instance, plugin_env) in plugin_map.values() {
+                            let handler =
+                                instance.exports.get_function("handle_global_key").unwrap();
+                            for key in input_bytes.keys() {
+                                if let Ok(key) = key {
+                                    wasi_write_string(
+                                        &plugin_env.wasi_env,
+                                        &serde_json::to_string(&key).unwrap(),
+                                    );
+                                    handler.call(&[]).unwrap();
+                                }
+                            }
+                        }
+
+                        drop(send_screen_instructions.send(ScreenInstruction::Render));
+                    }
+                    PluginInstruction::Unload(pid) => drop(plugin_map.remove(&pid)),
+                    PluginInstruction::Quit => break,
+                }
+            }
+        })
+        .unwrap();
+
+    // the session saver stops once the session ends, when this is dropped
+    let (stop_session_saver, session_saver_stopped) = mpsc::channel::<()>();
+    let mut session_saver_thread = None;
+    if let Some(session_save_interval) = opts.session_save_interval.or(SESSION_SAVE_INTERVAL) {
+        let session_saver = thread::Builder::new()
+            .name("session_saver".to_string())
+            .spawn({
+                let mut send_screen_instructions = send_screen_instructions.clone();
+                let session_name = session.alias.clone();
+                let scrollback_lines = if opts.save_scrollback || config.save_scrollback {
+                    Some(
+                        opts.scrollback_lines_to_save
+                            .unwrap_or(config.scrollback_lines_to_save),
+                    )
+                } else {
+                    None
+                };
+                move || {
+                    let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
+                    err_ctx.add_call(ContextType::SessionSaver);
+                    send_screen_instructions.update(err_ctx);
+                    while let Err(mpsc::RecvTimeoutError::Timeout) =
+                        session_saver_stopped.recv_timeout(session_save_interval)
+                    {
+                        let (session_layout_tx, session_layout_rx) = mpsc::channel();
+                        if send_screen_instructions
+                            .send(ScreenInstruction::GetSessionLayout(
+                                scrollback_lines,
+                                session_layout_tx,
+                            ))
+                            .is_err()
+                        {
+                            break;
+                        }
+                        match session_layout_rx.recv() {
+                            // keep the last save of a session that has no tabs left
+                            Ok(session_layout) if session_layout.tabs.is_empty() => {}
+                            Ok(session_layout) => {
+                                if let Err(e) = session_layout.save(&sessions_dir, &session_name) {
+                                    let _ = debug_log_to_file(format!(
+                                        "could not save session {:?}: {}",
+                                        session_name, e
+                                    ));
+                                }
+                            }
+                            Err(_) => break,
+                        }
+                    }
+                }
+            })
+            .unwrap();
+        session_saver_thread = Some(session_saver);
+    }
+
+    let _router_thread = thread::Builder::new()
+        .name("ipc_server".to_string())
+        .spawn({
+            let os_input = os_input.clone();
+            let send_screen_instructions = send_screen_instructions.clone();
+            let send_pty_instructions = send_pty_instructions.clone();
+            let send_plugin_instructions = send_plugin_instructions.clone();
+            let send_app_instructions = send_app_instructions.clone();
+            let session = session.clone();
+            move || {
+                let mut router = ClientRouter {
+                    session,
+                    config,
+                    os_input,
+                    command_is_executing,
+                    send_screen_instructions,
+                    send_pty_instructions,
+                    send_plugin_instructions,
+                    send_app_instructions,
+                    events,
+                    input_handlers: BTreeMap::new(),
+                    last_active_writer: Some(first_client_id),
+                };
+                router.route_client_messages(first_client_id)
+            }
+        })
+        .unwrap();
+    os_input.send_to_client(
+        first_client_id,
+        ServerToClientMsg::Response(Response::Data(ResponseData::SessionInfo(session))),
+    );
+
+    #[warn(clippy::never_loop)]
+    loop {
+        let (app_instruction, mut err_ctx) = receive_app_instructions
+            .recv()
+            .expect("failed to receive app instruction on channel");
+
+        err_ctx.add_call(ContextType::App(AppContext::from(&app_instruction)));
+        send_screen_instructions.update(err_ctx);
+        send_pty_instructions.update(err_ctx);
+        match app_instruction {
+            AppInstruction::GetState(state_tx) => drop(state_tx.send(app_state.clone())),
+            AppInstruction::SetState(state) => app_state = state,
+            AppInstruction::Exit => {
+                break;
+            }
+            AppInstruction::Error(backtrace) => {
+                let _ = send_screen_instructions.send(ScreenInstruction::Quit);
+                let _ = screen_thread.join();
+                let _ = send_pty_instructions.send(PtyInstruction::Quit);
+                let _ = pty_thread.join();
+                let _ = send_plugin_instructions.send(PluginInstruction::Quit);
+                let _ = wasm_thread.join();
+                for client_id in app_state.clients.keys() {
+                    os_input.send_to_client(
+                        *client_id,
+                        ServerToClientMsg::Exit(ExitReason::Error(backtrace.clone())),
+                    );
+                }
+                os_input.disconnect_clients();
+                let _ = fs::remove_file(&socket_path);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let _ = send_pty_instructions.send(PtyInstruction::Quit);
+    pty_thread.join().unwrap();
+    let _ = send_screen_instructions.send(ScreenInstruction::Quit);
+    screen_thread.join().unwrap();
+    let _ = send_plugin_instructions.send(PluginInstruction::Quit);
+    wasm_thread.join().unwrap();
+    // let a save that's underway finish, so that the session isn't left halfway saved
+    drop(stop_session_saver);
+    if let Some(session_saver_thread) = session_saver_thread {
+        session_saver_thread.join().unwrap();
+    }
+
+    for client_id in app_state.clients.keys() {
+        os_input.send_to_client(*client_id, ServerToClientMsg::Exit(ExitReason::Normal));
+    }
+    os_input.disconnect_clients();
+}
+
+/// Routes the messages received from clients to the rest of the server, keeping an
+/// [`InputHandler`] for every attached client.
+struct ClientRouter {
+    session: Session,
+    /// The config every client's input is handled with.
+    config: Config,
+    os_input: Box<dyn OsApi>,
+    command_is_executing: CommandIsExecuting,
+    send_screen_instructions: SenderWithContext<ScreenInstruction>,
+    send_pty_instructions: SenderWithContext<PtyInstruction>,
+    send_plugin_instructions: SenderWithContext<PluginInstruction>,
+    send_app_instructions: SenderWithContext<AppInstruction>,
+    events: EventSender,
+    input_handlers: BTreeMap<ClientId, InputHandler>,
+    /// The client that can change the session and sent some input last, if it's still attached.
+    last_active_writer: Option<ClientId>,
+}
+
+impl ClientRouter {
+    fn route_client_messages(&mut self, first_client_id: ClientId) {
+        let mut err_ctx = OPENCALLS.with(|ctx| *ctx.borrow());
+        err_ctx.add_call(ContextType::IPCServer);
+        self.send_screen_instructions.update(err_ctx);
+        self.send_pty_instructions.update(err_ctx);
+        self.send_plugin_instructions.update(err_ctx);
+        self.send_app_instructions.update(err_ctx);
+
+        // the first client is already known to the screen and the app state
+        let input_handler = self.new_input_handler(first_client_id, ClientType::Writer);
+        self.input_handlers.insert(first_client_id, input_handler);
+
+        loop {
+            let (client_id, msg) = match self.recv_from_client() {
+                Some(client_msg) => client_msg,
+                None => {
+                    self.handle_expired_pending_keys();
+                    continue;
+                }
+            };
+            match msg {
+                ClientToServerMsg::ListSessions => {
+                    let mut sessions = HashSet::new();
+                    sessions.insert(self.session_info());
+                    self.respond(
+                        client_id,
+                        Response::Data(ResponseData::SessionList(sessions)),
+                    );
+                }
+                ClientToServerMsg::CreateSession(_) => {
+                    // a server only ever holds the one session it was started for
+                    self.respond(
+                        client_id,
+                        Response::Error("a session is already running on this server".to_string()),
+                    );
+                }
+                ClientToServerMsg::AttachToSession(session_id, client_type, client_size) => {
+                    if session_id == self.session.id {
+                        self.attach_client(client_id, client_type, client_size);
+                    } else {
+                        self.respond(client_id, no_such_session(session_id));
+                    }
+                }
+                ClientToServerMsg::DetachSession(session_id) => {
+                    if session_id == self.session.id {
+                        let attached_clients: Vec<ClientId> =
+                            self.input_handlers.keys().copied().collect();
+                        for attached_client_id in attached_clients {
+                            self.os_input.send_to_client(
+                                attached_client_id,
+                                ServerToClientMsg::Exit(ExitReason::ForceDetached),
+                            );
+                            self.detach_client(attached_client_id);
+                        }
+                        self.respond(client_id, Response::Ok);
+                    } else {
+                        self.respond(client_id, no_such_session(session_id));
+                    }
+                }
+                ClientToServerMsg::DisconnectFromSession => {
+                    self.events.unsubscribe(client_id);
+                    self.detach_client(client_id);
+                }
+                ClientToServerMsg::KillSession => {
+                    self.respond(client_id, Response::Ok);
+                    // the same as quitting, the pty thread kills the panes' processes on its
+                    // way out
+                    self.send_app_instructions
+                        .send(AppInstruction::Exit)
+                        .unwrap();
+                }
+                ClientToServerMsg::TerminalResize(new_size) => {
+                    if self.input_handlers.contains_key(&client_id) {
+                        self.send_screen_instructions
+                            .send(ScreenInstruction::TerminalResize(client_id, new_size))
+                            .unwrap();
+                    }
+                }
+                ClientToServerMsg::Input(stdin_buffer) => {
+                    if let Some(input_handler) = self.input_handlers.get_mut(&client_id) {
+                        if input_handler.client_type() == ClientType::Writer {
+                            self.last_active_writer = Some(client_id);
+                        }
+                        if input_handler.handle_input(&stdin_buffer) {
+                            self.detach_client(client_id);
+                        }
+                        self.os_input
+                            .send_to_client(client_id, ServerToClientMsg::UnblockInputThread);
+                    }
+                }
+                ClientToServerMsg::Command(api_command) => {
+                    self.handle_command(api_command);
+                    self.respond(client_id, Response::Ok);
+                }
+                ClientToServerMsg::Action(action) => {
+                    let response = match self.handle_action(client_id, action) {
+                        Ok(()) => Response::Ok,
+                        Err(e) => Response::Error(e),
+                    };
+                    self.respond(client_id, response);
+                }
+                ClientToServerMsg::DumpScreen(pane_id, options) => {
+                    let response = match self.dump_pane(pane_id, options) {
+                        Ok(contents) => Response::Data(ResponseData::PaneContents(contents)),
+                        Err(e) => Response::Error(e),
+                    };
+                    self.respond(client_id, response);
+                }
+                ClientToServerMsg::QuerySession => {
+                    let response = match self.session_structure() {
+                        Some(structure) => {
+                            Response::Data(ResponseData::SessionStructure(structure))
+                        }
+                        None => Response::Error("the session is ending".to_string()),
+                    };
+                    self.respond(client_id, response);
+                }
+                ClientToServerMsg::SendKeys(pane_target, input) => {
+                    let response = match self.send_keys(pane_target, input) {
+                        Ok(()) => Response::Ok,
+                        Err(e) => Response::Error(e),
+                    };
+                    self.respond(client_id, response);
+                }
+                ClientToServerMsg::Subscribe => {
+                    self.respond(client_id, Response::Ok);
+                    self.events.subscribe(client_id);
+                }
+                ClientToServerMsg::Run(run_command, direction) => {
+                    let response = match self.run_command(client_id, run_command, direction) {
+                        Some(pane_id) => Response::Data(ResponseData::PaneId(pane_id)),
+                        None => Response::Error("the pane could not be opened".to_string()),
+                    };
+                    self.respond(client_id, response);
+                }
+            }
+        }
+    }
+
+    /// Blocks until a message is received from any of the clients, or until the keys of a
+    /// sequence that one of them started stop waiting for the rest of it, returning `None`.
+    fn recv_from_client(&self) -> Option<(ClientId, ClientToServerMsg)> {
+        let next_deadline = self
+            .input_handlers
+            .values()
+            .filter_map(InputHandler::pending_keys_deadline)
+            .min();
+        match next_deadline {
+            Some(deadline) => self
+                .os_input
+                .recv_from_client_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => Some(self.os_input.recv_from_client()),
+        }
+    }
+
+    /// Handles the keys of the sequences that waited too long for the rest of them.
+    fn handle_expired_pending_keys(&mut self) {
+        let leaving_clients: Vec<ClientId> = self
+            .input_handlers
+            .iter_mut()
+            .filter_map(|(client_id, input_handler)| {
+                if input_handler.handle_expired_pending_keys() {
+                    Some(*client_id)
+                } else {
+                    None
+                }
+            })
+            .collect();
+        for client_id in leaving_clients {
+            self.detach_client(client_id);
+        }
+    }
+
+    /// Answers the last request of the client with id `client_id`.
+    fn respond(&self, client_id: ClientId, response: Response) {
+        self.os_input
+            .send_to_client(client_id, ServerToClientMsg::Response(response));
+    }
+
+    /// Returns the current state of the session.
+    fn session_info(&self) -> Session {
+        let (tab_count_tx, tab_count_rx) = mpsc::channel();
+        // the screen might already be gone if the session is ending
+        let tab_count = match self
+            .send_screen_instructions
+            .send(ScreenInstruction::GetTabCount(tab_count_tx))
+        {
+            Ok(()) => tab_count_rx.recv().unwrap_or_default(),
+            Err(_) => 0,
+        };
+        Session {
+            tab_count,
+            client_count: self.input_handlers.len(),
+            ..self.session.clone()
+        }
+    }
+
+    fn new_input_handler(&self, client_id: ClientId, client_type: ClientType) -> InputHandler {
+        InputHandler::new(
+            client_id,
+            client_type,
+            self.config.keybinds.clone(),
+            self.config.key_sequence_timeout,
+            self.config.unbound_keys.clone(),
+            self.os_input.clone(),
+            self.command_is_executing.clone(),
+            self.send_screen_instructions.clone(),
+            self.send_pty_instructions.clone(),
+            self.send_plugin_instructions.clone(),
+            self.send_app_instructions.clone(),
+            self.events.clone(),
+        )
+    }
+
+    fn attach_client(
+        &mut self,
+        client_id: ClientId,
+        client_type: ClientType,
+        client_size: PositionAndSize,
+    ) {
+        let input_handler = self.new_input_handler(client_id, client_type);
+        self.input_handlers.insert(client_id, input_handler);
+        update_state(&self.send_app_instructions, |mut state| {
+            state.clients.insert(client_id, client_type);
+            state
+        });
+        self.respond(
+            client_id,
+            Response::Data(ResponseData::SessionInfo(self.session_info())),
+        );
+        self.send_screen_instructions
+            .send(ScreenInstruction::AddClient(client_id, client_size))
+            .unwrap();
+    }
+
+    fn detach_client(&mut self, client_id: ClientId) {
+        let input_handler = match self.input_handlers.remove(&client_id) {
+            Some(input_handler) => input_handler,
+            // this client was never attached (eg. a one-shot command from the CLI)
+            None => return,
+        };
+        if self.last_active_writer == Some(client_id) {
+            self.last_active_writer = None;
+        }
+        update_state(&self.send_app_instructions, |mut state| {
+            state.clients.remove(&client_id);
+            if input_handler.pending_keys_deadline().is_some() {
+                state.pending_keys.clear();
+            }
+            state
+        });
+        // the screen might already be gone if the session is ending
+        let _ = self
+            .send_screen_instructions
+            .send(ScreenInstruction::RemoveClient(client_id));
+    }
+
+    /// Returns the writer that was used last if it's still attached, or else any attached writer.
+    fn active_writer(&self) -> Option<ClientId> {
+        let input_handlers = &self.input_handlers;
+        self.last_active_writer
+            .filter(|writer_id| input_handlers.contains_key(writer_id))
+            .or_else(|| {
+                input_handlers
+                    .iter()
+                    .find(|(_, input_handler)| input_handler.client_type() == ClientType::Writer)
+                    .map(|(writer_id, _)| *writer_id)
+            })
+    }
+
+    /// Dispatches an action sent with `zellij action` through the input handler of the
+    /// [active writer](Self::active_writer), so that it acts on that client's tab and input mode.
+    /// With no writers attached, the sender acts as a writer of its own, except that there's no
+    /// input mode to switch and no client to detach then, so those actions fail.
+    fn handle_action(&mut self, client_id: ClientId, action: Action) -> Result<(), String> {
+        match (self.active_writer(), action) {
+            (Some(writer_id), action) => {
+                let input_handler = self.input_handlers.get_mut(&writer_id).unwrap();
+                if input_handler.handle_action(action) {
+                    self.detach_client(writer_id);
+                }
+            }
+            (None, Action::SwitchToMode(_)) | (None, Action::Detach) => {
+                return Err("no client is attached to the session".to_string());
+            }
+            (None, action) => {
+                let mut input_handler = self.new_input_handler(client_id, ClientType::Writer);
+                input_handler.handle_action(action);
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens a new pane running `run_command` through the input handler of the
+    /// [active writer](Self::active_writer), the same way as [`handle_action`](Self::handle_action)
+    /// would, returning its id.
+    fn run_command(
+        &mut self,
+        client_id: ClientId,
+        run_command: RunCommand,
+        direction: Option<Direction>,
+    ) -> Option<PaneId> {
+        match self.active_writer() {
+            Some(writer_id) => self
+                .input_handlers
+                .get_mut(&writer_id)
+                .unwrap()
+                .run_command(run_command, direction),
+            None => self
+                .new_input_handler(client_id, ClientType::Writer)
+                .run_command(run_command, direction),
+        }
+    }
+
+    /// Returns the contents of the pane with id `pane_id`, or else of the focused pane of the tab
+    /// the [active writer](Self::active_writer) is looking at.
+    fn dump_pane(&self, pane_id: Option<PaneId>, options: DumpOptions) -> Result<String, String> {
+        let (dump_tx, dump_rx) = mpsc::channel();
+        // the screen might already be gone if the session is ending
+        let _ = self
+            .send_screen_instructions
+            .send(ScreenInstruction::DumpPane(
+                pane_id,
+                self.active_writer(),
+                options,
+                dump_tx,
+            ));
+        dump_rx
+            .recv()
+            .unwrap_or_else(|_| Err("the session is ending".to_string()))
+    }
+
+    /// Returns the structure of the session, as seen by the
+    /// [active writer](Self::active_writer).
+    fn session_structure(&self) -> Option<SessionStructure> {
+        let (structure_tx, structure_rx) = mpsc::channel();
+        // the screen might already be gone if the session is ending
+        let _ = self
+            .send_screen_instructions
+            .send(ScreenInstruction::GetSessionStructure(
+                self.active_writer(),
+                structure_tx,
+            ));
+        structure_rx.recv().ok()
+    }
+
+    /// Writes `input` to the pane `pane_target`, or else to the focused pane of the tab the
+    /// [active writer](Self::active_writer) is looking at.
+    fn send_keys(
+        &self,
+        pane_target: Option<PaneTarget>,
+        input: Vec<Vec<u8>>,
+    ) -> Result<(), String> {
+        let (sent_tx, sent_rx) = mpsc::channel();
+        // the screen might already be gone if the session is ending
+        let _ = self
+            .send_screen_instructions
+            .send(ScreenInstruction::SendKeys(
+                pane_target,
+                self.active_writer(),
+                input,
+                sent_tx,
+            ));
+        sent_rx
+            .recv()
+            .unwrap_or_else(|_| Err("the session is ending".to_string()))
+    }
+
+    fn handle_command(&mut self, api_command: ApiCommand) {
+        match api_command {
+            ApiCommand::OpenFile(path) => {
+                self.send_pty_instructions
+                    .send(PtyInstruction::SpawnTerminal(Some(
+                        TerminalAction::OpenFile(path),
+                    )))
+                    .unwrap();
+            }
+            ApiCommand::SplitHorizontally => {
+                self.send_pty_instructions
+                    .send(PtyInstruction::SpawnTerminalHorizontally(None))
+                    .unwrap();
+            }
+            ApiCommand::SplitVertically => {
+                self.send_pty_instructions
+                    .send(PtyInstruction::SpawnTerminalVertically(None))
+                    .unwrap();
+            }
+            ApiCommand::MoveFocus => {
+                self.send_screen_instructions
+                    .send(ScreenInstruction::MoveFocus)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+/// The answer to a request about a session that isn't the one held by this server.
+fn no_such_session(session_id: SessionID) -> Response {
+    Response::Error(format!("no session with id {}", session_id))
+}
@@ -1,17 +1,30 @@
+use crate::common::events::Event;
+use crate::common::input::actions::Action;
+use crate::common::ipc::{ClientId, ClientToServerMsg, ExitReason, Response, ServerToClientMsg};
 use crate::panes::PositionAndSize;
+use crate::pty_bus::{ExitStatus, TerminalAction};
 use std::collections::{HashMap, VecDeque};
-use std::io::Write;
+use std::io::{self, Write};
 use std::os::unix::io::RawFd;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use crate::os_input_output::OsApi;
 use crate::tests::possible_tty_inputs::{get_possible_tty_inputs, Bytes};
+use crate::tests::utils::commands::{CLI_ACTION, OTHER_CLIENT, SIGWINCH, TERMINAL_EXIT};
 
 const MIN_TIME_BETWEEN_SNAPSHOTS: Duration = Duration::from_millis(50);
 
+/// The id of the client of the fake terminal.
+const TERMINAL_CLIENT_ID: ClientId = 0;
+
+/// The id of the command line client that sends the requests added with
+/// [`FakeInputOutput::add_cli_requests`], next to the client of the fake terminal.
+const CLI_CLIENT_ID: ClientId = 1;
+
 #[derive(Clone)]
 pub enum IoEvent {
     Kill(RawFd),
@@ -64,13 +77,36 @@ pub struct FakeInputOutput {
     read_buffers: Arc<Mutex<HashMap<RawFd, Bytes>>>,
     input_to_add: Arc<Mutex<Option<Vec<[u8; 10]>>>>,
     stdin_commands: Arc<Mutex<VecDeque<Vec<u8>>>>,
-    stdin_writes: Arc<Mutex<HashMap<RawFd, Vec<u8>>>>,
+    pub stdin_writes: Arc<Mutex<HashMap<RawFd, Vec<u8>>>>,
     pub stdout_writer: FakeStdoutWriter, // stdout_writer.output is already an arc/mutex
     io_events: Arc<Mutex<Vec<IoEvent>>>,
     win_sizes: Arc<Mutex<HashMap<RawFd, PositionAndSize>>>,
     possible_tty_inputs: HashMap<u16, Bytes>,
     last_snapshot_time: Arc<Mutex<Instant>>,
     started_reading_from_pty: Arc<AtomicBool>,
+    send_to_server: Arc<Mutex<Sender<(ClientId, ClientToServerMsg)>>>,
+    receive_from_client: Arc<Mutex<Receiver<(ClientId, ClientToServerMsg)>>>,
+    send_to_client: Arc<Mutex<Sender<ServerToClientMsg>>>,
+    receive_from_server: Arc<Mutex<Receiver<ServerToClientMsg>>>,
+    sigwinch_event: Option<PositionAndSize>,
+    trigger_sigwinch: Arc<Mutex<Sender<()>>>,
+    receive_sigwinch_trigger: Arc<Mutex<Receiver<()>>>,
+    sigwinch_handled: Arc<Mutex<Sender<()>>>,
+    receive_sigwinch_handled: Arc<Mutex<Receiver<()>>>,
+    cli_requests: Arc<Mutex<VecDeque<ClientToServerMsg>>>,
+    pub cli_responses: Arc<Mutex<Vec<Response>>>,
+    /// The events sent to the command line, once one of its requests subscribed to them.
+    pub cli_events: Arc<Mutex<Vec<Event>>>,
+    other_client_messages: Arc<Mutex<VecDeque<(ClientId, ClientToServerMsg)>>>,
+    /// What was rendered to each of the other clients, a frame for every render.
+    pub other_client_output_frames: Arc<Mutex<HashMap<ClientId, Vec<Vec<u8>>>>>,
+    other_client_messages_after_exit: Arc<Mutex<Vec<(ClientId, ClientToServerMsg)>>>,
+    /// Why each of the clients that were told to exit was, the fake terminal's one included.
+    pub client_exits: Arc<Mutex<HashMap<ClientId, ExitReason>>>,
+    terminal_exits: Arc<Mutex<VecDeque<(RawFd, ExitStatus)>>>,
+    exited_terminals: Arc<Mutex<HashMap<RawFd, ExitStatus>>>,
+    exit_noticed: Arc<Mutex<Sender<()>>>,
+    receive_exit_noticed: Arc<Mutex<Receiver<()>>>,
 }
 
 impl FakeInputOutput {
@@ -79,6 +115,11 @@ impl FakeInputOutput {
         let last_snapshot_time = Arc::new(Mutex::new(Instant::now()));
         let stdout_writer = FakeStdoutWriter::new(last_snapshot_time.clone());
         win_sizes.insert(0, winsize); // 0 is the current terminal
+        let (send_to_server, receive_from_client) = channel();
+        let (send_to_client, receive_from_server) = channel();
+        let (trigger_sigwinch, receive_sigwinch_trigger) = channel();
+        let (sigwinch_handled, receive_sigwinch_handled) = channel();
+        let (exit_noticed, receive_exit_noticed) = channel();
         FakeInputOutput {
             read_buffers: Arc::new(Mutex::new(HashMap::new())),
             stdin_writes: Arc::new(Mutex::new(HashMap::new())),
@@ -90,6 +131,26 @@ impl FakeInputOutput {
             win_sizes: Arc::new(Mutex::new(win_sizes)),
             possible_tty_inputs: get_possible_tty_inputs(),
             started_reading_from_pty: Arc::new(AtomicBool::new(false)),
+            send_to_server: Arc::new(Mutex::new(send_to_server)),
+            receive_from_client: Arc::new(Mutex::new(receive_from_client)),
+            send_to_client: Arc::new(Mutex::new(send_to_client)),
+            receive_from_server: Arc::new(Mutex::new(receive_from_server)),
+            sigwinch_event: None,
+            trigger_sigwinch: Arc::new(Mutex::new(trigger_sigwinch)),
+            receive_sigwinch_trigger: Arc::new(Mutex::new(receive_sigwinch_trigger)),
+            sigwinch_handled: Arc::new(Mutex::new(sigwinch_handled)),
+            receive_sigwinch_handled: Arc::new(Mutex::new(receive_sigwinch_handled)),
+            cli_requests: Arc::new(Mutex::new(VecDeque::new())),
+            cli_responses: Arc::new(Mutex::new(vec![])),
+            cli_events: Arc::new(Mutex::new(vec![])),
+            other_client_messages: Arc::new(Mutex::new(VecDeque::new())),
+            other_client_output_frames: Arc::new(Mutex::new(HashMap::new())),
+            other_client_messages_after_exit: Arc::new(Mutex::new(vec![])),
+            client_exits: Arc::new(Mutex::new(HashMap::new())),
+            terminal_exits: Arc::new(Mutex::new(VecDeque::new())),
+            exited_terminals: Arc::new(Mutex::new(HashMap::new())),
+            exit_noticed: Arc::new(Mutex::new(exit_noticed)),
+            receive_exit_noticed: Arc::new(Mutex::new(receive_exit_noticed)),
         }
     }
     pub fn with_tty_inputs(mut self, tty_inputs: HashMap<u16, Bytes>) -> Self {
@@ -103,6 +164,65 @@ impl FakeInputOutput {
         }
         self.stdin_commands = Arc::new(Mutex::new(stdin_commands));
     }
+    /// Resizes the terminal to `new_position_and_size` when the [`SIGWINCH`] command is read
+    /// from its input.
+    pub fn add_sigwinch_event(&mut self, new_position_and_size: PositionAndSize) {
+        self.sigwinch_event = Some(new_position_and_size);
+    }
+    /// Sends these actions to the server from the command line, one for every [`CLI_ACTION`]
+    /// command read from the terminal's input.
+    pub fn add_cli_actions(&mut self, actions: Vec<Action>) {
+        self.add_cli_requests(actions.into_iter().map(ClientToServerMsg::Action).collect());
+    }
+    /// Sends these requests to the server from the command line, one for every [`CLI_ACTION`]
+    /// command read from the terminal's input. The server's answers end up in
+    /// [`Self::cli_responses`].
+    pub fn add_cli_requests(&mut self, requests: Vec<ClientToServerMsg>) {
+        self.cli_requests = Arc::new(Mutex::new(requests.into_iter().collect()));
+    }
+    /// Sends these messages to the server from other clients than the ones of the fake terminal
+    /// and of the command line, with the given ids, one for every [`OTHER_CLIENT`] command read
+    /// from the terminal's input. What's rendered to them ends up in
+    /// [`Self::other_client_output_frames`].
+    pub fn add_other_client_messages(&mut self, messages: Vec<(ClientId, ClientToServerMsg)>) {
+        self.other_client_messages = Arc::new(Mutex::new(messages.into_iter().collect()));
+    }
+    /// Sends these messages to the server from other clients, like
+    /// [`Self::add_other_client_messages`] does, all at once when the client of the fake terminal
+    /// is told to exit (eg. because it detached). The session keeps running without the fake
+    /// terminal then, so these should end it (eg. with [`ClientToServerMsg::KillSession`]).
+    pub fn add_other_client_messages_after_exit(
+        &mut self,
+        messages: Vec<(ClientId, ClientToServerMsg)>,
+    ) {
+        self.other_client_messages_after_exit = Arc::new(Mutex::new(messages));
+    }
+    /// Returns how many frames were rendered to the other client with id `client_id`.
+    fn other_client_frame_count(&self, client_id: ClientId) -> usize {
+        self.other_client_output_frames
+            .lock()
+            .unwrap()
+            .get(&client_id)
+            .map_or(0, |frames| frames.len())
+    }
+    /// Makes what runs in these terminals exit with these statuses, one for every
+    /// [`TERMINAL_EXIT`] command read from the terminal's input. Until then, the output of the
+    /// terminals never ends.
+    pub fn add_terminal_exits(&mut self, exits: Vec<(RawFd, ExitStatus)>) {
+        self.terminal_exits = Arc::new(Mutex::new(exits.into_iter().collect()));
+    }
+    /// Returns the processes that were killed, in the order they were.
+    pub fn killed_processes(&self) -> Vec<RawFd> {
+        self.io_events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|io_event| match io_event {
+                IoEvent::Kill(pid) => Some(*pid),
+                _ => None,
+            })
+            .collect()
+    }
     pub fn add_terminal(&mut self, fd: RawFd) {
         self.stdin_writes.lock().unwrap().insert(fd, vec![]);
     }
@@ -119,10 +239,11 @@ impl OsApi for FakeInputOutput {
             .possible_tty_inputs
             .get(&cols)
             .expect(&format!("could not find input for size {:?}", cols));
-        self.read_buffers
-            .lock()
-            .unwrap()
-            .insert(pid, terminal_input.clone());
+        let mut read_buffers = self.read_buffers.lock().unwrap();
+        // what runs in the terminal doesn't print its output again once it printed it
+        if !matches!(read_buffers.get(&pid), Some(bytes) if bytes.read_position > 0) {
+            read_buffers.insert(pid, terminal_input.clone());
+        }
         self.io_events
             .lock()
             .unwrap()
@@ -140,27 +261,44 @@ impl OsApi for FakeInputOutput {
             .unwrap()
             .push(IoEvent::UnsetRawMode(pid));
     }
-    fn spawn_terminal(&mut self, _file_to_open: Option<PathBuf>) -> (RawFd, RawFd) {
+    fn spawn_terminal(
+        &mut self,
+        _terminal_action: Option<TerminalAction>,
+        _cwd: Option<PathBuf>,
+    ) -> (RawFd, RawFd) {
         let next_terminal_id = self.stdin_writes.lock().unwrap().keys().len() as RawFd + 1;
         self.add_terminal(next_terminal_id);
-        (next_terminal_id as i32, next_terminal_id + 1000) // secondary number is arbitrary here
+        (next_terminal_id as i32, next_terminal_id + 1000) // stands for the pid of what runs in it
+    }
+    fn get_cwd(&self, _fd: RawFd) -> Option<PathBuf> {
+        None
+    }
+    fn get_foreground_command(&self, _fd: RawFd) -> Option<String> {
+        None
     }
     fn read_from_tty_stdout(&mut self, pid: RawFd, buf: &mut [u8]) -> Result<usize, nix::Error> {
         let mut read_buffers = self.read_buffers.lock().unwrap();
         let mut bytes_read = 0;
-        match read_buffers.get_mut(&pid) {
-            Some(bytes) => {
-                for i in bytes.read_position..bytes.content.len() {
-                    bytes_read += 1;
-                    buf[i] = bytes.content[i];
-                }
-                if bytes_read > bytes.read_position {
-                    bytes.set_read_position(bytes_read);
-                }
-                self.started_reading_from_pty.store(true, Ordering::Release);
-                return Ok(bytes_read);
+        if let Some(bytes) = read_buffers.get_mut(&pid) {
+            for i in bytes.read_position..bytes.content.len() {
+                bytes_read += 1;
+                buf[i] = bytes.content[i];
             }
-            None => Err(nix::Error::Sys(nix::errno::Errno::EAGAIN)),
+            if bytes_read > bytes.read_position {
+                bytes.set_read_position(bytes_read);
+            }
+            self.started_reading_from_pty.store(true, Ordering::Release);
+        }
+        if bytes_read > 0 {
+            Ok(bytes_read)
+        } else if read_buffers.contains_key(&pid)
+            && self.exited_terminals.lock().unwrap().contains_key(&pid)
+        {
+            // like a real terminal, whose output ends once what runs in it printed everything
+            // and exited
+            Ok(0)
+        } else {
+            Err(nix::Error::Sys(nix::errno::Errno::EAGAIN))
         }
     }
     fn write_to_tty_stdin(&mut self, pid: RawFd, buf: &mut [u8]) -> Result<usize, nix::Error> {
@@ -189,11 +327,84 @@ impl OsApi for FakeInputOutput {
                 ::std::thread::sleep(MIN_TIME_BETWEEN_SNAPSHOTS - last_snapshot_time.elapsed());
             }
         }
-        self.stdin_commands
+        let command = self
+            .stdin_commands
             .lock()
             .unwrap()
             .pop_front()
-            .unwrap_or(vec![])
+            .unwrap_or(vec![]);
+        if command == SIGWINCH {
+            // make sure the resize reaches the server before the next input does
+            self.trigger_sigwinch.lock().unwrap().send(()).unwrap();
+            self.receive_sigwinch_handled
+                .lock()
+                .unwrap()
+                .recv()
+                .unwrap();
+            return vec![];
+        }
+        if command == TERMINAL_EXIT {
+            let (terminal, status) = self.terminal_exits.lock().unwrap().pop_front().unwrap();
+            self.exited_terminals
+                .lock()
+                .unwrap()
+                .insert(terminal, status);
+            // make sure the exit is noticed, and give the server the time to handle it, before
+            // the next input reaches it
+            self.receive_exit_noticed.lock().unwrap().recv().unwrap();
+            *self.last_snapshot_time.lock().unwrap() = Instant::now();
+            return vec![];
+        }
+        if command == CLI_ACTION {
+            // the terminal's (empty) input only reaches the server after the action does
+            let request = self.cli_requests.lock().unwrap().pop_front().unwrap();
+            self.send_to_server
+                .lock()
+                .unwrap()
+                .send((CLI_CLIENT_ID, request))
+                .unwrap();
+            return vec![];
+        }
+        if command == OTHER_CLIENT {
+            // like the command line's requests, reaches the server before the terminal's input
+            let (client_id, message) = self
+                .other_client_messages
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap();
+            let is_attaching = matches!(message, ClientToServerMsg::AttachToSession(..));
+            let frame_count = self.other_client_frame_count(client_id);
+            self.send_to_server
+                .lock()
+                .unwrap()
+                .send((client_id, message))
+                .unwrap();
+            if is_attaching {
+                // make sure the client is attached, and was shown the session, before the next
+                // input reaches the server
+                while self.other_client_frame_count(client_id) == frame_count {
+                    ::std::thread::sleep(Duration::from_millis(10));
+                }
+            }
+            return vec![];
+        }
+        command
+    }
+    fn receive_sigwinch(&self, cb: Box<dyn Fn()>) {
+        if let Some(new_position_and_size) = self.sigwinch_event {
+            self.receive_sigwinch_trigger
+                .lock()
+                .unwrap()
+                .recv()
+                .unwrap();
+            self.win_sizes
+                .lock()
+                .unwrap()
+                .insert(0, new_position_and_size);
+            cb();
+            self.sigwinch_handled.lock().unwrap().send(()).unwrap();
+        }
     }
     fn get_stdout_writer(&self) -> Box<dyn Write> {
         Box::new(self.stdout_writer.clone())
@@ -202,4 +413,102 @@ impl OsApi for FakeInputOutput {
         self.io_events.lock().unwrap().push(IoEvent::Kill(fd));
         Ok(())
     }
+    fn wait_for_exit(&self, pid: RawFd) -> Option<ExitStatus> {
+        // the output of a terminal only ends once it was made to exit, and the secondary number
+        // of a terminal is its id plus 1000 (see spawn_terminal)
+        let status = self
+            .exited_terminals
+            .lock()
+            .unwrap()
+            .get(&(pid - 1000))
+            .copied();
+        let _ = self.exit_noticed.lock().unwrap().send(());
+        status
+    }
+    // there is a single, in-process client in tests, next to the command line client and the
+    // other clients, whose messages are kept apart
+    fn bind(&mut self, _socket_path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+    fn serve(&mut self, _socket_path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+    fn recv_from_client(&self) -> (ClientId, ClientToServerMsg) {
+        self.receive_from_client.lock().unwrap().recv().unwrap()
+    }
+    fn recv_from_client_timeout(&self, timeout: Duration) -> Option<(ClientId, ClientToServerMsg)> {
+        match self
+            .receive_from_client
+            .lock()
+            .unwrap()
+            .recv_timeout(timeout)
+        {
+            Ok(client_msg) => Some(client_msg),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => panic!("the clients are gone"),
+        }
+    }
+    fn send_to_client(&self, client_id: ClientId, msg: ServerToClientMsg) -> bool {
+        match msg {
+            ServerToClientMsg::Response(response) if client_id == CLI_CLIENT_ID => {
+                self.cli_responses.lock().unwrap().push(response);
+                true
+            }
+            ServerToClientMsg::Event(event) if client_id == CLI_CLIENT_ID => {
+                self.cli_events.lock().unwrap().push(event);
+                true
+            }
+            ServerToClientMsg::Exit(reason) if client_id == TERMINAL_CLIENT_ID => {
+                self.client_exits
+                    .lock()
+                    .unwrap()
+                    .insert(client_id, reason.clone());
+                let send_to_server = self.send_to_server.lock().unwrap();
+                for message in self
+                    .other_client_messages_after_exit
+                    .lock()
+                    .unwrap()
+                    .drain(..)
+                {
+                    send_to_server.send(message).unwrap();
+                }
+                self.send_to_client
+                    .lock()
+                    .unwrap()
+                    .send(ServerToClientMsg::Exit(reason))
+                    .is_ok()
+            }
+            msg if client_id == TERMINAL_CLIENT_ID || client_id == CLI_CLIENT_ID => {
+                self.send_to_client.lock().unwrap().send(msg).is_ok()
+            }
+            ServerToClientMsg::Render(output) => {
+                self.other_client_output_frames
+                    .lock()
+                    .unwrap()
+                    .entry(client_id)
+                    .or_default()
+                    .push(output.into_bytes());
+                true
+            }
+            ServerToClientMsg::Exit(reason) => {
+                self.client_exits.lock().unwrap().insert(client_id, reason);
+                true
+            }
+            _ => true,
+        }
+    }
+    fn disconnect_clients(&self) {}
+    fn connect_to_server(&mut self, _socket_path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+    fn send_to_server(&self, msg: ClientToServerMsg) {
+        let _ = self
+            .send_to_server
+            .lock()
+            .unwrap()
+            .send((TERMINAL_CLIENT_ID, msg));
+    }
+    fn recv_from_server(&self) -> Option<ServerToClientMsg> {
+        self.receive_from_server.lock().unwrap().recv().ok()
+    }
 }
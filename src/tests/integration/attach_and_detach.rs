@@ -0,0 +1,165 @@
+use insta::assert_snapshot;
+
+use crate::common::ipc::{
+    ClientId, ClientToServerMsg, ClientType, ExitReason, Response, ResponseData,
+};
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{
+    CLI_ACTION, COMMAND_TOGGLE, DETACH_IN_COMMAND_MODE, ESC, OTHER_CLIENT, PANE_MODE, QUIT,
+    SPLIT_RIGHT_IN_PANE_MODE,
+};
+use crate::tests::utils::get_output_frame_snapshots;
+use crate::{start, CliArgs};
+
+/// The id of the client of the fake terminal.
+const TERMINAL_CLIENT_ID: ClientId = 0;
+/// The id of a client attached next to the one of the fake terminal.
+const OTHER_CLIENT_ID: ClientId = 2;
+/// The id of the client that attaches to the session after the others left it.
+const REATTACHED_CLIENT_ID: ClientId = 3;
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+fn session_id() -> u64 {
+    // tests run the server in-process, so the session has the id of the test's process
+    std::process::id() as u64
+}
+
+fn attach(client_size: PositionAndSize) -> ClientToServerMsg {
+    ClientToServerMsg::AttachToSession(session_id(), ClientType::Writer, client_size)
+}
+
+/// Returns the snapshot of what was rendered to the client with id `client_id` when it attached.
+fn snapshot_on_attach(
+    fake_input_output: &FakeInputOutput,
+    client_id: ClientId,
+    client_size: &PositionAndSize,
+) -> String {
+    let output_frames = fake_input_output.other_client_output_frames.lock().unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames[&client_id], client_size);
+    snapshots.first().cloned().expect("could not find snapshot")
+}
+
+#[test]
+pub fn detached_session_can_be_reattached() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &ESC,
+        &DETACH_IN_COMMAND_MODE,
+    ]);
+    fake_input_output.add_other_client_messages_after_exit(vec![
+        (REATTACHED_CLIENT_ID, attach(fake_win_size)),
+        (REATTACHED_CLIENT_ID, ClientToServerMsg::KillSession),
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let client_exits = fake_input_output.client_exits.lock().unwrap();
+    assert_eq!(client_exits[&TERMINAL_CLIENT_ID], ExitReason::Detached);
+    // both panes are still there
+    assert_snapshot!(snapshot_on_attach(
+        &fake_input_output,
+        REATTACHED_CLIENT_ID,
+        &fake_win_size
+    ));
+}
+
+#[test]
+pub fn force_detach_detaches_every_client() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![
+        (OTHER_CLIENT_ID, attach(fake_win_size)),
+        (
+            REATTACHED_CLIENT_ID,
+            ClientToServerMsg::DetachSession(session_id()),
+        ),
+    ]);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &ESC,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+    ]);
+    // the client that detached the others attaches in their place, like `zellij attach --force`
+    fake_input_output.add_other_client_messages_after_exit(vec![
+        (REATTACHED_CLIENT_ID, attach(fake_win_size)),
+        (REATTACHED_CLIENT_ID, ClientToServerMsg::KillSession),
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let client_exits = fake_input_output.client_exits.lock().unwrap();
+    assert_eq!(client_exits[&TERMINAL_CLIENT_ID], ExitReason::ForceDetached);
+    assert_eq!(client_exits[&OTHER_CLIENT_ID], ExitReason::ForceDetached);
+    // the one that detached the others is only told to exit once it ends the session
+    assert_eq!(client_exits[&REATTACHED_CLIENT_ID], ExitReason::Normal);
+    assert_snapshot!(snapshot_on_attach(
+        &fake_input_output,
+        REATTACHED_CLIENT_ID,
+        &fake_win_size
+    ));
+}
+
+#[test]
+pub fn disconnected_client_can_reattach() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    // the other client goes away without detaching (eg. its terminal was closed), and then
+    // attaches again
+    fake_input_output.add_other_client_messages(vec![
+        (OTHER_CLIENT_ID, attach(fake_win_size)),
+        (OTHER_CLIENT_ID, ClientToServerMsg::DisconnectFromSession),
+        (REATTACHED_CLIENT_ID, attach(fake_win_size)),
+    ]);
+    fake_input_output.add_cli_requests(vec![ClientToServerMsg::ListSessions]);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &ESC,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &CLI_ACTION,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let client_counts: Vec<usize> = match fake_input_output.cli_responses.lock().unwrap().as_slice()
+    {
+        [Response::Data(ResponseData::SessionList(sessions))] => sessions
+            .iter()
+            .map(|session| session.client_count)
+            .collect(),
+        responses => panic!("unexpected responses: {:?}", responses),
+    };
+    assert_eq!(client_counts, vec![2]);
+    assert_snapshot!(snapshot_on_attach(
+        &fake_input_output,
+        REATTACHED_CLIENT_ID,
+        &fake_win_size
+    ));
+}
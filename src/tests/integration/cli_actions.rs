@@ -0,0 +1,340 @@
+use insta::assert_snapshot;
+use std::path::PathBuf;
+
+use crate::common::input::actions::{Action, Direction};
+use crate::common::input::handler::InputMode;
+use crate::common::ipc::{ClientToServerMsg, Response, ResponseData};
+use crate::panes::{PaneId, PositionAndSize};
+use crate::pty_bus::{ExitStatus, RunCommand};
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{
+    CLI_ACTION, CLOSE_HELD_PANE, COMMAND_TOGGLE, PANE_MODE, QUIT, RERUN_HELD_PANE,
+    SPLIT_RIGHT_IN_PANE_MODE, TERMINAL_EXIT,
+};
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+fn held_command() -> Action {
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".to_string()],
+        cwd: None,
+        env: vec![],
+        hold_on_exit: true,
+    };
+    Action::Run(run_command, Some(Direction::Down))
+}
+
+#[test]
+pub fn new_pane_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![Action::NewPane(Some(Direction::Right))]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn run_command_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["watch".to_string(), "-x".to_string(), "test".to_string()],
+        cwd: Some(PathBuf::from("/tmp")),
+        env: vec![("RUST_BACKTRACE".to_string(), "1".to_string())],
+        hold_on_exit: true,
+    };
+    fake_input_output.add_cli_actions(vec![Action::Run(run_command, Some(Direction::Down))]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn resize_pane_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![
+        Action::NewPane(Some(Direction::Right)),
+        Action::Resize(Direction::Left),
+    ]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &CLI_ACTION, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn switch_tabs_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![
+        Action::NewPane(Some(Direction::Right)),
+        Action::NewTab,
+        Action::GoToPreviousTab,
+    ]);
+    fake_input_output.add_terminal_input(&[
+        &CLI_ACTION,
+        &CLI_ACTION,
+        &CLI_ACTION,
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn switch_mode_from_cli_changes_mode_of_last_used_client() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![Action::SwitchToMode(InputMode::Pane)]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &SPLIT_RIGHT_IN_PANE_MODE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn rename_pane_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![
+        Action::NewPane(Some(Direction::Down)),
+        Action::RenamePane("cargo watch".to_string()),
+    ]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &CLI_ACTION, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn rename_single_pane_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![Action::RenamePane("cargo watch".to_string())]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn held_command_from_cli_shows_how_it_exited() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![held_command()]);
+    fake_input_output.add_terminal_exits(vec![(2, ExitStatus::Code(101))]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &TERMINAL_EXIT, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn held_command_from_cli_runs_again_on_enter() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_actions(vec![held_command()]);
+    fake_input_output
+        .add_terminal_exits(vec![(2, ExitStatus::Code(101)), (3, ExitStatus::Code(0))]);
+    // the command is run again in a new terminal, which is held once it exits too
+    fake_input_output.add_terminal_input(&[
+        &CLI_ACTION,
+        &TERMINAL_EXIT,
+        &RERUN_HELD_PANE,
+        &TERMINAL_EXIT,
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn held_command_from_cli_closes_on_x() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_requests(vec![
+        ClientToServerMsg::Action(held_command()),
+        ClientToServerMsg::QuerySession,
+    ]);
+    fake_input_output.add_terminal_exits(vec![(2, ExitStatus::Code(101))]);
+    fake_input_output.add_terminal_input(&[
+        &CLI_ACTION,
+        &TERMINAL_EXIT,
+        &CLOSE_HELD_PANE,
+        &CLI_ACTION,
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    match fake_input_output.cli_responses.lock().unwrap().last() {
+        Some(Response::Data(ResponseData::SessionStructure(structure))) => {
+            let pane_ids: Vec<PaneId> =
+                structure.tabs[0].panes.iter().map(|pane| pane.id).collect();
+            assert_eq!(pane_ids, [PaneId::Terminal(1)]);
+        }
+        response => panic!("unexpected response: {:?}", response),
+    }
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn kill_session_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_requests(vec![ClientToServerMsg::KillSession]);
+    // the session ends without being quit
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &CLI_ACTION,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    assert_eq!(
+        *fake_input_output.cli_responses.lock().unwrap(),
+        [Response::Ok]
+    );
+    let mut killed_processes = fake_input_output.killed_processes();
+    killed_processes.sort_unstable();
+    assert_eq!(killed_processes, [1001, 1002]);
+}
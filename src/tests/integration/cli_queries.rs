@@ -0,0 +1,445 @@
+use insta::assert_snapshot;
+use std::fs;
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::thread;
+
+use crate::common::events::Event;
+use crate::common::input::actions::Direction;
+use crate::common::input::handler::InputMode;
+use crate::common::input::keys::{key_to_bytes, parse_key};
+use crate::common::ipc::{
+    recv_ipc_msg, send_ipc_msg, ClientToServerMsg, PaneTarget, Response, ResponseData,
+    ServerToClientMsg, Session,
+};
+use crate::panes::{DumpOptions, PaneId, PositionAndSize};
+use crate::pty_bus::{ExitStatus, RunCommand};
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{
+    CLI_ACTION, CLOSE_PANE_IN_PANE_MODE, CLOSE_TAB_IN_TAB_MODE, COMMAND_TOGGLE, ESC,
+    NEW_TAB_IN_TAB_MODE, PANE_MODE, QUIT, SPLIT_RIGHT_IN_PANE_MODE, TAB_MODE, TERMINAL_EXIT,
+    TOGGLE_ACTIVE_TERMINAL_FULLSCREEN_IN_PANE_MODE,
+};
+use crate::{find_sessions, start, wait_for_command, CliArgs, ListedSession};
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+#[test]
+pub fn query_session_structure_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_requests(vec![ClientToServerMsg::QuerySession]);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &TOGGLE_ACTIVE_TERMINAL_FULLSCREEN_IN_PANE_MODE,
+        &CLI_ACTION,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let cli_responses = fake_input_output.cli_responses.lock().unwrap();
+    match cli_responses.as_slice() {
+        [Response::Data(ResponseData::SessionStructure(structure))] => {
+            assert_snapshot!(serde_json::to_string_pretty(structure).unwrap());
+        }
+        responses => panic!("unexpected responses: {:?}", responses),
+    }
+}
+
+#[test]
+pub fn dump_focused_pane_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_requests(vec![
+        ClientToServerMsg::DumpScreen(None, DumpOptions::default()),
+        ClientToServerMsg::DumpScreen(Some(PaneId::Terminal(42)), DumpOptions::default()),
+    ]);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &CLI_ACTION,
+        &CLI_ACTION,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let cli_responses = fake_input_output.cli_responses.lock().unwrap();
+    match cli_responses.as_slice() {
+        [Response::Data(ResponseData::PaneContents(contents)), Response::Error(error)] => {
+            assert_snapshot!(contents);
+            assert_eq!(error, "there is no terminal pane with id 42");
+        }
+        responses => panic!("unexpected responses: {:?}", responses),
+    }
+}
+
+#[test]
+pub fn send_keys_to_pane_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let key = |name| key_to_bytes(parse_key(name).unwrap());
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_requests(vec![
+        ClientToServerMsg::SendKeys(
+            Some(PaneTarget::Id(PaneId::Terminal(1))),
+            vec![b"ls".to_vec(), key("Enter"), key("C-c")],
+        ),
+        ClientToServerMsg::SendKeys(
+            Some(PaneTarget::Index { tab: None, pane: 1 }),
+            vec![key("Up"), key("M-x")],
+        ),
+        ClientToServerMsg::SendKeys(Some(PaneTarget::Id(PaneId::Terminal(42))), vec![key("a")]),
+    ]);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &CLI_ACTION,
+        &CLI_ACTION,
+        &CLI_ACTION,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let cli_responses = fake_input_output.cli_responses.lock().unwrap();
+    assert_eq!(
+        cli_responses.as_slice(),
+        [
+            Response::Ok,
+            Response::Ok,
+            Response::Error("there is no terminal pane with id 42".to_string())
+        ]
+    );
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], b"ls\r\x03");
+    assert_eq!(stdin_writes[&2], b"\x1b[A\x1bx");
+}
+
+#[test]
+pub fn run_command_from_cli_and_get_its_pane() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".to_string()],
+        cwd: None,
+        env: vec![],
+        hold_on_exit: false,
+    };
+    fake_input_output.add_cli_requests(vec![
+        ClientToServerMsg::Run(run_command, Some(Direction::Down)),
+        ClientToServerMsg::QuerySession,
+    ]);
+    fake_input_output.add_terminal_input(&[&CLI_ACTION, &CLI_ACTION, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let cli_responses = fake_input_output.cli_responses.lock().unwrap();
+    match cli_responses.as_slice() {
+        [Response::Data(ResponseData::PaneId(pane_id)), Response::Data(ResponseData::SessionStructure(structure))] =>
+        {
+            assert_eq!(*pane_id, PaneId::Terminal(2));
+            let focused_pane = structure.tabs[0].panes.iter().find(|pane| pane.focused);
+            assert_eq!(focused_pane.map(|pane| pane.id), Some(*pane_id));
+        }
+        responses => panic!("unexpected responses: {:?}", responses),
+    }
+}
+
+#[test]
+pub fn subscribe_to_events_from_cli() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_cli_requests(vec![ClientToServerMsg::Subscribe]);
+    fake_input_output.add_terminal_input(&[
+        &CLI_ACTION,
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &CLOSE_PANE_IN_PANE_MODE,
+        &ESC,
+        &TAB_MODE,
+        &NEW_TAB_IN_TAB_MODE,
+        &CLOSE_TAB_IN_TAB_MODE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    assert_eq!(
+        fake_input_output.cli_responses.lock().unwrap().as_slice(),
+        [Response::Ok]
+    );
+    let cli_events = fake_input_output.cli_events.lock().unwrap();
+    assert_eq!(
+        cli_events.as_slice(),
+        [
+            Event::ModeSwitched {
+                client: 0,
+                mode: InputMode::Command
+            },
+            Event::ModeSwitched {
+                client: 0,
+                mode: InputMode::Pane
+            },
+            Event::PaneOpened {
+                tab: 0,
+                pane: PaneId::Terminal(2)
+            },
+            Event::PaneClosed {
+                tab: 0,
+                pane: PaneId::Terminal(2)
+            },
+            Event::ModeSwitched {
+                client: 0,
+                mode: InputMode::Command
+            },
+            Event::ModeSwitched {
+                client: 0,
+                mode: InputMode::Tab
+            },
+            Event::TabOpened { tab: 1 },
+            Event::PaneOpened {
+                tab: 1,
+                pane: PaneId::Terminal(3)
+            },
+            Event::TabSwitched { client: 0, tab: 1 },
+            Event::TabSwitched { client: 0, tab: 0 },
+            Event::TabClosed { tab: 1 },
+        ]
+    );
+}
+
+#[test]
+pub fn command_exits_are_sent_to_subscribers() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    let run_command = |hold_on_exit| RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".to_string()],
+        cwd: None,
+        env: vec![],
+        hold_on_exit,
+    };
+    fake_input_output.add_cli_requests(vec![
+        ClientToServerMsg::Subscribe,
+        ClientToServerMsg::Run(run_command(true), Some(Direction::Down)),
+        ClientToServerMsg::Run(run_command(false), Some(Direction::Right)),
+    ]);
+    fake_input_output
+        .add_terminal_exits(vec![(2, ExitStatus::Code(101)), (3, ExitStatus::Signal(9))]);
+    fake_input_output.add_terminal_input(&[
+        &CLI_ACTION,
+        &CLI_ACTION,
+        &TERMINAL_EXIT,
+        &CLI_ACTION,
+        &TERMINAL_EXIT,
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    assert_eq!(
+        fake_input_output.cli_responses.lock().unwrap().as_slice(),
+        [
+            Response::Ok,
+            Response::Data(ResponseData::PaneId(PaneId::Terminal(2))),
+            Response::Data(ResponseData::PaneId(PaneId::Terminal(3))),
+        ]
+    );
+    // the held pane stays open, the other one is closed
+    let cli_events = fake_input_output.cli_events.lock().unwrap();
+    assert_eq!(
+        cli_events.as_slice(),
+        [
+            Event::PaneOpened {
+                tab: 0,
+                pane: PaneId::Terminal(2)
+            },
+            Event::CommandExited {
+                pane: PaneId::Terminal(2),
+                status: ExitStatus::Code(101)
+            },
+            Event::PaneOpened {
+                tab: 0,
+                pane: PaneId::Terminal(3)
+            },
+            Event::CommandExited {
+                pane: PaneId::Terminal(3),
+                status: ExitStatus::Signal(9)
+            },
+            Event::PaneClosed {
+                tab: 0,
+                pane: PaneId::Terminal(3)
+            },
+            Event::ModeSwitched {
+                client: 0,
+                mode: InputMode::Command
+            },
+        ]
+    );
+}
+
+#[test]
+pub fn wait_for_command_from_cli() {
+    let (mut client, mut server) = UnixStream::pair().unwrap();
+    let server = thread::spawn(move || {
+        assert!(matches!(
+            recv_ipc_msg(&mut server),
+            Ok(ClientToServerMsg::Subscribe)
+        ));
+        send_ipc_msg(&mut server, &ServerToClientMsg::Response(Response::Ok)).unwrap();
+        assert!(matches!(
+            recv_ipc_msg(&mut server),
+            Ok(ClientToServerMsg::Run(..))
+        ));
+        let replies = vec![
+            ServerToClientMsg::Event(Event::CommandExited {
+                pane: PaneId::Terminal(1),
+                status: ExitStatus::Code(1),
+            }),
+            ServerToClientMsg::Response(Response::Data(ResponseData::PaneId(PaneId::Terminal(2)))),
+            ServerToClientMsg::Event(Event::PaneOpened {
+                tab: 0,
+                pane: PaneId::Terminal(2),
+            }),
+            ServerToClientMsg::Event(Event::CommandExited {
+                pane: PaneId::Terminal(2),
+                status: ExitStatus::Code(3),
+            }),
+        ];
+        for reply in replies {
+            send_ipc_msg(&mut server, &reply).unwrap();
+        }
+    });
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".to_string()],
+        cwd: None,
+        env: vec![],
+        hold_on_exit: false,
+    };
+    let status = wait_for_command(
+        &mut client,
+        "wait-for-command",
+        ClientToServerMsg::Run(run_command, None),
+    );
+    server.join().unwrap();
+    assert_eq!(status, ExitStatus::Code(3));
+}
+
+#[test]
+pub fn wait_for_command_that_exited_before_it_was_reported_from_cli() {
+    let (mut client, mut server) = UnixStream::pair().unwrap();
+    let server = thread::spawn(move || {
+        let _ = recv_ipc_msg::<ClientToServerMsg>(&mut server);
+        send_ipc_msg(&mut server, &ServerToClientMsg::Response(Response::Ok)).unwrap();
+        let _ = recv_ipc_msg::<ClientToServerMsg>(&mut server);
+        // the events of the command can reach the client before the answer to its request
+        let replies = vec![
+            ServerToClientMsg::Event(Event::CommandExited {
+                pane: PaneId::Terminal(2),
+                status: ExitStatus::Signal(15),
+            }),
+            ServerToClientMsg::Response(Response::Data(ResponseData::PaneId(PaneId::Terminal(2)))),
+        ];
+        for reply in replies {
+            send_ipc_msg(&mut server, &reply).unwrap();
+        }
+    });
+    let run_command = RunCommand {
+        command: PathBuf::from("cargo"),
+        args: vec!["test".to_string()],
+        cwd: None,
+        env: vec![],
+        hold_on_exit: false,
+    };
+    let status = wait_for_command(
+        &mut client,
+        "wait-for-command",
+        ClientToServerMsg::Run(run_command, None),
+    );
+    server.join().unwrap();
+    assert_eq!(status, ExitStatus::Signal(15));
+}
+
+#[test]
+pub fn list_sessions_from_cli() {
+    let sock_dir = std::env::temp_dir().join(format!("zellij-test-{}", std::process::id()));
+    fs::create_dir_all(&sock_dir).unwrap();
+    let session = Session {
+        id: 0,
+        conn_name: "running".to_string(),
+        alias: "running".to_string(),
+        created_at: 1_600_000_000,
+        tab_count: 2,
+        client_count: 1,
+    };
+    let running = UnixListener::bind(sock_dir.join("running")).unwrap();
+    let reply = session.clone();
+    let running = thread::spawn(move || {
+        let (mut stream, _) = running.accept().unwrap();
+        assert!(matches!(
+            recv_ipc_msg(&mut stream),
+            Ok(ClientToServerMsg::ListSessions)
+        ));
+        let response = Response::Data(ResponseData::SessionList(vec![reply].into_iter().collect()));
+        send_ipc_msg(&mut stream, &ServerToClientMsg::Response(response)).unwrap();
+    });
+    let other_version = UnixListener::bind(sock_dir.join("other-version")).unwrap();
+    let other_version = thread::spawn(move || {
+        let (mut stream, _) = other_version.accept().unwrap();
+        let _ = stream.read(&mut [0; 64]);
+        // an empty message of an older version of the protocol
+        stream.write_all(&[0, 3, 0, 0, 0, 0]).unwrap();
+    });
+    // the server of this one died without removing its socket
+    drop(UnixListener::bind(sock_dir.join("stale")).unwrap());
+
+    let sessions = find_sessions(&sock_dir);
+    running.join().unwrap();
+    other_version.join().unwrap();
+    assert_eq!(
+        sessions,
+        [
+            ListedSession::Unknown(
+                "other-version".to_string(),
+                "went away without answering: malformed message: unsupported protocol version 3 \
+                 (expected 4)"
+                    .to_string()
+            ),
+            ListedSession::Running(session),
+        ]
+    );
+    assert!(sock_dir.join("running").exists());
+    assert!(sock_dir.join("other-version").exists());
+    assert!(!sock_dir.join("stale").exists());
+    fs::remove_dir_all(&sock_dir).unwrap();
+}
@@ -0,0 +1,369 @@
+use insta::assert_snapshot;
+use std::path::{Path, PathBuf};
+
+use crate::common::config::Config;
+use crate::common::input::handler::{get_help, InputMode};
+use crate::common::theme::PaletteColor;
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{
+    COMMAND_TOGGLE, ESC, MOVE_FOCUS_LEFT_IN_PANE_MODE, PANE_MODE, QUIT, SPLIT_DOWN_IN_PANE_MODE,
+    SPLIT_RIGHT_IN_PANE_MODE, TAB_MODE,
+};
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+#[test]
+pub fn keybinds_from_config_replace_defaults() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE, // unbound in normal mode
+        &[1],            // ctrl-a
+        &PANE_MODE,
+        &[37],  // %
+        &[114], // r, unbound in pane mode
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from(
+            "src/tests/fixtures/configs/tmux-keybinds.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], COMMAND_TOGGLE);
+    assert_eq!(stdin_writes[&2], b"r");
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn config_can_clear_default_keybinds() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &[17], // ctrl-q
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from(
+            "src/tests/fixtures/configs/no-default-keybinds.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], b"\x07p");
+}
+
+#[test]
+pub fn config_errors_are_reported_with_their_line() {
+    let config_path = Path::new("src/tests/fixtures/configs/invalid-key.yaml");
+    let error = Config::new(Some(config_path)).unwrap_err();
+    assert_eq!(
+        error,
+        "Could not parse the config file src/tests/fixtures/configs/invalid-key.yaml: \
+         keybinds.modes.normal.bind[0].keys[1]: invalid key \"Ctrl+1\": only letters can be \
+         combined with Ctrl at line 5 column 26"
+    );
+    let error = Config::from_yaml("keybinds:\n  modes:\n    \"\": {}\n").unwrap_err();
+    assert_eq!(
+        error,
+        "keybinds.modes: the name of an input mode can't be empty at line 3 column 5"
+    );
+    let error = Config::from_yaml(
+        "keybinds:\n  modes:\n    normal:\n      bind:\n        \
+         - {keys: [Ctrl+v], actions: [SwitchToMode: gti]}\n",
+    )
+    .unwrap_err();
+    assert_eq!(
+        error,
+        "keybinds: the input mode \"gti\" is switched to but has no keybinds"
+    );
+}
+
+#[test]
+pub fn key_sequences_from_config() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &[1],  // ctrl-a
+        &[37], // %
+        &[1],
+        &[122], // z, which doesn't finish a sequence
+        &[1],
+        &[99], // c
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from(
+            "src/tests/fixtures/configs/key-sequences.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], b"");
+    assert_eq!(stdin_writes[&2], b"\x01z");
+    assert_eq!(stdin_writes[&3], b"");
+}
+
+#[test]
+pub fn unfinished_key_sequence_times_out() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &[1],  // ctrl-a
+        &[99], // c, too late to finish the sequence
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from(
+            "src/tests/fixtures/configs/key-sequence-timeout.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], b"\x01c");
+    assert!(!stdin_writes.contains_key(&2));
+}
+
+#[test]
+pub fn custom_mode_from_config() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &[22],  // ctrl-v
+        &[100], // d, which opens a pane below and goes back to normal mode
+        &[22],
+        &ESC,
+        &[120], // x
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from("src/tests/fixtures/configs/custom-mode.yaml")),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], b"");
+    assert_eq!(stdin_writes[&2], b"x");
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn help_of_custom_mode() {
+    let config = Config::new(Some(Path::new(
+        "src/tests/fixtures/configs/custom-mode.yaml",
+    )))
+    .unwrap();
+    let git_mode = InputMode::Custom("git".to_string());
+    assert!(config.keybinds.contains_key(&git_mode));
+    let help = get_help(&git_mode, &config.keybinds_help);
+    assert_eq!(help.mode, git_mode);
+    assert_eq!(
+        help.keybinds,
+        [
+            ("d".to_string(), "Diff".to_string()),
+            ("Esc/Ctrl+g".to_string(), "Back".to_string()),
+        ]
+    );
+}
+
+#[test]
+pub fn unbound_keys_per_mode() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &[122], // z, swallowed in pane mode
+        &ESC,
+        &TAB_MODE,
+        &[121], // y, which gets out of tab mode
+        &[120], // x
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from(
+            "src/tests/fixtures/configs/unbound-keys.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    assert_eq!(stdin_writes[&1], b"yx");
+}
+
+#[test]
+pub fn borders_are_drawn_in_the_colors_of_the_theme() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &SPLIT_DOWN_IN_PANE_MODE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from("src/tests/fixtures/configs/theme.yaml")),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    // the focused pane is the one at the bottom right, whose border meets the one above it
+    let frame = output_frames
+        .iter()
+        .map(|frame| String::from_utf8_lossy(frame))
+        .find(|frame| frame.contains('├'))
+        .expect("could not find the frame with three panes");
+    assert!(frame.contains("\u{1b}[38;5;240m│"));
+    assert!(frame.contains("\u{1b}[38;5;208m│"));
+    assert!(frame.contains("\u{1b}[38;5;208m├"));
+    assert!(!frame.contains("\u{1b}[38;5;240m├"));
+}
+
+#[test]
+pub fn theme_from_config() {
+    let config = Config::new(Some(Path::new("src/tests/fixtures/configs/theme.yaml"))).unwrap();
+    assert_eq!(config.theme.fg, PaletteColor::Index(7));
+    assert_eq!(config.theme.inactive_border, PaletteColor::Index(240));
+    assert_eq!(config.theme.accent, PaletteColor::Rgb(0xd7, 0x5f, 0));
+    assert_eq!(
+        config.theme.modes.get(&InputMode::Normal),
+        Some(&PaletteColor::Index(6))
+    );
+    let error = Config::from_yaml("theme:\n  fg: purple\n").unwrap_err();
+    assert_eq!(
+        error,
+        "theme.fg: invalid color \"purple\": expected the name of a color, a number from 0 to 255 \
+         or \"#rrggbb\" at line 2 column 7"
+    );
+}
+
+fn four_panes_with_border_style(config_path: &str) -> String {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &SPLIT_DOWN_IN_PANE_MODE,
+        &MOVE_FOCUS_LEFT_IN_PANE_MODE,
+        &SPLIT_DOWN_IN_PANE_MODE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from(config_path)),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    get_next_to_last_snapshot(snapshots).expect("could not find snapshot")
+}
+
+#[test]
+pub fn double_borders() {
+    let snapshot_before_quit =
+        four_panes_with_border_style("src/tests/fixtures/configs/double-borders.yaml");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn ascii_borders() {
+    let snapshot_before_quit =
+        four_panes_with_border_style("src/tests/fixtures/configs/ascii-borders.yaml");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn blank_borders() {
+    let snapshot_before_quit =
+        four_panes_with_border_style("src/tests/fixtures/configs/blank-borders.yaml");
+    assert_snapshot!(snapshot_before_quit);
+}
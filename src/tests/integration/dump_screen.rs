@@ -0,0 +1,55 @@
+use insta::assert_snapshot;
+
+use crate::panes::{DumpOptions, PositionAndSize, TerminalPane};
+use crate::tab::Pane;
+
+/// A pane 10 columns wide and 3 rows high, whose first line was wrapped and that has scrolled
+/// down by a couple of lines.
+fn pane_with_output() -> TerminalPane {
+    let size = PositionAndSize {
+        columns: 10,
+        rows: 3,
+        x: 0,
+        y: 0,
+    };
+    let mut pane = TerminalPane::new(0, size);
+    pane.load_scrollback(b"a line long enough to wrap\r\n\x1b[31mred\x1b[m text\r\nlast line\r\n");
+    pane
+}
+
+#[test]
+pub fn dump_viewport() {
+    let dump = pane_with_output().dump(DumpOptions::default()).unwrap();
+    assert_snapshot!(dump);
+}
+
+#[test]
+pub fn dump_full_scrollback() {
+    let options = DumpOptions {
+        full: true,
+        ..DumpOptions::default()
+    };
+    let dump = pane_with_output().dump(options).unwrap();
+    assert_snapshot!(dump);
+}
+
+#[test]
+pub fn dump_full_scrollback_with_wrapped_lines_joined() {
+    let options = DumpOptions {
+        full: true,
+        join_wrapped: true,
+        ..DumpOptions::default()
+    };
+    let dump = pane_with_output().dump(options).unwrap();
+    assert_snapshot!(dump);
+}
+
+#[test]
+pub fn dump_viewport_with_styles() {
+    let options = DumpOptions {
+        ansi: true,
+        ..DumpOptions::default()
+    };
+    let dump = pane_with_output().dump(options).unwrap();
+    assert_snapshot!(format!("{:?}", dump));
+}
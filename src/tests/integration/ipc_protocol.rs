@@ -0,0 +1,159 @@
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use crate::common::input::actions::{Action, Direction};
+use crate::common::input::handler::InputMode;
+use crate::common::ipc::{
+    recv_ipc_msg, send_ipc_msg, ClientToServerMsg, IpcError, PaneInfo, Response, ServerToClientMsg,
+    IPC_PROTOCOL_VERSION,
+};
+use crate::panes::{PaneId, PositionAndSize};
+use crate::pty_bus::{ExitStatus, RunCommand};
+
+fn frame(version: u16, body: &[u8]) -> Vec<u8> {
+    let mut frame = version.to_be_bytes().to_vec();
+    frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    frame.extend_from_slice(body);
+    frame
+}
+
+#[test]
+pub fn several_messages_on_one_connection() {
+    let mut connection = vec![];
+    send_ipc_msg(&mut connection, &ClientToServerMsg::ListSessions).unwrap();
+    send_ipc_msg(&mut connection, &ClientToServerMsg::Input(b"hi".to_vec())).unwrap();
+    let mut connection = Cursor::new(connection);
+    assert!(matches!(
+        recv_ipc_msg(&mut connection),
+        Ok(ClientToServerMsg::ListSessions)
+    ));
+    assert!(matches!(
+        recv_ipc_msg(&mut connection),
+        Ok(ClientToServerMsg::Input(input)) if input == b"hi"
+    ));
+    assert!(matches!(
+        recv_ipc_msg::<ClientToServerMsg>(&mut connection),
+        Err(IpcError::Io(_))
+    ));
+}
+
+#[test]
+pub fn messages_after_a_malformed_one_can_be_read() {
+    let mut connection = frame(IPC_PROTOCOL_VERSION, &[0xff; 8]);
+    send_ipc_msg(
+        &mut connection,
+        &ServerToClientMsg::Response(Response::Error("nope".to_string())),
+    )
+    .unwrap();
+    let mut connection = Cursor::new(connection);
+    assert!(matches!(
+        recv_ipc_msg::<ServerToClientMsg>(&mut connection),
+        Err(IpcError::Malformed(_))
+    ));
+    assert!(matches!(
+        recv_ipc_msg(&mut connection),
+        Ok(ServerToClientMsg::Response(Response::Error(error))) if error == "nope"
+    ));
+}
+
+#[test]
+pub fn messages_of_another_protocol_version_are_rejected() {
+    let body = bincode::serialize(&ClientToServerMsg::ListSessions).unwrap();
+    let mut connection = Cursor::new(frame(IPC_PROTOCOL_VERSION + 1, &body));
+    match recv_ipc_msg::<ClientToServerMsg>(&mut connection) {
+        Err(IpcError::Malformed(reason)) => assert!(reason.contains("protocol version")),
+        other => panic!("expected a malformed message, got {:?}", other),
+    }
+}
+
+#[test]
+pub fn oversized_messages_end_the_connection() {
+    let mut connection = IPC_PROTOCOL_VERSION.to_be_bytes().to_vec();
+    connection.extend_from_slice(&u32::MAX.to_be_bytes());
+    let mut connection = Cursor::new(connection);
+    assert!(matches!(
+        recv_ipc_msg::<ClientToServerMsg>(&mut connection),
+        Err(IpcError::Io(_))
+    ));
+}
+
+#[test]
+pub fn actions_keep_their_encoding() {
+    // actions added anywhere but at the end would change how the ones after them are encoded
+    assert_eq!(
+        IPC_PROTOCOL_VERSION, 4,
+        "the encodings below are those of protocol version 4"
+    );
+    let run_command = RunCommand {
+        command: PathBuf::from("ls"),
+        args: vec![],
+        cwd: None,
+        env: vec![],
+        hold_on_exit: false,
+    };
+    let actions = vec![
+        Action::Quit,
+        Action::Write(vec![]),
+        Action::SwitchToMode(InputMode::Normal),
+        Action::Resize(Direction::Left),
+        Action::SwitchFocus(Direction::Left),
+        Action::MoveFocus(Direction::Left),
+        Action::ScrollUp,
+        Action::ScrollDown,
+        Action::ToggleFocusFullscreen,
+        Action::NewPane(None),
+        Action::CloseFocus,
+        Action::NewTab,
+        Action::GoToNextTab,
+        Action::GoToPreviousTab,
+        Action::CloseTab,
+        Action::Detach,
+        Action::RenamePane(String::new()),
+        Action::Run(run_command, None),
+    ];
+    for (variant_index, action) in actions.iter().enumerate() {
+        let encoded = bincode::serialize(action).unwrap();
+        assert_eq!(
+            encoded[..4],
+            (variant_index as u32).to_le_bytes(),
+            "{:?} isn't encoded as variant {}",
+            action,
+            variant_index
+        );
+    }
+}
+
+#[test]
+pub fn pane_info_keeps_its_encoding() {
+    // fields can't be added to structs without bumping the protocol version, as they have no
+    // marker telling older versions to skip them
+    assert_eq!(
+        IPC_PROTOCOL_VERSION, 4,
+        "the encoding below is the one of protocol version 4"
+    );
+    let pane_info = PaneInfo {
+        id: PaneId::Terminal(1),
+        geometry: PositionAndSize {
+            x: 0,
+            y: 0,
+            rows: 2,
+            columns: 3,
+        },
+        selectable: true,
+        fullscreen: false,
+        focused: true,
+        title: None,
+        command: None,
+        cwd: None,
+        exit_status: Some(ExitStatus::Code(4)),
+    };
+    let mut expected = vec![0, 0, 0, 0, 1, 0, 0, 0]; // id
+    expected.extend_from_slice(&0u64.to_le_bytes()); // geometry
+    expected.extend_from_slice(&0u64.to_le_bytes());
+    expected.extend_from_slice(&2u64.to_le_bytes());
+    expected.extend_from_slice(&3u64.to_le_bytes());
+    expected.extend_from_slice(&[1, 0, 1]); // selectable, fullscreen, focused
+    expected.extend_from_slice(&[0, 0, 0]); // title, command, cwd
+    expected.extend_from_slice(&[1, 0, 0, 0, 0, 4, 0, 0, 0]); // exit_status
+    assert_eq!(bincode::serialize(&pane_info).unwrap(), expected);
+}
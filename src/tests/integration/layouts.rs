@@ -21,10 +21,12 @@ pub fn accepts_basic_layout() {
     };
     let mut fake_input_output = get_fake_os_input(&fake_win_size);
     fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
-    let mut opts = CliArgs::default();
-    opts.layout = Some(PathBuf::from(
-        "src/tests/fixtures/layouts/three-panes-with-nesting.yaml",
-    ));
+    let opts = CliArgs {
+        layout: Some(PathBuf::from(
+            "src/tests/fixtures/layouts/three-panes-with-nesting.yaml",
+        )),
+        ..Default::default()
+    };
 
     start(Box::new(fake_input_output.clone()), opts);
     let output_frames = fake_input_output
@@ -50,3 +52,32 @@ pub fn accepts_basic_layout() {
     assert_snapshot!(next_to_last_snapshot);
     assert_snapshot!(last_snapshot);
 }
+
+#[test]
+pub fn titles_from_layout() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    let opts = CliArgs {
+        layout: Some(PathBuf::from(
+            "src/tests/fixtures/layouts/titled-panes.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    // the pane on top shows its title on the row above the panes
+    let next_to_last_snapshot = snapshots.get(snapshots.len() - 2).unwrap();
+    assert_snapshot!(next_to_last_snapshot);
+}
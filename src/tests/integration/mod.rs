@@ -1,14 +1,26 @@
+pub mod attach_and_detach;
 pub mod basic;
+pub mod cli_actions;
+pub mod cli_queries;
 pub mod close_pane;
 pub mod compatibility;
+pub mod config;
+pub mod dump_screen;
+pub mod ipc_protocol;
 pub mod layouts;
 pub mod move_focus_down;
 pub mod move_focus_left;
 pub mod move_focus_right;
 pub mod move_focus_up;
+pub mod multiple_clients;
+pub mod osc;
 pub mod resize_down;
 pub mod resize_left;
 pub mod resize_right;
 pub mod resize_up;
+pub mod scrollback_persistence;
+pub mod session_resurrection;
+pub mod session_sockets;
 pub mod tabs;
+pub mod terminal_window_resize;
 pub mod toggle_fullscreen;
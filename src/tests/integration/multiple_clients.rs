@@ -0,0 +1,326 @@
+use insta::assert_snapshot;
+use std::path::PathBuf;
+
+use crate::cli::SizePolicy;
+use crate::common::ipc::{
+    ClientId, ClientToServerMsg, ClientType, ExitReason, Response, ResponseData,
+};
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{
+    CLI_ACTION, COMMAND_TOGGLE, DETACH_IN_COMMAND_MODE, ESC, NEW_TAB_IN_TAB_MODE, OTHER_CLIENT,
+    PANE_MODE, QUIT, SPLIT_DOWN_IN_PANE_MODE, TAB_MODE,
+};
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+/// The id of the client attached next to the one of the fake terminal.
+const OTHER_CLIENT_ID: ClientId = 2;
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+fn attach(client_size: PositionAndSize) -> ClientToServerMsg {
+    attach_as(ClientType::Writer, client_size)
+}
+
+fn attach_as(client_type: ClientType, client_size: PositionAndSize) -> ClientToServerMsg {
+    // tests run the server in-process, so the session has the id of the test's process
+    let session_id = std::process::id() as u64;
+    ClientToServerMsg::AttachToSession(session_id, client_type, client_size)
+}
+
+fn input(keys: &[u8]) -> ClientToServerMsg {
+    ClientToServerMsg::Input(keys.to_vec())
+}
+
+/// Opens the session with a status bar, which shows how many clients of each type are attached.
+fn opts_with_status_bar() -> CliArgs {
+    CliArgs {
+        layout: Some(PathBuf::from("src/tests/fixtures/layouts/status-bar.yaml")),
+        ..CliArgs::default()
+    }
+}
+
+/// Returns the snapshot of the fake terminal from before it quit.
+fn terminal_snapshot(fake_input_output: &FakeInputOutput, win_size: &PositionAndSize) -> String {
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, win_size);
+    get_next_to_last_snapshot(snapshots).expect("could not find snapshot")
+}
+
+/// Returns the last of the snapshots whose status bar shows that a writer and a reader are
+/// attached. The frames rendered while the session is ending don't count, as the status bar can't
+/// get the state of the session by then.
+fn snapshot_with_reader(output_frames: &[Vec<u8>], win_size: &PositionAndSize) -> String {
+    get_output_frame_snapshots(output_frames, win_size)
+        .into_iter()
+        .rev()
+        .find(|snapshot| snapshot.contains("(1 writer, 1 reader)"))
+        .expect("could not find snapshot")
+}
+
+/// Returns the snapshot of what was last rendered to the other client.
+fn other_client_snapshot(
+    fake_input_output: &FakeInputOutput,
+    client_size: &PositionAndSize,
+) -> String {
+    let output_frames = fake_input_output.other_client_output_frames.lock().unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames[&OTHER_CLIENT_ID], client_size);
+    snapshots.last().cloned().expect("could not find snapshot")
+}
+
+#[test]
+pub fn session_fits_the_smallest_client() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let other_client_size = PositionAndSize {
+        columns: 60,
+        rows: 15,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![(OTHER_CLIENT_ID, attach(other_client_size))]);
+    fake_input_output.add_terminal_input(&[&OTHER_CLIENT, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    // the area outside of the session is filled when the other client attaches, and left alone
+    // by the renders that follow
+    let frames_filling_unused_area = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|frame| String::from_utf8_lossy(frame).contains('·'))
+        .count();
+    assert_eq!(frames_filling_unused_area, 1);
+    assert_snapshot!(terminal_snapshot(&fake_input_output, &fake_win_size));
+    assert_snapshot!(other_client_snapshot(
+        &fake_input_output,
+        &other_client_size
+    ));
+}
+
+#[test]
+pub fn session_grows_back_when_the_smallest_client_leaves() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let other_client_size = PositionAndSize {
+        columns: 60,
+        rows: 15,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![
+        (OTHER_CLIENT_ID, attach(other_client_size)),
+        (OTHER_CLIENT_ID, ClientToServerMsg::DisconnectFromSession),
+    ]);
+    fake_input_output.add_terminal_input(&[&OTHER_CLIENT, &OTHER_CLIENT, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    assert_snapshot!(terminal_snapshot(&fake_input_output, &fake_win_size));
+}
+
+#[test]
+pub fn session_follows_the_resize_of_a_client() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let new_other_client_size = PositionAndSize {
+        columns: 60,
+        rows: 15,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![
+        (OTHER_CLIENT_ID, attach(fake_win_size)),
+        (
+            OTHER_CLIENT_ID,
+            ClientToServerMsg::TerminalResize(new_other_client_size),
+        ),
+    ]);
+    fake_input_output.add_terminal_input(&[&OTHER_CLIENT, &OTHER_CLIENT, &COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    assert_snapshot!(terminal_snapshot(&fake_input_output, &fake_win_size));
+    assert_snapshot!(other_client_snapshot(
+        &fake_input_output,
+        &new_other_client_size
+    ));
+}
+
+#[test]
+pub fn session_fits_the_latest_client() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let other_client_size = PositionAndSize {
+        columns: 60,
+        rows: 15,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![
+        (OTHER_CLIENT_ID, attach(other_client_size)),
+        (OTHER_CLIENT_ID, ClientToServerMsg::Input(ESC.to_vec())),
+    ]);
+    // the other client is used last when it's attached, and then the fake terminal is
+    fake_input_output.add_terminal_input(&[&OTHER_CLIENT, &OTHER_CLIENT, &COMMAND_TOGGLE, &QUIT]);
+    let opts = CliArgs {
+        size_policy: SizePolicy::Latest,
+        ..CliArgs::default()
+    };
+    start(Box::new(fake_input_output.clone()), opts);
+
+    assert_snapshot!(terminal_snapshot(&fake_input_output, &fake_win_size));
+    assert_snapshot!(other_client_snapshot(
+        &fake_input_output,
+        &other_client_size
+    ));
+}
+
+#[test]
+pub fn clients_look_at_different_tabs() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![(OTHER_CLIENT_ID, attach(fake_win_size))]);
+    // the other client stays on the first tab when the fake terminal opens a new one
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_DOWN_IN_PANE_MODE,
+        &ESC,
+        &OTHER_CLIENT,
+        &TAB_MODE,
+        &NEW_TAB_IN_TAB_MODE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    assert_snapshot!(terminal_snapshot(&fake_input_output, &fake_win_size));
+    assert_snapshot!(other_client_snapshot(&fake_input_output, &fake_win_size));
+}
+
+#[test]
+pub fn keys_of_reader_client_are_ignored() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    // the reader types some text, and then tries to split the pane
+    fake_input_output.add_other_client_messages(vec![
+        (
+            OTHER_CLIENT_ID,
+            attach_as(ClientType::Reader, fake_win_size),
+        ),
+        (OTHER_CLIENT_ID, input(b"abc")),
+        (OTHER_CLIENT_ID, input(&COMMAND_TOGGLE)),
+        (OTHER_CLIENT_ID, input(&PANE_MODE)),
+        (OTHER_CLIENT_ID, input(&SPLIT_DOWN_IN_PANE_MODE)),
+    ]);
+    // the writer goes to command mode first, as the status bar shows the mode of the writers
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), opts_with_status_bar());
+
+    assert_eq!(fake_input_output.stdin_writes.lock().unwrap()[&1], b"");
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    assert_snapshot!(snapshot_with_reader(&output_frames, &fake_win_size));
+    let other_client_output_frames = fake_input_output.other_client_output_frames.lock().unwrap();
+    assert_snapshot!(snapshot_with_reader(
+        &other_client_output_frames[&OTHER_CLIENT_ID],
+        &fake_win_size
+    ));
+}
+
+#[test]
+pub fn reader_client_can_detach() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_other_client_messages(vec![
+        (
+            OTHER_CLIENT_ID,
+            attach_as(ClientType::Reader, fake_win_size),
+        ),
+        (OTHER_CLIENT_ID, input(&COMMAND_TOGGLE)),
+        (OTHER_CLIENT_ID, input(&DETACH_IN_COMMAND_MODE)),
+    ]);
+    // the session is listed once the reader detached, to see how many clients are left
+    fake_input_output.add_cli_requests(vec![ClientToServerMsg::ListSessions]);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &OTHER_CLIENT,
+        &CLI_ACTION,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), opts_with_status_bar());
+
+    let client_exits = fake_input_output.client_exits.lock().unwrap();
+    assert_eq!(client_exits[&OTHER_CLIENT_ID], ExitReason::Detached);
+    let client_counts: Vec<usize> = match fake_input_output.cli_responses.lock().unwrap().as_slice()
+    {
+        [Response::Data(ResponseData::SessionList(sessions))] => sessions
+            .iter()
+            .map(|session| session.client_count)
+            .collect(),
+        responses => panic!("unexpected responses: {:?}", responses),
+    };
+    assert_eq!(client_counts, vec![1]);
+    // the reader was counted in the status bar until it detached
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    assert_snapshot!(snapshot_with_reader(&output_frames, &fake_win_size));
+}
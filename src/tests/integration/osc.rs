@@ -0,0 +1,151 @@
+use ::insta::assert_snapshot;
+use ::std::collections::HashMap;
+use ::std::path::PathBuf;
+
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::possible_tty_inputs::Bytes;
+use crate::tests::utils::commands::{COMMAND_TOGGLE, PANE_MODE, QUIT, SPLIT_DOWN_IN_PANE_MODE};
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+/*
+ * These tests run the OSC sequences of the osc_sequences fixture through zellij: a window title,
+ * hyperlinks, a copy to the clipboard and questions about the default colors. The
+ * many_hyperlinks fixture has a link followed by a thousand others written over each other.
+ */
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize, fixture_name: &str) -> FakeInputOutput {
+    let mut tty_inputs = HashMap::new();
+    let fixture_bytes = Bytes::from_file_in_fixtures(fixture_name);
+    tty_inputs.insert(fake_win_size.columns as u16, fixture_bytes);
+    FakeInputOutput::new(*fake_win_size).with_tty_inputs(tty_inputs)
+}
+
+/// Returns everything written to the fake terminal, in one piece.
+fn get_output(fake_input_output: &FakeInputOutput) -> String {
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    String::from_utf8_lossy(&output_frames.concat()).into_owned()
+}
+
+#[test]
+pub fn title_from_osc() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size, "osc_sequences");
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_DOWN_IN_PANE_MODE,
+        &QUIT,
+    ]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn hyperlinks_are_kept() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size, "osc_sequences");
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output = get_output(&fake_input_output);
+    assert!(output.contains(
+        "\u{1b}]8;;file:///tmp/notes.txt\u{1b}\\notes.txt\u{1b}]8;;\u{1b}\\  \
+         \u{1b}]8;id=2;https://zellij.dev/a;b\u{1b}\\zellij.dev\u{1b}]8;;\u{1b}\\"
+    ));
+}
+
+#[test]
+pub fn hyperlinks_are_kept_while_others_are_dropped() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size, "many_hyperlinks");
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let last_frame = output_frames
+        .iter()
+        .rev()
+        .map(|frame| String::from_utf8_lossy(frame))
+        .find(|frame| frame.contains("link 999"))
+        .expect("could not find the last link");
+    assert!(last_frame.contains("\u{1b}]8;;file:///tmp/kept.txt\u{1b}\\kept.txt\u{1b}]8;;\u{1b}\\"));
+    assert!(
+        last_frame.contains("\u{1b}]8;;https://zellij.dev/999\u{1b}\\link 999\u{1b}]8;;\u{1b}\\")
+    );
+}
+
+#[test]
+pub fn default_colors_are_answered_from_the_theme() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size, "osc_sequences");
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    let stdin_writes = fake_input_output.stdin_writes.lock().unwrap();
+    let answers = String::from_utf8_lossy(stdin_writes.get(&1).unwrap());
+    // each one ending the way its question did
+    assert_eq!(
+        answers,
+        "\u{1b}]10;rgb:e5e5/e5e5/e5e5\u{1b}\\\u{1b}]11;rgb:0000/0000/0000\u{7}"
+    );
+}
+
+#[test]
+pub fn clipboard_writes_are_forwarded_if_allowed() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let set_clipboard = "\u{1b}]52;c;aGVsbG8gZnJvbSB2aW0=\u{7}";
+
+    let mut fake_input_output = get_fake_os_input(&fake_win_size, "osc_sequences");
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+    assert!(!get_output(&fake_input_output).contains(set_clipboard));
+
+    let mut fake_input_output = get_fake_os_input(&fake_win_size, "osc_sequences");
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    let opts = CliArgs {
+        config: Some(PathBuf::from("src/tests/fixtures/configs/clipboard.yaml")),
+        ..Default::default()
+    };
+    start(Box::new(fake_input_output.clone()), opts);
+    assert!(get_output(&fake_input_output).contains(set_clipboard));
+}
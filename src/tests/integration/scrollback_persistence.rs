@@ -0,0 +1,150 @@
+use insta::assert_snapshot;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::layout::{session_scrollback_dirs, SessionLayout};
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{COMMAND_TOGGLE, QUIT, SCROLL_MODE, SCROLL_UP_IN_SCROLL_MODE};
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+#[test]
+pub fn saved_scrollback_is_kept_above_new_output() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &SCROLL_MODE,
+        &SCROLL_UP_IN_SCROLL_MODE,
+        &SCROLL_UP_IN_SCROLL_MODE,
+        &SCROLL_UP_IN_SCROLL_MODE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        resurrect: Some(PathBuf::from(
+            "src/tests/fixtures/sessions/with-scrollback.yaml",
+        )),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+/// Runs a session that's saved every now and then, until it's been saved a few times.
+fn run_saved_session(fake_win_size: &PositionAndSize, opts: CliArgs) {
+    let mut fake_input_output = get_fake_os_input(fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &COMMAND_TOGGLE,
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        session_save_interval: Some(Duration::from_millis(10)),
+        ..opts
+    };
+    start(Box::new(fake_input_output), opts);
+}
+
+#[test]
+pub fn saved_scrollback_is_resurrected() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let sessions_dir =
+        std::env::temp_dir().join(format!("zellij-test-{}-scrollback", std::process::id()));
+    run_saved_session(
+        &fake_win_size,
+        CliArgs {
+            session: Some("with-scrollback".to_string()),
+            save_scrollback: true,
+            sessions_dir: Some(sessions_dir.clone()),
+            ..Default::default()
+        },
+    );
+    // only the scrollback of the last save is kept
+    assert_eq!(
+        session_scrollback_dirs(&sessions_dir, "with-scrollback").len(),
+        1
+    );
+
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &SCROLL_MODE,
+        &SCROLL_UP_IN_SCROLL_MODE,
+        &SCROLL_UP_IN_SCROLL_MODE,
+        &SCROLL_UP_IN_SCROLL_MODE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        resurrect: Some(PathBuf::from("with-scrollback")),
+        sessions_dir: Some(sessions_dir.clone()),
+        ..Default::default()
+    };
+    start(Box::new(fake_input_output.clone()), opts);
+    fs::remove_dir_all(&sessions_dir).unwrap();
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn scrollback_saving_is_set_in_config_file() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let sessions_dir =
+        std::env::temp_dir().join(format!("zellij-test-{}-config", std::process::id()));
+    run_saved_session(
+        &fake_win_size,
+        CliArgs {
+            session: Some("with-scrollback".to_string()),
+            config: Some(PathBuf::from(
+                "src/tests/fixtures/configs/save-scrollback.yaml",
+            )),
+            sessions_dir: Some(sessions_dir.clone()),
+            ..Default::default()
+        },
+    );
+    let saved_session = SessionLayout::new(Path::new("with-scrollback"), &sessions_dir);
+    let scrollback = saved_session
+        .ok()
+        .and_then(|saved_session| saved_session.tabs[0].parts[0].scrollback.clone())
+        .and_then(|scrollback_path| fs::read_to_string(scrollback_path).ok());
+    fs::remove_dir_all(&sessions_dir).unwrap();
+    let scrollback = scrollback.expect("scrollback was not saved");
+    assert_eq!(scrollback.lines().count(), 3);
+}
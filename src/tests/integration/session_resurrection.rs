@@ -0,0 +1,111 @@
+use insta::assert_snapshot;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::layout::SessionLayout;
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::commands::{
+    COMMAND_TOGGLE, PANE_MODE, QUIT, SPLIT_RIGHT_IN_PANE_MODE, SWITCH_PREV_TAB_IN_TAB_MODE,
+    TAB_MODE,
+};
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+#[test]
+pub fn resurrects_every_tab_of_saved_session() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &QUIT]);
+    let opts = CliArgs {
+        resurrect: Some(PathBuf::from("src/tests/fixtures/sessions/two-tabs.yaml")),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn resurrected_session_keeps_tab_order() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &TAB_MODE,
+        &SWITCH_PREV_TAB_IN_TAB_MODE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        resurrect: Some(PathBuf::from("src/tests/fixtures/sessions/two-tabs.yaml")),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &fake_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn running_session_is_saved() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let sessions_dir =
+        std::env::temp_dir().join(format!("zellij-test-{}-saved", std::process::id()));
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &COMMAND_TOGGLE,
+        &COMMAND_TOGGLE,
+        &QUIT,
+    ]);
+    let opts = CliArgs {
+        session: Some("saved-session".to_string()),
+        session_save_interval: Some(Duration::from_millis(10)),
+        sessions_dir: Some(sessions_dir.clone()),
+        ..Default::default()
+    };
+
+    start(Box::new(fake_input_output.clone()), opts);
+    let saved_session = SessionLayout::new(Path::new("saved-session"), &sessions_dir);
+    let _ = fs::remove_dir_all(&sessions_dir);
+    let saved_session = saved_session.expect("session was not saved");
+    assert_eq!(saved_session.tabs.len(), 1);
+    assert_eq!(saved_session.tabs[0].parts.len(), 2);
+}
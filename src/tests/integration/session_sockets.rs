@@ -0,0 +1,57 @@
+use std::fs;
+use std::io;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use crate::bind_session_socket;
+
+#[test]
+pub fn one_of_the_sessions_started_at_once_takes_over_a_stale_socket() {
+    let sock_dir = std::env::temp_dir().join(format!("zellij-test-{}-stale", std::process::id()));
+    fs::create_dir_all(&sock_dir).unwrap();
+    let socket_path = sock_dir.join("named-session");
+    for _ in 0..20 {
+        // the server of the previous session by that name died without removing its socket
+        drop(UnixListener::bind(&socket_path).unwrap());
+        let barrier = Arc::new(Barrier::new(4));
+        let starts: Vec<_> = (0..4)
+            .map(|_| {
+                let socket_path = socket_path.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    let mut listener = None;
+                    barrier.wait();
+                    let result = bind_session_socket(&socket_path, |path| {
+                        listener = Some(UnixListener::bind(path)?);
+                        Ok(())
+                    });
+                    (result.map_err(|e| e.kind()), listener)
+                })
+            })
+            .collect();
+        let results: Vec<_> = starts.into_iter().map(|s| s.join().unwrap()).collect();
+        let errors: Vec<_> = results
+            .iter()
+            .filter_map(|(result, _)| result.err())
+            .collect();
+        assert_eq!(errors, [io::ErrorKind::AddrInUse; 3]);
+        // the socket is still the one of the session that took it over
+        assert!(UnixStream::connect(&socket_path).is_ok());
+        drop(results);
+        fs::remove_file(&socket_path).unwrap();
+    }
+    fs::remove_dir_all(&sock_dir).unwrap();
+}
+
+#[test]
+pub fn socket_of_running_session_is_not_taken_over() {
+    let sock_dir = std::env::temp_dir().join(format!("zellij-test-{}-alive", std::process::id()));
+    fs::create_dir_all(&sock_dir).unwrap();
+    let socket_path = sock_dir.join("named-session");
+    let _running_session = UnixListener::bind(&socket_path).unwrap();
+    let result = bind_session_socket(&socket_path, |path| UnixListener::bind(path).map(drop));
+    assert_eq!(result.map_err(|e| e.kind()), Err(io::ErrorKind::AddrInUse));
+    assert!(UnixStream::connect(&socket_path).is_ok());
+    fs::remove_dir_all(&sock_dir).unwrap();
+}
@@ -0,0 +1,204 @@
+use ::insta::assert_snapshot;
+
+use crate::panes::PositionAndSize;
+use crate::tests::fakes::FakeInputOutput;
+use crate::tests::utils::{get_next_to_last_snapshot, get_output_frame_snapshots};
+use crate::{start, CliArgs};
+
+use crate::tests::utils::commands::{
+    COMMAND_TOGGLE, PANE_MODE, QUIT, SIGWINCH, SPLIT_DOWN_IN_PANE_MODE, SPLIT_RIGHT_IN_PANE_MODE,
+};
+
+fn get_fake_os_input(fake_win_size: &PositionAndSize) -> FakeInputOutput {
+    FakeInputOutput::new(*fake_win_size)
+}
+
+#[test]
+pub fn window_width_decrease_with_one_pane() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let new_win_size = PositionAndSize {
+        columns: 50,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[&COMMAND_TOGGLE, &SIGWINCH, &QUIT]);
+    fake_input_output.add_sigwinch_event(new_win_size);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &new_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn window_width_decrease_with_panes_side_by_side() {
+    // ┌─────┬─────┐                   ┌──┬──┐
+    // │     │     │                   │  │  │
+    // │     │     │ ==window=width==> │  │  │
+    // │     │     │                   │  │  │
+    // └─────┴─────┘                   └──┴──┘
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let new_win_size = PositionAndSize {
+        columns: 61,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &SIGWINCH,
+        &QUIT,
+    ]);
+    fake_input_output.add_sigwinch_event(new_win_size);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &new_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn window_width_increase_with_panes_side_by_side() {
+    // ┌──┬──┐                   ┌─────┬─────┐
+    // │  │  │                   │     │     │
+    // │  │  │ ==window=width==> │     │     │
+    // │  │  │                   │     │     │
+    // └──┴──┘                   └─────┴─────┘
+    let fake_win_size = PositionAndSize {
+        columns: 40,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let new_win_size = PositionAndSize {
+        columns: 60,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &SIGWINCH,
+        &QUIT,
+    ]);
+    fake_input_output.add_sigwinch_event(new_win_size);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &new_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn window_height_decrease_with_panes_on_top_of_each_other() {
+    // ┌───────────┐                   ┌───────────┐
+    // │           │                   │           │
+    // ├───────────┤ ==window=height==> ├───────────┤
+    // │           │                   │           │
+    // └───────────┘                   └───────────┘
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let new_win_size = PositionAndSize {
+        columns: 121,
+        rows: 10,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_DOWN_IN_PANE_MODE,
+        &SIGWINCH,
+        &QUIT,
+    ]);
+    fake_input_output.add_sigwinch_event(new_win_size);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &new_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
+
+#[test]
+pub fn window_too_small_for_panes() {
+    let fake_win_size = PositionAndSize {
+        columns: 121,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let new_win_size = PositionAndSize {
+        columns: 9,
+        rows: 20,
+        x: 0,
+        y: 0,
+    };
+    let mut fake_input_output = get_fake_os_input(&fake_win_size);
+    fake_input_output.add_terminal_input(&[
+        &COMMAND_TOGGLE,
+        &PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &SPLIT_RIGHT_IN_PANE_MODE,
+        &SIGWINCH,
+        &QUIT,
+    ]);
+    fake_input_output.add_sigwinch_event(new_win_size);
+    start(Box::new(fake_input_output.clone()), CliArgs::default());
+
+    let output_frames = fake_input_output
+        .stdout_writer
+        .output_frames
+        .lock()
+        .unwrap();
+    let snapshots = get_output_frame_snapshots(&output_frames, &new_win_size);
+    let snapshot_before_quit =
+        get_next_to_last_snapshot(snapshots).expect("could not find snapshot");
+    assert_snapshot!(snapshot_before_quit);
+}
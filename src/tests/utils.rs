@@ -49,7 +49,20 @@ pub fn get_next_to_last_snapshot(mut snapshots: Vec<String>) -> Option<String> {
 pub mod commands {
     pub const COMMAND_TOGGLE: [u8; 1] = [7]; // ctrl-g
     pub const QUIT: [u8; 1] = [113]; // q
+    pub const DETACH_IN_COMMAND_MODE: [u8; 1] = [100]; // d
     pub const ESC: [u8; 1] = [27];
+    pub const MOUSE_CLICK: [u8; 9] = [27, 91, 60, 48, 59, 49, 59, 49, 77]; // esc [<0;1;1M
+    /// Not actual input, makes the fake terminal send a resize signal
+    pub const SIGWINCH: [u8; 1] = [255];
+    /// Not actual input, makes a command line client send the next of the fake terminal's
+    /// requests (eg. actions)
+    pub const CLI_ACTION: [u8; 1] = [254];
+    /// Not actual input, makes what runs in the next of the fake terminals that were told to
+    /// exit (see `FakeInputOutput::add_terminal_exits`) exit
+    pub const TERMINAL_EXIT: [u8; 1] = [253];
+    /// Not actual input, makes the next of the messages of the other clients (see
+    /// `FakeInputOutput::add_other_client_messages`) reach the server
+    pub const OTHER_CLIENT: [u8; 1] = [252];
 
     pub const PANE_MODE: [u8; 1] = [112]; // p
     pub const SPAWN_TERMINAL_IN_PANE_MODE: [u8; 1] = [110]; // n
@@ -78,4 +91,7 @@ pub mod commands {
     pub const SWITCH_NEXT_TAB_IN_TAB_MODE: [u8; 1] = [108]; // l
     pub const SWITCH_PREV_TAB_IN_TAB_MODE: [u8; 1] = [104]; // h
     pub const CLOSE_TAB_IN_TAB_MODE: [u8; 1] = [120]; // x
+
+    pub const RERUN_HELD_PANE: [u8; 1] = [13]; // enter
+    pub const CLOSE_HELD_PANE: [u8; 1] = [120]; // x
 }
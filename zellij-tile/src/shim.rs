@@ -1,5 +1,8 @@
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{io, path::Path};
+use serde::{
+    de::{self, DeserializeOwned, Deserializer, Visitor},
+    Deserialize, Serialize, Serializer,
+};
+use std::{collections::HashMap, fmt, io, path::Path};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Key {
@@ -28,10 +31,14 @@ pub enum Key {
 pub struct Help {
     pub mode: InputMode,
     pub keybinds: Vec<(String, String)>,
+    pub pending_keys: Vec<String>,
+    pub readers: usize,
+    pub writers: usize,
 }
 
 // TODO: use same struct from main crate?
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Sent by its name, which is how modes defined in the config file come through as `Custom`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum InputMode {
     Normal,
     Command,
@@ -40,6 +47,59 @@ pub enum InputMode {
     Tab,
     Scroll,
     Exiting,
+    /// A mode defined in the config file, by its name.
+    Custom(String),
+}
+
+impl InputMode {
+    /// Returns the name the mode is sent by.
+    pub fn name(&self) -> &str {
+        match self {
+            InputMode::Normal => "Normal",
+            InputMode::Command => "Command",
+            InputMode::Resize => "Resize",
+            InputMode::Pane => "Pane",
+            InputMode::Tab => "Tab",
+            InputMode::Scroll => "Scroll",
+            InputMode::Exiting => "Exiting",
+            InputMode::Custom(name) => name,
+        }
+    }
+}
+
+impl Serialize for InputMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for InputMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InputModeVisitor;
+
+        impl<'de> Visitor<'de> for InputModeVisitor {
+            type Value = InputMode;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("the name of an input mode")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<InputMode, E> {
+                Ok(match s {
+                    "Normal" => InputMode::Normal,
+                    "Command" => InputMode::Command,
+                    "Resize" => InputMode::Resize,
+                    "Pane" => InputMode::Pane,
+                    "Tab" => InputMode::Tab,
+                    "Scroll" => InputMode::Scroll,
+                    "Exiting" => InputMode::Exiting,
+                    name => InputMode::Custom(name.to_string()),
+                })
+            }
+        }
+
+        deserializer.deserialize_str(InputModeVisitor)
+    }
 }
 
 impl Default for InputMode {
@@ -48,6 +108,85 @@ impl Default for InputMode {
     }
 }
 
+// TODO: use same struct from main crate?
+/// The look of the UI, from the `theme` section of the config file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Theme {
+    pub fg: PaletteColor,
+    pub bg: PaletteColor,
+    pub accent: PaletteColor,
+    pub focused_border: PaletteColor,
+    pub inactive_border: PaletteColor,
+    pub border_style: BorderStyle,
+    pub modes: HashMap<InputMode, PaletteColor>,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        let mut modes = HashMap::new();
+        modes.insert(InputMode::Normal, PaletteColor::Index(2));
+        Theme {
+            fg: PaletteColor::Index(7),
+            bg: PaletteColor::Index(0),
+            accent: PaletteColor::Index(5),
+            focused_border: PaletteColor::Index(2),
+            inactive_border: PaletteColor::Default,
+            border_style: BorderStyle::Single,
+            modes,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the color of `mode` in the status bar.
+    pub fn mode_color(&self, mode: &InputMode) -> PaletteColor {
+        self.modes.get(mode).copied().unwrap_or(self.accent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderStyle {
+    Single,
+    Rounded,
+    Double,
+    Heavy,
+    Ascii,
+    Blank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PaletteColor {
+    /// The default foreground or background color of the terminal.
+    Default,
+    /// One of the 256 indexed colors of the terminal, the first 8 of which are black, red,
+    /// green, yellow, blue, magenta, cyan and white.
+    Index(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl PaletteColor {
+    /// Returns the escape sequence that makes this the foreground color.
+    pub fn fg(self) -> String {
+        match self {
+            PaletteColor::Default => "\u{1b}[39m".to_string(),
+            PaletteColor::Index(index) if index < 8 => format!("\u{1b}[{}m", 30 + index),
+            PaletteColor::Index(index) => format!("\u{1b}[38;5;{}m", index),
+            PaletteColor::Rgb(r, g, b) => format!("\u{1b}[38;2;{};{};{}m", r, g, b),
+        }
+    }
+
+    /// Returns the escape sequence that makes this the background color.
+    pub fn bg(self) -> String {
+        match self {
+            PaletteColor::Default => "\u{1b}[49m".to_string(),
+            PaletteColor::Index(index) if index < 8 => format!("\u{1b}[{}m", 40 + index),
+            PaletteColor::Index(index) => format!("\u{1b}[48;5;{}m", index),
+            PaletteColor::Rgb(r, g, b) => format!("\u{1b}[48;2;{};{};{}m", r, g, b),
+        }
+    }
+}
+
 pub fn get_key() -> Key {
     deserialize_from_stdin().unwrap()
 }
@@ -76,6 +215,11 @@ pub fn get_help() -> Help {
     deserialize_from_stdin().unwrap_or_default()
 }
 
+pub fn get_theme() -> Theme {
+    unsafe { host_get_theme() };
+    deserialize_from_stdin().unwrap_or_default()
+}
+
 fn deserialize_from_stdin<T: DeserializeOwned>() -> Option<T> {
     let mut json = String::new();
     io::stdin().read_line(&mut json).unwrap();
@@ -89,4 +233,5 @@ extern "C" {
     fn host_set_selectable(selectable: i32);
     fn host_set_invisible_borders(invisible_borders: i32);
     fn host_get_help();
+    fn host_get_theme();
 }